@@ -0,0 +1,86 @@
+"""Benchmark the handoff of the latest frame from the capture thread to concurrent readers.
+
+Readers acquire the latest frame without blocking the capture thread, so the number of frames the
+capture stores should not drop as more threads read frames at full speed. Run with
+
+    python benchmarks/frame_handoff.py --threads 1 2 4 8 --duration 5
+
+The handoff alone is measured against the previous ``Mutex<Option<Frame>>`` by the ignored
+``frame_slot_handoff_benchmark`` test in ``src/capture_utils.rs``. Baseline on a single-core Linux
+machine, ``cargo test --release frame_slot_handoff -- --ignored --nocapture``:
+
+    readers  handoff      published/s     reads/s
+          1  slot             1279770    12316600
+          1  mutex            3746814    24366500
+          2  slot              697622    17206257
+          2  mutex            2605894    33241132
+          4  slot              309397    24500856
+          4  mutex            1853826    37690067
+          8  slot              112519    24664060
+          8  mutex            1017935    40552164
+
+Without a second core the uncontended mutex is faster, so compare on the capture machine.
+"""
+
+import argparse
+import threading
+import time
+
+import numpy as np
+
+from pixel_forge import Capture, primary_monitor
+
+
+def read_frames(capture: Capture, stop: threading.Event, latencies: list[float]):
+    """Read frames until ``stop`` is set and record the duration of each read."""
+    while not stop.is_set():
+        t0 = time.perf_counter()
+        capture.frame()
+        latencies.append(time.perf_counter() - t0)
+
+
+def run(n_threads: int, duration: float) -> dict[str, float]:
+    """Capture the primary monitor while ``n_threads`` threads read frames for ``duration`` s."""
+    capture = Capture()
+    capture.start(primary_monitor(), await_first_frame=True)
+    stop = threading.Event()
+    latencies = [[] for _ in range(n_threads)]
+    threads = [
+        threading.Thread(target=read_frames, args=(capture, stop, thread_latencies))
+        for thread_latencies in latencies
+    ]
+    produced = capture.stats().produced
+    for thread in threads:
+        thread.start()
+    time.sleep(duration)
+    stop.set()
+    for thread in threads:
+        thread.join()
+    produced = capture.stats().produced - produced
+    capture.stop()
+    all_latencies = np.concatenate([np.asarray(x) for x in latencies]) * 1000
+    return {
+        "stored_fps": produced / duration,
+        "reads_per_s": len(all_latencies) / duration,
+        "median_ms": float(np.median(all_latencies)),
+        "p99_ms": float(np.percentile(all_latencies, 99)),
+    }
+
+
+def main():
+    """Run the benchmark for each thread count and print the results."""
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--threads", type=int, nargs="+", default=[1, 2, 4, 8])
+    parser.add_argument("--duration", type=float, default=5.0)
+    args = parser.parse_args()
+    print(f"{'threads':>8} {'stored fps':>11} {'reads/s':>9} {'median ms':>10} {'p99 ms':>8}")
+    for n_threads in args.threads:
+        result = run(n_threads, args.duration)
+        print(
+            f"{n_threads:>8} {result['stored_fps']:>11.1f} {result['reads_per_s']:>9.1f} "
+            f"{result['median_ms']:>10.2f} {result['p99_ms']:>8.2f}"
+        )
+
+
+if __name__ == "__main__":
+    main()
@@ -1,10 +1,23 @@
 from __future__ import annotations
 
+import asyncio
+from typing import TYPE_CHECKING, Any, Callable
+
 import numpy as np
 
-def enumerate_windows() -> list[Window]:
+if TYPE_CHECKING:
+    from PIL import Image
+
+def enumerate_windows(skip_untitled: bool = False, z_order: bool = False) -> list[Window]:
     """Create a list of all windows that are currently available.
 
+    Args:
+        skip_untitled: Excludes windows with an empty name if True. Untitled windows are rarely
+            useful capture targets.
+        z_order: Returns the windows in z-order if True, with the topmost window first, e.g. to
+            capture the window in front of all others. The order is a snapshot that changes as
+            soon as another window is activated. If False, the order is unspecified.
+
     Returns:
         The list of all windows.
 
@@ -12,6 +25,82 @@ def enumerate_windows() -> list[Window]:
         RuntimeError: If the window enumeration fails.
     """
 
+def enumerate_windows_on_monitor(
+    monitor: Monitor, skip_untitled: bool = False, z_order: bool = False
+) -> list[Window]:
+    """Create a list of the windows that are shown on a monitor.
+
+    Windows that span several monitors belong to the monitor with the largest area of intersection,
+    see :meth:`Window.monitor`, so every window is listed for at most one monitor. Useful to group
+    the windows of a picker by their display.
+
+    Args:
+        monitor: The monitor whose windows are returned.
+        skip_untitled: Excludes windows with an empty name if True.
+        z_order: Returns the windows in z-order if True, with the topmost window first. See
+            :func:`enumerate_windows`.
+
+    Returns:
+        The list of the windows on the monitor.
+
+    Raises:
+        RuntimeError: If the window enumeration fails.
+    """
+
+def enumerate_windows_async(
+    skip_untitled: bool = False, z_order: bool = False
+) -> asyncio.Future[list[Window]]:
+    """Create a list of all windows that are currently available without blocking the event loop.
+
+    The enumeration runs in a background thread and the returned future resolves with the same list
+    as :func:`enumerate_windows`.
+
+    Note:
+        This function has to be called from within a running asyncio event loop.
+
+    Args:
+        skip_untitled: Excludes windows with an empty name if True.
+        z_order: Returns the windows in z-order if True, with the topmost window first.
+
+    Returns:
+        A future that resolves to the list of all windows.
+    """
+
+def enumerate_top_level_windows(skip_untitled: bool = False) -> list[Window]:
+    """Create a list of all top-level windows that are currently available.
+
+    Unlike :func:`enumerate_windows`, which walks all descendants of the desktop window, only
+    top-level windows are visited. Both functions apply the same validity checks and return the
+    same windows, because child windows are never valid capture targets. Windows are returned in
+    z-order, with the topmost window first.
+
+    Args:
+        skip_untitled: Excludes windows with an empty name if True. Untitled windows are rarely
+            useful capture targets.
+
+    Returns:
+        The list of all top-level windows.
+
+    Raises:
+        RuntimeError: If the window enumeration fails.
+    """
+
+def enumerate_processes_with_windows() -> list[tuple[int, str | None, int]]:
+    """Create a list of the processes that have capturable windows.
+
+    The windows of :func:`enumerate_top_level_windows` are grouped by the process that owns them,
+    which is the view a capture source picker or task manager needs. Processes are sorted by their
+    ID.
+
+    Returns:
+        A list of (pid, process_name, window_count) tuples. The process name is the file name of the
+        executable, e.g. ``"notepad.exe"``, or None if the process denies access, like protected
+        system processes do.
+
+    Raises:
+        RuntimeError: If the window enumeration fails.
+    """
+
 def foreground_window() -> Window:
     """Get the current foreground window.
 
@@ -22,26 +111,175 @@ def foreground_window() -> Window:
         RuntimeError: No foreground window was found.
     """
 
+def wallpaper_window() -> Window:
+    """Get the window that hosts the desktop wallpaper.
+
+    Capturing this window yields the desktop background without the desktop icons and without any
+    application windows on top of it.
+
+    Returns:
+        The wallpaper window.
+
+    Raises:
+        RuntimeError: If the wallpaper window can't be resolved on the current shell.
+    """
+
 class Window:
     """Window abstraction for the Windows operating system.
 
     Windows can be used as capture target for the :class:`.Capture` class.
     """
 
-    def __init__(self, name: str):
+    def __init__(self, name: str, timeout_ms: int | None = None):
         """Create a new window object.
 
         Args:
             name: The window name.
+            timeout_ms: Waits up to this many milliseconds for the window to appear, e.g. for an
+                application that was just launched. If None, the window has to exist already.
+
+        Raises:
+            RuntimeError: The window was not found within the timeout.
+        """
+
+    @staticmethod
+    def from_hwnd(handle: int) -> Window:
+        """Create a window object from a raw window handle (HWND).
+
+        This allows wrapping handles obtained from other libraries, e.g. pywin32.
+
+        Args:
+            handle: The raw window handle.
+
+        Returns:
+            The window object.
+
+        Raises:
+            RuntimeError: The handle is zero.
         """
 
     @property
     def valid(self) -> bool:
         """True if the window is still valid (i.e., open), else False."""
 
+    @property
+    def is_visible(self) -> bool:
+        """True if the window is visible, else False."""
+
+    @property
+    def is_minimized(self) -> bool:
+        """True if the window is minimized, else False."""
+
+    @property
+    def is_protected(self) -> bool:
+        """True if the window excludes itself from screen captures, else False.
+
+        Protected windows capture as black frames. Hardware protected video content, e.g. DRM
+        streams, cannot be detected this way. Use :attr:`.Capture.is_protected` to detect it.
+        """
+
+    @property
+    def is_foreground(self) -> bool:
+        """True if the window is the foreground window, else False."""
+
+    @property
+    def client_rect(self) -> tuple[int, int, int, int]:
+        """The client area of the window as (x, y, width, height).
+
+        The position is relative to the top left corner of the captured frames of the window, so it
+        excludes the title bar and borders. Child windows are captured through their top-level
+        window, so their position is relative to the frames of the top-level window. The rectangle
+        is only exact if the process is DPI aware, see :func:`set_process_dpi_aware`.
+        """
+
+    @property
+    def window_rect(self) -> tuple[int, int, int, int]:
+        """The bounds of the window as (x, y, width, height).
+
+        The bounds include the title bar and borders of the window. The position is in virtual
+        desktop coordinates, so it can be negative for windows on monitors left of or above the
+        primary monitor. Unlike :attr:`client_rect`, the rectangle includes the invisible resize
+        borders of the window. The rectangle is only exact if the process is DPI aware, see
+        :func:`set_process_dpi_aware`.
+        """
+
+    def monitor(self) -> Monitor | None:
+        """Get the monitor that has the largest area of intersection with the window.
+
+        Returns:
+            The monitor, or None if the window doesn't intersect with any monitor.
+        """
+
+    def center(self) -> tuple[int, int]:
+        """Get the center of the client area of the window in virtual desktop coordinates.
+
+        The point can be used to click into the window or to find the monitor that shows it with
+        :meth:`.Monitor.contains_point`. It is only exact if the process is DPI aware, see
+        :func:`set_process_dpi_aware`.
+
+        Returns:
+            The center as (x, y).
+
+        Raises:
+            RuntimeError: If the client area of the window can't be queried.
+        """
+
+    def child_windows(self) -> list[Window]:
+        """Enumerate the visible child windows of the window.
+
+        Some applications render their content into a child window, e.g. an embedded browser
+        control. Child windows can be passed to :meth:`.Capture.start` like any other window. They
+        are captured through their top-level window and the frames are cropped to the client area
+        of the child window.
+
+        Returns:
+            A list of all visible child windows, including nested children.
+        """
+
+    def activate(self):
+        """Bring the window to the foreground and restore it if it is minimized.
+
+        Windows restricts which processes may change the foreground window, so activation can fail
+        if another application currently holds the foreground lock.
+
+        Raises:
+            RuntimeError: If the window could not be brought to the foreground.
+        """
+
+    def on_title_change(self, callback: Callable[[str], None]) -> TitleChangeListener:
+        """Invoke a callback whenever the title of the window changes.
+
+        The callback is called with the new title from a dedicated listener thread. Use it to keep
+        labels in sync or to notice that a target found by its title has to be looked up again.
+
+        Args:
+            callback: The callable to invoke with the new title.
+
+        Returns:
+            The listener. Call :meth:`TitleChangeListener.stop` to stop listening.
+        """
+
     @property
     def name(self) -> str:
-        """The window name."""
+        """The window name. Empty if the window has no name."""
+
+    @property
+    def class_name(self) -> str:
+        """The name of the window class, e.g. to tell windows of the same process apart."""
+
+    @property
+    def process_id(self) -> int:
+        """The ID of the process that created the window, or 0 if the window was destroyed."""
+
+class TitleChangeListener:
+    """Listener for window title changes returned by :meth:`Window.on_title_change`."""
+
+    def stop(self):
+        """Stop listening for title changes."""
+
+    @property
+    def active(self) -> bool:
+        """True if the listener is running, False otherwise."""
 
 def primary_monitor() -> Monitor:
     """Get the primary monitor.
@@ -54,7 +292,27 @@ def enumerate_monitors() -> list[Monitor]:
     """Create a list of all monitors.
 
     Returns:
-        The list of all monitors.
+        The list of all monitors, ordered by their index.
+    """
+
+def monitor_count() -> int:
+    """Count the monitors connected to the system without enumerating them.
+
+    Returns:
+        The number of display monitors on the desktop.
+    """
+
+def enumerate_monitors_async() -> asyncio.Future[list[Monitor]]:
+    """Create a list of all monitors without blocking the event loop.
+
+    The enumeration runs in a background thread and the returned future resolves with the same list
+    as :func:`enumerate_monitors`.
+
+    Note:
+        This function has to be called from within a running asyncio event loop.
+
+    Returns:
+        A future that resolves to the list of all monitors, ordered by their index.
     """
 
 class Monitor:
@@ -65,8 +323,31 @@ class Monitor:
 
         Args:
             id: The monitor ID. If None, the primary monitor is selected. Monitor IDs start at 1.
+
+        Raises:
+            MonitorNotFoundError: If no monitor with the ID exists.
+        """
+
+    @staticmethod
+    def from_hmonitor(handle: int) -> Monitor:
+        """Create a monitor object from a raw monitor handle (HMONITOR).
+
+        This allows wrapping handles obtained from other libraries, e.g. pywin32.
+
+        Args:
+            handle: The raw monitor handle.
+
+        Returns:
+            The monitor object.
+
+        Raises:
+            RuntimeError: The handle is zero.
         """
 
+    @property
+    def valid(self) -> bool:
+        """True if the monitor is still connected, else False."""
+
     @property
     def width(self) -> int:
         """The monitor pixel width."""
@@ -83,6 +364,22 @@ class Monitor:
     def refresh_rate(self) -> int:
         """The monitor refresh rate in Hz."""
 
+    @property
+    def refresh_rate_precise(self) -> float:
+        """The exact refresh rate of the monitor in Hz, e.g. 59.94.
+
+        :attr:`refresh_rate` rounds fractional rates to whole numbers. This rate is taken from the
+        timing of the video signal, which makes it suitable for precise frame pacing.
+        """
+
+    @property
+    def bits_per_pixel(self) -> int:
+        """The color depth of the monitor in bits per pixel."""
+
+    @property
+    def orientation(self) -> int:
+        """The clockwise rotation of the monitor in degrees (0, 90, 180 or 270)."""
+
     @property
     def device_name(self) -> str:
         """The monitor device name."""
@@ -91,45 +388,1889 @@ class Monitor:
     def device_string(self) -> str:
         """The monitor device string."""
 
-class Capture:
-    """Capture class to capture frames from a monitor or a window.
+    @property
+    def friendly_name(self) -> str:
+        """The friendly name of the monitor from its EDID, e.g. "DELL U2720Q".
 
-    The idea is to get either a :class:`.Monitor` or a :class:`.Window` as target, create a Capture
-    object, and then start a capture thread that will update the internal frame of the Capture
-    object whenever a new frame is available. Frames are only materialized, converted to NumPy
-    arrays and passed over to Python when the user requests it to avoid unnecessary copies.
-    """
+        Unlike :attr:`device_string`, this is the name users know the monitor by. Some monitors,
+        e.g. internal laptop panels, report an empty name.
+        """
 
-    def start(self, capture_target: Monitor | Window, await_first_frame: bool = True) -> None:
-        """Start the capture.
+    @property
+    def adapter_name(self) -> str:
+        """The description of the graphics adapter (GPU) that drives the monitor."""
 
-        This registeres an event handler that automatically updates the latest frame whenever a new
-        frame is available. The frame can be accessed using :meth:`frame`. Since the event handler
-        runs in a separate thread, the first frame might not be available immediately. To ensure a
-        frame is available before continuing, set ``await_first_frame`` to True. This will block the
-        main thread until the first frame is available.
+    def contains_point(self, x: int, y: int) -> bool:
+        """Check whether a point in virtual desktop coordinates lies on the monitor.
+
+        Useful to map a click or a window position to the monitor that shows it. The right and
+        bottom edges of the monitor belong to its neighbours.
 
         Args:
-            capture_target: The monitor or window to capture.
-            await_first_frame: Waits for the first frame to arrive if True.
+            x: The horizontal coordinate of the point.
+            y: The vertical coordinate of the point.
+
+        Returns:
+            True if the point lies on the monitor, else False.
         """
 
-    def stop(self) -> None:
-        """Stop the capture thread, wait for it to join and invalidate the last frame.
+class VirtualDesktop:
+    """The virtual desktop spanning all monitors.
 
-        This method is also called automatically when the object is garbage collected.
-        """
+    VirtualDesktop can be used as capture target for the :class:`.Capture` class. Frames cover the
+    bounding box of all monitors. Monitors to the left of or above the primary monitor have negative
+    virtual desktop coordinates. They are shifted into the frame so that pixel (0, 0) of the frame
+    corresponds to the virtual desktop point (:attr:`x`, :attr:`y`). Areas of the bounding box that
+    are not covered by any monitor are black.
+    """
 
-    def frame(self) -> np.ndarray:
-        """Convert the latest frame to an array and return it.
+    def __init__(self):
+        """Create a new virtual desktop object."""
 
-        Returns:
-            The frame as a 3D NumPy array with dimensions [h w 4] (height x width x RGBA).
+    @property
+    def x(self) -> int:
+        """The left edge of the virtual desktop."""
 
-        Raises:
-            RuntimeError: If the capture thread has not yet picked up a frame.
-        """
+    @property
+    def y(self) -> int:
+        """The top edge of the virtual desktop."""
+
+    @property
+    def width(self) -> int:
+        """The pixel width of the virtual desktop."""
+
+    @property
+    def height(self) -> int:
+        """The pixel height of the virtual desktop."""
+
+class DisplayDevice:
+    """A display output of a graphics adapter, returned by :func:`enumerate_display_devices`.
+
+    Unlike :class:`.Monitor`, display devices include outputs that are not part of the desktop,
+    e.g. monitors that are connected but disabled in the display settings.
+    """
+
+    @property
+    def device_name(self) -> str:
+        r"""The device name, e.g. ``\\.\DISPLAY1``. Matches :attr:`.Monitor.device_name` of active
+        devices."""
+
+    @property
+    def adapter_string(self) -> str:
+        """The description of the graphics adapter that drives the output."""
+
+    @property
+    def monitor_string(self) -> str | None:
+        """The description of the connected monitor, or None if no monitor is known."""
+
+    @property
+    def active(self) -> bool:
+        """True if the output is part of the desktop, i.e. it is available as a Monitor."""
+
+    @property
+    def primary(self) -> bool:
+        """True if the output shows the primary monitor."""
+
+def enumerate_display_devices() -> list[DisplayDevice]:
+    """Enumerate all display outputs of the graphics adapters, including inactive ones.
+
+    Use this function to present the full display topology, e.g. in configuration dialogs. Only
+    active devices can be captured, use :func:`enumerate_monitors` to get them as capture targets.
+
+    Returns:
+        The list of all display devices.
+    """
+
+def set_process_dpi_aware() -> bool:
+    """Make the process aware of the DPI scaling of each monitor.
+
+    Processes that are not DPI-aware see scaled, virtualized coordinates on displays with a scale
+    factor other than 100%. Monitor sizes and window rectangles then do not match the pixels of the
+    captured frames. Call this function once at startup, before any windows are created. The DPI
+    awareness of a process can only be set once, so the call fails if it was already set, e.g. by
+    the application manifest or another library.
+
+    Returns:
+        True if the process is now per-monitor DPI-aware, False if the awareness could not be set.
+    """
+
+def on_display_change(callback: Callable[[], None]) -> DisplayChangeListener:
+    """Invoke a callback whenever the display configuration changes.
+
+    The callback is called without arguments from a dedicated listener thread when monitors are
+    added, removed or change their resolution. Previously created :class:`.Monitor` objects may be
+    stale afterwards, so the callback should re-enumerate the monitors with
+    :func:`enumerate_monitors`.
+
+    Args:
+        callback: The callable to invoke on display changes.
+
+    Returns:
+        The listener. Call :meth:`DisplayChangeListener.stop` to stop listening.
+    """
+
+class DisplayChangeListener:
+    """Listener for display configuration changes returned by :func:`on_display_change`."""
+
+    def stop(self):
+        """Stop listening for display changes."""
 
     @property
     def active(self) -> bool:
-        """True if the capture thread is running, False otherwise."""
+        """True if the listener is running, False otherwise."""
+
+class FeatureLevel:
+    """Direct3D feature levels that can be requested as minimum for the capture device."""
+
+    Level9_1: FeatureLevel
+    Level9_2: FeatureLevel
+    Level9_3: FeatureLevel
+    Level10_0: FeatureLevel
+    Level10_1: FeatureLevel
+    Level11_0: FeatureLevel
+    Level11_1: FeatureLevel
+
+class ColorFormat:
+    """Pixel formats in which frames can be captured.
+
+    ``Rgba8`` frames are returned as ``uint8`` arrays in the sRGB color space. HDR and wide-gamut
+    content is tone mapped to sRGB by the compositor. ``Rgba16Float`` preserves the extended range
+    of HDR content and frames are returned as ``float16`` arrays in the linear scRGB color space,
+    which has to be converted before it can be displayed as sRGB.
+    """
+
+    Rgba8: ColorFormat
+    Rgba16Float: ColorFormat
+
+class ApartmentType:
+    """COM apartment types in which the capture thread initializes the Windows Runtime.
+
+    ``Multithreaded`` works for most applications. Use ``Singlethreaded`` if the capture objects
+    have to live in a single-threaded apartment, e.g. to interoperate with COM objects that are not
+    free-threaded.
+    """
+
+    Multithreaded: ApartmentType
+    Singlethreaded: ApartmentType
+
+class ThreadPriority:
+    """Scheduling priorities of the capture thread.
+
+    Raising the priority above ``Normal`` reduces stutters of high frame rate captures when the
+    system is under load. ``TimeCritical`` can starve other threads of the process and should be
+    used with care.
+    """
+
+    Lowest: ThreadPriority
+    BelowNormal: ThreadPriority
+    Normal: ThreadPriority
+    AboveNormal: ThreadPriority
+    Highest: ThreadPriority
+    TimeCritical: ThreadPriority
+
+class ResizePolicy:
+    """Policies for window targets that are resized while they are captured.
+
+    ``Recreate`` resizes the frames along with the window. The frame that reveals the new size is
+    dropped while the frame buffers are recreated. ``Fixed`` keeps the frames at the size the
+    capture started with, so recordings keep constant dimensions. Content of a larger window is
+    clipped at the right and bottom edges, and a smaller window leaves the rest of the frame
+    unspecified. ``Error`` ends the capture instead and sets :attr:`.Capture.resized`. The target
+    is not reported as closed, and :meth:`.Capture.try_stop` raises the error.
+    """
+
+    Recreate: ResizePolicy
+    Fixed: ResizePolicy
+    Error: ResizePolicy
+
+class CaptureStats:
+    """Frame statistics of a capture since the last call of :meth:`.Capture.start`.
+
+    A frame counts as dropped if it was replaced by a newer frame before it was read.
+    """
+
+    @property
+    def produced(self) -> int:
+        """The number of frames stored by the capture thread."""
+
+    @property
+    def consumed(self) -> int:
+        """The number of distinct frames that have been read."""
+
+    @property
+    def dropped(self) -> int:
+        """The number of frames that were overwritten before they were read."""
+
+def capture_size(
+    capture_target: Monitor | Window | VirtualDesktop | CaptureItem,
+) -> tuple[int, int]:
+    """Get the pixel size of the frames of a capture target without starting a capture.
+
+    Args:
+        capture_target: The monitor, window or virtual desktop.
+
+    Returns:
+        The frame size as (width, height).
+
+    Raises:
+        RuntimeError: If the size of the target cannot be determined.
+    """
+
+def supported_formats(
+    capture_target: Monitor | Window | VirtualDesktop | CaptureItem,
+    adapter_index: int | None = None,
+) -> list[ColorFormat]:
+    """Get the color formats that a capture of the target can use, without starting a capture.
+
+    Creates the device that :meth:`Capture.start` would create with the same ``adapter_index``
+    and queries it with ``CheckFormatSupport``. Use the result to choose the ``color_format``
+    before starting the capture.
+
+    Args:
+        capture_target: The monitor, window or virtual desktop.
+        adapter_index: The index of the graphics adapter. If None, the default adapter is used.
+
+    Returns:
+        The supported color formats in the order of :class:`ColorFormat`.
+
+    Raises:
+        RuntimeError: If the target is no longer valid or the device cannot be created.
+    """
+
+class CaptureItem:
+    """A capture target selected by the user in the system picker, see :func:`pick_capture_target`.
+
+    Capture items can be passed to :meth:`.Capture.start` like monitors and windows.
+    """
+
+    @property
+    def display_name(self) -> str:
+        """The name of the item as shown in the picker, e.g. the window title."""
+
+def pick_capture_target(owner: Window | None = None) -> CaptureItem | None:
+    """Show the system picker to let the user choose a window or monitor to capture.
+
+    The call blocks until the user confirms or cancels the picker. This is the standard way for
+    screen sharing applications to select a target without enumerating windows and monitors.
+
+    Args:
+        owner: The window that owns the picker dialog. If None, the foreground window is used.
+
+    Returns:
+        The selected capture item, or None if the user canceled the picker.
+    """
+
+def is_supported() -> bool:
+    """Check if the system supports graphics capture.
+
+    Graphics capture requires Windows 10, version 1803 or later. Use this function to check for
+    support before creating a :class:`.Capture`.
+
+    Returns:
+        True if graphics capture is supported, False otherwise.
+    """
+
+def diagnostics() -> Diagnostics:
+    """Collect information about the capture support of the system.
+
+    Attach the result to bug reports if captures fail without an apparent reason. Creating the
+    device to query the feature level and adapter takes a few milliseconds.
+
+    Returns:
+        The :class:`.Diagnostics` of the system.
+    """
+
+def windows_build() -> WindowsBuild:
+    """Query the version of Windows and the capture features it provides.
+
+    Newer capture features are only available on recent builds, e.g. hiding the cursor since
+    Windows 10, version 2004 (build 19041), hiding the border since build 20348, including
+    secondary windows since Windows 11 (build 22000) and the minimum update interval since
+    Windows 11, version 24H2 (build 26100). Apps can use the result to show which options are
+    available instead of failing when the capture starts.
+
+    Returns:
+        The :class:`.WindowsBuild` of the system.
+    """
+
+def supports_cursor_toggle() -> bool:
+    """Check if the system can exclude the cursor from captures.
+
+    Hiding the cursor requires Windows 10, version 2004 or later. Apps can use this function to
+    disable options that would not work instead of failing when the capture starts.
+
+    Returns:
+        True if the cursor capture can be toggled, False otherwise.
+    """
+
+def supports_border_toggle() -> bool:
+    """Check if the system can hide the yellow border around captured targets.
+
+    Hiding the border requires Windows 11 or Windows Server 2022 (build 20348) or later. Apps can
+    use this function to disable options that would not work instead of failing when the capture
+    starts.
+
+    Returns:
+        True if the capture border can be toggled, False otherwise.
+    """
+
+class Diagnostics:
+    """Information about the capture support of the system, returned by :func:`.diagnostics`."""
+
+    @property
+    def graphics_capture_supported(self) -> bool:
+        """True if the system supports graphics capture."""
+
+    @property
+    def feature_level(self) -> FeatureLevel | None:
+        """The highest feature level of a device on the default adapter, or None if no device could
+        be created."""
+
+    @property
+    def adapter_name(self) -> str | None:
+        """The name of the default graphics adapter, or None if no device could be created.
+
+        The software rasterizer is reported if no hardware device is available.
+        """
+
+    @property
+    def os_build(self) -> int | None:
+        """The build number of Windows, or None if it is unknown."""
+
+    @property
+    def cursor_toggle_supported(self) -> bool:
+        """True if capture sessions can hide the cursor."""
+
+    @property
+    def border_toggle_supported(self) -> bool:
+        """True if capture sessions can hide the yellow capture border."""
+
+class WindowsBuild:
+    """The version of Windows and the capture features it provides, returned by
+    :func:`.windows_build`.
+    """
+
+    @property
+    def major(self) -> int:
+        """The major version of Windows, 10 for Windows 10 and 11."""
+
+    @property
+    def minor(self) -> int:
+        """The minor version of Windows."""
+
+    @property
+    def build(self) -> int:
+        """The build number of Windows, e.g. 22631. Windows 11 starts at build 22000."""
+
+    @property
+    def graphics_capture_supported(self) -> bool:
+        """True if the system supports graphics capture."""
+
+    @property
+    def cursor_toggle_supported(self) -> bool:
+        """True if capture sessions can hide the cursor."""
+
+    @property
+    def border_toggle_supported(self) -> bool:
+        """True if capture sessions can hide the yellow capture border."""
+
+    @property
+    def min_update_interval_supported(self) -> bool:
+        """True if capture sessions support a minimum update interval."""
+
+    @property
+    def secondary_windows_supported(self) -> bool:
+        """True if capture sessions can include the secondary windows of window targets."""
+
+def grab(
+    capture_target: Monitor | Window | VirtualDesktop | CaptureItem, timeout_ms: int | None = None
+) -> np.ndarray:
+    """Capture a single frame of a capture target.
+
+    Starts a capture, waits for the first frame, converts it to an array and stops the capture
+    again. Use a :class:`.Capture` instead if you need more than one frame.
+
+    Args:
+        capture_target: The monitor, window or virtual desktop.
+        timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely if
+            None.
+
+    Returns:
+        The frame as a 3D NumPy array with dimensions [h w 4].
+
+    Raises:
+        RuntimeError: If the capture fails or no frame arrives before the timeout.
+    """
+
+def grab_monitors(timeout_ms: int | None = None) -> list[tuple[Monitor, np.ndarray]]:
+    """Capture a single frame of each connected monitor.
+
+    Starts one capture per monitor, each on its own thread, waits for the first frames, converts
+    them to arrays and stops the captures again. The monitors are captured in parallel, so the call
+    takes about as long as the slowest monitor instead of the sum of all monitors.
+
+    Args:
+        timeout_ms: The maximum time to wait for the frames in milliseconds. Waits indefinitely if
+            None.
+
+    Returns:
+        A list of the monitors and their frames as 3D NumPy arrays with dimensions [h w 4], in the
+        order of :func:`enumerate_monitors`.
+
+    Raises:
+        RuntimeError: If the capture of any monitor fails. The error lists the failures of all
+            monitors.
+    """
+
+def screenshot_foreground(timeout_ms: int | None = None) -> np.ndarray:
+    """Capture a single frame of the foreground window.
+
+    Resolves the window with :func:`foreground_window` and captures it with :func:`grab`, so the
+    capture thread is stopped again whether or not a frame arrives.
+
+    Args:
+        timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely if
+            None.
+
+    Returns:
+        The frame as a 3D NumPy array with dimensions [h w 4].
+
+    Raises:
+        WindowNotFoundError: No window is in the foreground.
+    """
+
+def capture_when_available(title_or_class: str, timeout_ms: int) -> Capture:
+    """Wait for a window to appear and start capturing it.
+
+    Useful for capturing an application that was just launched. The window is matched by its
+    title first and by its class name if no window has that title. The capture is started with the
+    default options of :meth:`.Capture.start`. Use :class:`.Window` with a timeout and start the
+    capture yourself if you need other options.
+
+    Args:
+        title_or_class: The title or the class name of the window.
+        timeout_ms: The maximum time to wait for the window in milliseconds.
+
+    Returns:
+        The running capture of the window.
+
+    Raises:
+        WindowNotFoundError: No matching window appeared before the timeout.
+    """
+
+class Capture:
+    """Capture class to capture frames from a monitor or a window.
+
+    The idea is to get either a :class:`.Monitor` or a :class:`.Window` as target, create a Capture
+    object, and then start a capture thread that will update the internal frame of the Capture
+    object whenever a new frame is available. Frames are only materialized, converted to NumPy
+    arrays and passed over to Python when the user requests it to avoid unnecessary copies.
+    """
+
+    def __init__(
+        self, capture_target: Monitor | Window | VirtualDesktop | CaptureItem | None = None
+    ):
+        """Create a new capture.
+
+        Args:
+            capture_target: The default target that :meth:`start` captures if it is called
+                without a target.
+        """
+
+    @staticmethod
+    def with_device(
+        device: int, capture_target: Monitor | Window | VirtualDesktop | None = None
+    ) -> Capture:
+        """Create a capture that runs on an existing DirectX device.
+
+        Applications that already render with DirectX can share their device with the capture to
+        avoid copying frame textures between devices, e.g. if the captured content is rendered
+        again into the swapchain of the application. The ``min_feature_level``,
+        ``software_fallback``, ``adapter_index`` and ``device_flags`` options of :meth:`start` are
+        ignored for this capture.
+
+        .. note::
+           Direct3D 11 devices are free-threaded COM objects, so the device does not have to be
+           created in the COM apartment that the capture thread joins (see ``apartment_type`` of
+           :meth:`start`). The swapchain itself stays bound to the window thread of the
+           application and is never touched by the capture.
+
+        .. warning::
+           The immediate context of the device is used from the capture thread and from the thread
+           that reads the frames. Direct3D 11 immediate contexts are not thread-safe, so the
+           application must not use the context concurrently unless it enabled multithread
+           protection with ``ID3D11Multithread::SetMultithreadProtected``. The device must have
+           been created with ``D3D11_CREATE_DEVICE_BGRA_SUPPORT``.
+
+        Args:
+            device: The address of a COM interface of the device, i.e. its ``ID3D11Device`` or the
+                ``IDXGIDevice`` that a swapchain was created with. The capture holds its own
+                reference to the device, so the application may release its reference afterwards.
+            capture_target: The default target that :meth:`start` captures if it is called
+                without a target.
+        """
+
+    @staticmethod
+    def snapshot(
+        capture_target: Monitor | Window | VirtualDesktop | CaptureItem,
+        timeout_ms: int | None = None,
+    ) -> np.ndarray:
+        """Take a single screenshot of a capture target.
+
+        Unlike :meth:`frame`, no running capture is required. A short-lived capture is started, the
+        first frame is copied into an array and the capture is stopped again, so no thread or
+        DirectX resources remain afterwards.
+
+        Args:
+            capture_target: The monitor, window or virtual desktop to capture.
+            timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely
+                if None.
+
+        Returns:
+            The frame as a 3D NumPy array with dimensions [h w 4].
+
+        Raises:
+            RuntimeError: If the capture fails or no frame arrives before the timeout.
+        """
+
+    def start(
+        self,
+        capture_target: (
+            Monitor | Window | VirtualDesktop | CaptureItem | CaptureConfig | None
+        ) = None,
+        await_first_frame: bool = True,
+        max_fps: int | None = None,
+        restore_minimized: bool = False,
+        min_feature_level: FeatureLevel = ...,
+        software_fallback: bool = False,
+        color_format: ColorFormat = ...,
+        buffer_count: int = 1,
+        adapter_index: int | None = None,
+        flip_vertical: bool = False,
+        flip_horizontal: bool = False,
+        scale: float | None = None,
+        min_update_interval_ms: int | None = None,
+        apartment_type: ApartmentType = ...,
+        client_region: tuple[int, int, int, int] | None = None,
+        thread_priority: ThreadPriority = ...,
+        history_size: int = 0,
+        fixed_interval_ms: int | None = None,
+        sticky_window: bool = False,
+        device_flags: int | None = None,
+        queue_depth: int = 0,
+        resize_policy: ResizePolicy = ...,
+        unpremultiply_alpha: bool = False,
+        max_size: tuple[int, int] | None = None,
+        include_secondary_windows: bool = False,
+        on_started: Callable[[], None] | None = None,
+        on_target_closed: Callable[[], None] | None = None,
+    ) -> None:
+        """Start the capture.
+
+        This registeres an event handler that automatically updates the latest frame whenever a new
+        frame is available. The frame can be accessed using :meth:`frame`. Since the event handler
+        runs in a separate thread, the first frame might not be available immediately. To ensure a
+        frame is available before continuing, set ``await_first_frame`` to True. This will block the
+        main thread until the first frame is available.
+
+        The method returns once the capture thread has created its device and started the capture
+        sessions, so errors during this setup are raised here. Errors after the setup end the
+        capture thread and are raised by :meth:`try_stop`.
+
+        The options can also be bundled in a :class:`.CaptureConfig` that is passed in place of the
+        target. Options passed as keyword arguments in addition override the options of the
+        configuration.
+
+        Args:
+            capture_target: The monitor, window or virtual desktop to capture, or a
+                :class:`.CaptureConfig` with the target and all options. If None, the target passed
+                to the constructor is used.
+            await_first_frame: Waits for the first frame to arrive if True. Raises an error if the
+                capture stops before any frame arrives, so a frame is guaranteed to be available
+                after :meth:`start` returns.
+            max_fps: The maximum number of frames per second that are stored. Frames arriving
+                faster are skipped, but the latest skipped frame is stored once the interval
+                elapsed. If None, every frame is stored, which the compositor delivers at most at
+                the refresh rate of the monitor displaying the target.
+            restore_minimized: Restores a minimized window target before starting the capture if
+                True. Otherwise, capturing a minimized window raises an error.
+            min_feature_level: The lowest :class:`.FeatureLevel` the DirectX device has to support.
+                Defaults to ``FeatureLevel.Level11_1``.
+            software_fallback: Falls back to the WARP software rasterizer if no hardware DirectX
+                device can be created if True. Useful on machines without a GPU, e.g. CI runners.
+            color_format: The :class:`.ColorFormat` of the captured frames. Use
+                ``ColorFormat.Rgba16Float`` to capture HDR content without clipping. Defaults to
+                ``ColorFormat.Rgba8``. The format is checked against the capture device before the
+                capture starts, and the error lists the formats the device supports instead.
+            buffer_count: The number of frame buffers of the capture frame pool. More buffers let
+                the compositor queue frames while a frame is being processed, at the cost of GPU
+                memory.
+            adapter_index: The index of the graphics adapter that runs the capture. On systems
+                with several GPUs, this should be the adapter that renders the target. If None,
+                the default adapter is used.
+            flip_vertical: Returns frames with their rows in reverse order, i.e. bottom-up, if
+                True.
+            flip_horizontal: Returns frames mirrored from left to right if True.
+            scale: Downscales frames on the GPU before they are copied to the CPU, which saves
+                bandwidth if only thumbnails are needed. Frames can only be halved, so the actual
+                scale is the largest power of two that does not fall below ``scale``, e.g. 0.5 for
+                a scale of 0.3. Use the shape of the frames or :attr:`width` and :attr:`height` to
+                get the output dimensions. Must be in the range (0, 1]. If None, frames are not
+                scaled.
+            min_update_interval_ms: The minimum time in milliseconds between two frames delivered
+                by the compositor, which saves the work of producing frames that would be skipped.
+                On Windows builds before 11 24H2, frames are skipped after they arrive instead. Use
+                :attr:`throttled_at_source` to check which one is used.
+            apartment_type: The :class:`.ApartmentType` in which the capture thread initializes
+                the Windows Runtime.
+            client_region: Crops the frames returned by :meth:`frame`, :meth:`read` and the other
+                frame methods to the rectangle (x, y, width, height) inside the client area of a
+                window target, e.g. to the viewport of a game without the title bar and borders.
+                Methods that return the whole frame document it. The crop follows the client area
+                if the window moves its decorations. If None, frames of child windows are cropped
+                to their client area and other frames are not cropped.
+            thread_priority: The :class:`.ThreadPriority` of the capture thread. Latency-sensitive
+                captures at high frame rates can raise it to avoid stutters under load.
+            history_size: The number of recent frames that are kept for :meth:`recent_frames`,
+                e.g. for an instant replay. Each kept frame holds a full copy of its pixels in GPU
+                memory, so a history of 60 frames of a 4K monitor takes about 2 GB.
+            fixed_interval_ms: Stores exactly one frame every ``fixed_interval_ms`` milliseconds,
+                repeating the latest content if the target did not change. This keeps the frame
+                rate of recordings steady while the screen is static. The timer sets the pace, so
+                ``max_fps`` is ignored. If None, frames are stored whenever the compositor delivers
+                them.
+            sticky_window: Keeps capturing if a window target is closed and its process opens a
+                new top-level window of the same window class within 10 seconds, e.g. a game that
+                recreates its window when switching to fullscreen. :attr:`target` returns the new
+                window and :attr:`rebind_count` counts the replacements. If False, closed windows
+                end the capture, see :attr:`closed`.
+            device_flags: The ``D3D11_CREATE_DEVICE_FLAG`` bit mask the DirectX device is created
+                with, e.g. ``0x22`` to add the debug layer (``0x2``) to the required BGRA support
+                (``0x20``). The debug layer reports invalid API usage to the debugger output and
+                requires the Graphics Tools optional feature of Windows. If None, the device is
+                created with BGRA support only.
+            queue_depth: The number of frames that are queued for :meth:`recv` in addition to the
+                latest frame, so pipelines do not miss frames that arrive while they are busy. If
+                the queue is full, the oldest frame is dropped and counted in
+                :attr:`queue_drop_count`. Each queued frame holds a full copy of its pixels in GPU
+                memory. If 0, no frames are queued.
+            resize_policy: The :class:`.ResizePolicy` for window targets that are resized during
+                the capture. ``Recreate`` follows the new size, ``Fixed`` keeps the initial frame
+                size for recordings with constant dimensions, and ``Error`` ends the capture and
+                sets :attr:`resized`.
+            unpremultiply_alpha: The compositor delivers colors premultiplied by their alpha. If
+                True, the colors are divided by the alpha when frames are copied to the CPU, so
+                semi-transparent pixels have straight alpha, see :attr:`alpha_mode`. Only
+                supported for ``ColorFormat.Rgba8``.
+            max_size: The (width, height) that the frame buffers of window and monitor targets are
+                allocated with at least. Windows that grow up to this size keep their buffers
+                instead of recreating them on every resize, and frames are cropped to the window
+                content. Only buffers of windows that outgrow the size are recreated. Use this for
+                windows that you know will be enlarged. If None, the buffers have the target size.
+            include_secondary_windows: Also captures the secondary windows of a window target,
+                e.g. popups and dialogs that packaged apps render in separate windows. Requires
+                Windows 11, see :attr:`.WindowsBuild.secondary_windows_supported`.
+            on_started: A callable that is called without arguments once the capture sessions
+                have started, before waiting for the first frame. The capture is still starting, so
+                the callable must not use it. If it raises, the capture is stopped and the error is
+                raised by :meth:`start`.
+            on_target_closed: A callable that is called without arguments from a background
+                thread when the captured window is closed or the monitor is disconnected. The
+                capture thread ends on its own in that case, so the capture is no longer
+                :attr:`active`. Errors raised by the callable are printed. Not called if the
+                capture is stopped before the target closes.
+
+        Raises:
+            RuntimeError: If the window target is minimized and ``restore_minimized`` is False, or
+                if no target was given to either the constructor or :meth:`start`, or if the
+                adapter with ``adapter_index`` does not exist, or if ``await_first_frame`` is True
+                and the capture stops before the first frame arrives, or if ``client_region`` is
+                given for a target that is not a window or exceeds its client area, or if the
+                capture is already running, or if ``fixed_interval_ms`` is 0, or if the capture
+                device does not support ``color_format``, or if ``unpremultiply_alpha`` is True for
+                a color format other than ``Rgba8``, or if ``include_secondary_windows`` is True
+                on a build without support, or if the setup of the capture thread fails.
+            TypeError: If ``on_started`` or ``on_target_closed`` is not callable.
+        """
+
+    def stop(self) -> None:
+        """Stop the capture thread, wait for it to join and invalidate the last frame.
+
+        This method is also called automatically when the object is garbage collected. Threads that
+        are blocked in :meth:`wait_for_frame` or in a :meth:`frames` iteration return None first,
+        so the capture can be stopped from another thread. Errors of the capture thread are
+        ignored, so stopping never fails. Use :meth:`try_stop` to receive them.
+        """
+
+    def try_stop(self) -> None:
+        """Stop the capture like :meth:`stop` and raise the error the capture thread exited with.
+
+        The capture is stopped and its resources are released even if an error is raised.
+
+        Raises:
+            RuntimeError: If the capture thread failed, e.g. while releasing the capture session
+                of a target that was destroyed.
+        """
+
+    def start_recording(self, path: str, fps: int = 30) -> None:
+        """Start encoding the captured frames into an H.264 MP4 file.
+
+        Frames are encoded on the GPU as they arrive, so recording does not require reading frames.
+        The video has the size of the latest frame, rounded down to even dimensions. Frames that
+        arrive after the target was resized are skipped. Recording requires the ``rgba8`` color
+        format. The recording is finished by :meth:`stop_recording` or :meth:`stop`.
+
+        Args:
+            path: The path of the MP4 file. Existing files are overwritten.
+            fps: The frame rate stored in the file.
+
+        Raises:
+            RuntimeError: If no frame has arrived yet, if a recording is already running, if the
+                color format is not ``rgba8``, or if the encoder cannot be created.
+        """
+
+    def stop_recording(self) -> None:
+        """Finish the running recording and close its file.
+
+        Raises:
+            RuntimeError: If no recording is running, or if encoding any of the frames failed.
+        """
+
+    def start_pipe(self, path: str, fps: int = 30) -> None:
+        """Start streaming the captured frames as raw video to a file, a named pipe or stdout.
+
+        Frames are copied and written on the capture thread without involving Python. Each frame
+        is written as ``height`` rows of ``width`` pixels, top row first, with the four bytes R, G,
+        B and A per pixel and without any padding, headers or separators. ``client_region`` and
+        flips are not applied. The stream can be read with ``ffmpeg -f rawvideo -pixel_format rgba
+        -video_size {width}x{height} -framerate {fps} -i {path}``.
+
+        Frames are repeated or dropped according to their timestamps, so the stream has exactly
+        ``fps`` frames per second of capture time. Frames that arrive after the target was resized
+        are skipped. A slow reader delays the capture thread. The pipe is stopped by
+        :meth:`stop_pipe` or :meth:`stop`.
+
+        Args:
+            path: The path of the file or of an existing named pipe, e.g. ``\\\\.\\pipe\\frames``,
+                or ``"-"`` for stdout. Existing files are overwritten.
+            fps: The frame rate of the stream.
+
+        Raises:
+            FrameUnavailableError: If no frame has arrived yet.
+            PipeError: If a pipe is already running, if ``fps`` is zero, if the color format is not
+                ``rgba8``, or if the output cannot be opened.
+        """
+
+    def stop_pipe(self) -> None:
+        """Stop the running pipe and flush its output.
+
+        Raises:
+            PipeError: If no pipe is running, or if writing any of the frames failed.
+        """
+
+    def ping(self, timeout_ms: int = 1000) -> bool:
+        """Check that the message loop of the capture thread is alive and responsive.
+
+        Posts a message to the capture thread and waits for its acknowledgment. Use this to detect
+        a deadlocked capture thread in long-running captures, which :attr:`active` still reports as
+        running.
+
+        Args:
+            timeout_ms: The maximum time to wait for the acknowledgment in milliseconds.
+
+        Returns:
+            True if the capture thread responded within the timeout, else False.
+        """
+
+    def stats(self) -> CaptureStats:
+        """Return the frame statistics since the last call of :meth:`start`.
+
+        Returns:
+            The number of produced, consumed and dropped frames.
+        """
+
+    def frame(self, layout: str | None = None) -> np.ndarray:
+        """Convert the latest frame to an array and return it.
+
+        Args:
+            layout: The axis order of the array. ``"hwc"`` returns the channels of each pixel next
+                to each other with dimensions [h w 4]. ``"chw"`` returns planar channels with
+                dimensions [4 h w] as expected by many ML frameworks. The transpose happens during
+                the copy, so the array is C-contiguous in both layouts. Defaults to ``"hwc"``.
+
+        Returns:
+            The frame as a 3D NumPy array with the dimensions of ``layout`` (RGBA channels). One of
+            the buffers of :meth:`configure_double_buffer` if they are configured.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if the frame does
+                not fit the configured double buffer.
+        """
+
+    def configure_double_buffer(
+        self, shape: tuple[int, int, int] | None
+    ) -> tuple[np.ndarray, np.ndarray] | None:
+        """Let :meth:`frame` write into two preallocated arrays in turn instead of new arrays.
+
+        Each call of :meth:`frame` copies the latest frame into the array that was not returned
+        last, and returns it once the copy is complete. The array returned by the previous call is
+        left untouched, so it can be processed while the next frame is read, without allocating an
+        array per frame. Unlike :meth:`frame_into`, the arrays are managed by the capture. An array
+        is overwritten by the second call of :meth:`frame` after it was returned, so it has to be
+        processed or copied before then.
+
+        The buffers are kept across :meth:`stop` and :meth:`start`. They only hold ``Rgba8`` frames
+        with the ``"hwc"`` layout, frames with the ``"chw"`` layout are still returned in new
+        arrays. Reading fails if the frame shape differs from the buffers, e.g. after the target
+        was resized, so configure the buffers again with the new shape in that case.
+
+        Args:
+            shape: The shape (height, width, 4) of the frames, e.g. ``(capture.height,
+                capture.width, 4)``. If None, the buffers are released and :meth:`frame` returns
+                new arrays again.
+
+        Returns:
+            The two buffers, or None if they were released.
+
+        Raises:
+            RuntimeError: If the shape is empty or does not have 4 channels.
+        """
+
+    def share(self) -> CaptureHandle:
+        """Create a read-only handle to this capture for another consumer, e.g. another thread.
+
+        Handles read the frames of this capture without borrowing it, so they can wait for frames
+        while the capture is stopped or restarted from another thread. Each handle tracks the
+        frames it has returned on its own, so handles do not take new frames away from each other
+        or from :meth:`wait_for_frame` of the capture. Handles keep the capture alive, so the
+        capture thread runs until :meth:`stop` is called or the capture and all of its handles are
+        deleted.
+
+        Returns:
+            A new :class:`CaptureHandle`.
+        """
+
+    def recent_frames(self, count: int | None = None) -> list[np.ndarray]:
+        """Convert the most recent frames to arrays and return them, oldest first.
+
+        Frames are only kept if ``history_size`` was passed to :meth:`start`. The latest frame is
+        the last element. The frames are not marked as read by this method.
+
+        Args:
+            count: The maximum number of frames to return. Returns all kept frames if None.
+
+        Returns:
+            The frames as 3D NumPy arrays with dimensions [h w 4].
+
+        Raises:
+            RuntimeError: If the capture thread is not running.
+        """
+
+    def frame_averaged(self, n: int) -> np.ndarray:
+        """Average the most recent frames and return the mean as an array.
+
+        Averaging reduces noise of static content, e.g. of low-light camera previews or dithered
+        video, at the cost of latency and smearing: moving content leaves trails across the
+        averaged frames, and changes only show up fully after ``n`` new frames. The frames are
+        summed one at a time in Rust, which avoids stacking them into a large array in NumPy.
+
+        Frames are taken from the history, so ``history_size`` has to be passed to :meth:`start`.
+        Fewer frames are averaged while the history holds less than ``n`` frames. Frames with a
+        different size than the latest frame, e.g. from before the target was resized, are left
+        out. Only ``Rgba8`` frames can be averaged. The frames are not marked as read by this
+        method.
+
+        Args:
+            n: The number of recent frames to average.
+
+        Returns:
+            The rounded mean as a 3D NumPy array with dimensions [h w 4].
+
+        Raises:
+            CaptureNotRunningError: If the capture thread is not running.
+            FrameUnavailableError: If the history holds no frames.
+            PixelForgeError: If ``n`` is 0 or the frames are not in the ``Rgba8`` format.
+        """
+
+    def recv(self, timeout_ms: int | None = None) -> np.ndarray | None:
+        """Block until a queued frame is available, remove it from the queue and return it.
+
+        Frames are only queued if ``queue_depth`` was passed to :meth:`start`. Frames are received
+        in the order they arrived, and queued frames are still received after the target was
+        closed. The frames are not marked as read by this method.
+
+        Args:
+            timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+
+        Returns:
+            The oldest queued frame as a 3D NumPy array with dimensions [h w 4], or None if no
+            frame was queued within the timeout, or the queue is empty and the capture was stopped,
+            failed or the target was closed.
+
+        Raises:
+            RuntimeError: If the capture thread is not running.
+        """
+
+    def try_recv(self) -> np.ndarray | None:
+        """Remove the oldest queued frame from the queue and return it without blocking.
+
+        Returns:
+            The oldest queued frame as a 3D NumPy array with dimensions [h w 4], or None if the
+            queue is empty.
+
+        Raises:
+            RuntimeError: If the capture thread is not running.
+        """
+
+    def frame_on_monitor(self, monitor: Monitor, layout: str | None = None) -> np.ndarray:
+        """Return the part of the latest frame of a window target that is shown on ``monitor``.
+
+        Windows that span several monitors are captured as a whole. This crops the frame to the
+        intersection of the window and the monitor, e.g. to process only the half of a window on
+        the secondary display. The intersection is computed for each call so it follows the window
+        when it moves. Frames cropped to a ``client_region`` are cropped to the part of the region
+        on the monitor.
+
+        Args:
+            monitor: The :class:`.Monitor` whose part of the window is returned.
+            layout: The axis order of the array, see :meth:`frame`. Defaults to ``"hwc"``.
+
+        Returns:
+            The part of the frame on the monitor as a 3D NumPy array.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, if the target is not
+                a window, or if the window does not intersect with the monitor.
+        """
+
+    def frame_at_size(
+        self,
+        size: tuple[int, int],
+        preserve_aspect: bool = False,
+        pad_color: tuple[int, int, int, int] = (0, 0, 0, 255),
+    ) -> np.ndarray:
+        """Resize the latest frame to a fixed size and return it as an array.
+
+        Useful for ML models with a fixed input size. The frame is resampled with nearest neighbor
+        interpolation while it is copied out, so no full size array is allocated in between. Only
+        ``Rgba8`` frames can be resized.
+
+        Args:
+            size: The (width, height) of the returned array.
+            preserve_aspect: If True, the frame is scaled to fit into ``size`` without distortion
+                and centered, and the remaining bars are filled with ``pad_color``. If False, the
+                frame is stretched to ``size``.
+            pad_color: The RGBA color of the letterbox bars.
+
+        Returns:
+            The frame as a 3D NumPy array with dimensions [height width 4].
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, if ``size`` is
+                empty, or if the frames are not in the ``Rgba8`` format.
+        """
+
+    def desktop_to_frame(self, x: int, y: int) -> tuple[int, int] | None:
+        """Convert a virtual desktop point to the pixel of the frames of a monitor capture.
+
+        Pixel (0, 0) of a monitor capture is the top left corner of the monitor, while window
+        positions, cursor positions and most automation tools use virtual desktop coordinates. The
+        conversion accounts for the position of the monitor, the ``scale`` option and flips.
+        Processes that are not DPI aware see scaled desktop coordinates, which are converted to the
+        physical pixels of the frames as well.
+
+        Args:
+            x: The horizontal position on the virtual desktop.
+            y: The vertical position on the virtual desktop.
+
+        Returns:
+            The (column, row) of the pixel in the frame arrays, or None if the point is not on the
+            monitor.
+
+        Raises:
+            RuntimeError: If the capture is not running, if no frame is available yet, or if the
+                target is not a monitor.
+        """
+
+    def frame_pil(self, alpha: bool = True) -> Image.Image:
+        """Return the latest frame as a Pillow image.
+
+        The image is created from the frame data directly, so no NumPy array is allocated in
+        between. Only ``Rgba8`` frames can be converted, since Pillow has no mode for 16 bit float
+        channels. Requires Pillow to be installed.
+
+        Args:
+            alpha: Returns an ``RGBA`` image if True, or an ``RGB`` image without the alpha channel
+                if False.
+
+        Returns:
+            The frame as a ``PIL.Image.Image``.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if the frames are
+                not ``Rgba8``.
+            ImportError: If Pillow is not installed.
+        """
+
+    def frame_alpha(self) -> np.ndarray:
+        """Extract the alpha channel of the latest frame and return it.
+
+        The channel is extracted without creating the RGBA array, so this is cheaper than
+        slicing the array of :meth:`frame`, e.g. to build a hit-test mask of a window with
+        transparent regions. Opaque sources such as monitors and most windows have a uniform alpha
+        of 255. Only ``Rgba8`` frames are supported.
+
+        Returns:
+            The alpha channel as a 2D ``uint8`` NumPy array with dimensions [h w].
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if the frames
+                are not in the ``Rgba8`` format.
+        """
+
+    def frame_rgb565(self) -> np.ndarray:
+        """Convert the latest frame to a 16 bit RGB565 array and return it.
+
+        Each pixel is packed into a ``uint16`` with 5 bits of red in the high bits, 6 bits of green
+        and 5 bits of blue in the low bits, i.e. ``(r >> 3) << 11 | (g >> 2) << 5 | b >> 3``. The
+        alpha channel is dropped. The array takes half the bytes of :meth:`frame`, which helps when
+        frames are streamed over a network. Only ``Rgba8`` frames can be converted.
+
+        Returns:
+            The frame as a 2D ``uint16`` NumPy array with dimensions [h w].
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if the frames
+                are not in the ``Rgba8`` format.
+        """
+
+    def shared_texture_handle(self) -> int:
+        """Copy the latest frame into a shareable GPU texture and return its shared handle.
+
+        The handle lets other Direct3D devices, e.g. of a rendering engine or a CUDA interop
+        pipeline, open the frame with ``ID3D11Device::OpenSharedResource`` without copying it to
+        the CPU. The texture has the size of the full frame and the DXGI format of the capture's
+        ``color_format``. ``client_region`` and flips are not applied.
+
+        The handle is a legacy DXGI shared handle, not an NT handle, so it must not be closed. The
+        capture keeps the texture alive until the next call of this method or until the capture is
+        stopped, after which the handle becomes invalid. Resources opened from the handle stay
+        valid on their own. The copy is submitted before the handle is returned, but consumers on
+        other devices are responsible for any further synchronization.
+
+        Returns:
+            The shared handle of the texture as an integer.
+
+        Raises:
+            CaptureNotRunningError: If the capture is not running.
+            FrameUnavailableError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_into(self, out: np.ndarray) -> None:
+        """Copy the latest frame into an existing array.
+
+        Reusing the same array for every frame avoids allocating a new array per frame, which
+        reduces garbage collection churn in high frame rate loops. Only ``Rgba8`` frames can be
+        copied, since the array must have dtype uint8.
+
+        Args:
+            out: A writeable uint8 array with the dimensions [h w 4] of the frame. Frames change
+                their dimensions if the target is resized, so check :attr:`width` and
+                :attr:`height` and reallocate the array if the copy fails.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if the shape of
+                ``out`` does not match the frame, or if the frames are not ``Rgba8``.
+        """
+
+    def read(self, auto_crop: bool = False, tolerance: int = 16) -> FrameData:
+        """Convert the latest frame to an array and return it together with its metadata.
+
+        The array and the metadata are taken from the same acquired frame, so they are always
+        consistent, unlike separate calls of :meth:`frame` and the properties.
+
+        Args:
+            auto_crop: Crop uniform black borders from the array, e.g. the letterbox bars of
+                fullscreen games. The borders are detected for each frame, and the region of the
+                frame inside the borders is returned as :attr:`.FrameData.crop`. Frames that are
+                black as a whole are not cropped. Only supported for ``Rgba8`` frames.
+            tolerance: The largest color value of a pixel in the range [0, 255] that still counts
+                as black, so near-black bars of compressed video are cropped as well.
+
+        Returns:
+            The frame array, timestamp, frame ID, width and height.
+
+        Raises:
+            RuntimeError: If the capture is not running or no frame is available, or if
+                ``auto_crop`` is set for frames that are not ``Rgba8``.
+        """
+
+    def frame_contiguous(self) -> np.ndarray:
+        """Convert the latest frame to a tightly packed array and return it.
+
+        The frame is copied row by row, so the array is C-contiguous and never contains the row
+        padding of the underlying texture. Use this for libraries that cannot handle strided data.
+
+        Returns:
+            The frame as a C-contiguous 3D NumPy array with dimensions [h w 4].
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def wait_for_frame(self, timeout_ms: int | None = None) -> np.ndarray | None:
+        """Block until a new frame is available and return it.
+
+        A frame is new if it has not been returned by :meth:`frame` or any other frame method yet.
+        If such a frame is already available, it is returned immediately.
+
+        Args:
+            timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+
+        Returns:
+            The frame as a 3D NumPy array with dimensions [h w 4], or None if no new frame arrived
+            within the timeout, the capture was stopped or failed or the target was closed.
+
+        Raises:
+            RuntimeError: If the capture thread is not running.
+        """
+
+    def frames(self, timeout_ms: int | None = None) -> FrameIterator:
+        """Iterate over new frames as they arrive.
+
+        Each iteration blocks until a new frame is available, see :meth:`wait_for_frame`. The
+        iteration stops once the capture is stopped or no new frame arrives within the timeout.
+
+        Args:
+            timeout_ms: The maximum time to wait for each frame in milliseconds. Waits indefinitely
+                if None.
+
+        Returns:
+            An iterator over the frames as 3D NumPy arrays with dimensions [h w 4].
+        """
+
+    def frame_bytes(self) -> tuple[bytes, int, int, int]:
+        """Copy the latest frame into a bytes object.
+
+        The bytes contain the RGBA pixels row by row. Rows are ``stride`` bytes apart, which can be
+        more than ``4 * width`` because of padding at the end of each row. Frames that are cropped
+        to a client region or a child window are packed without padding. Flips are not applied.
+
+        Returns:
+            The tuple (data, width, height, stride).
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_info(self) -> tuple[int, int, int, int]:
+        """Return the memory layout of the latest frame.
+
+        The stride is the number of bytes between the starts of two rows. It can be larger than the
+        width times the pixel size because rows may be padded for alignment. The frame is not marked
+        as read by this method. The layout is the one of the whole mapped frame, so
+        ``client_region`` and the crop of child windows are not applied.
+
+        Returns:
+            The tuple (width, height, stride, channels).
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_hash(self) -> int:
+        """Return a hash of the pixels of the latest frame.
+
+        The hash covers every pixel (FNV-1a over the frame bytes without row padding), so any pixel
+        change produces a different hash with high probability. Hashes are stable across runs and
+        can be used to skip identical frames without copying them into an array. The frame is not
+        marked as read by this method. The hash covers the whole frame, so ``client_region`` and the
+        crop of child windows are not applied.
+
+        Returns:
+            The 64-bit hash of the latest frame.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frames_differ(self, threshold: float = 0.01, downsample: int = 1) -> bool:
+        """Check whether the latest frame differs from the frame of the previous call.
+
+        The pixels are compared in Rust, so motion-triggered recordings do not have to copy two
+        arrays into Python and diff them there. The first call after :meth:`start` has no frame to
+        compare with and returns True, as do calls after the frame size changed. The frame is not
+        marked as read by this method. The whole frame is compared, so ``client_region`` and the
+        crop of child windows are not applied.
+
+        Args:
+            threshold: The fraction of pixels in the range [0, 1] that has to be exceeded for the
+                frames to differ. A pixel changes if any of its channels changes.
+            downsample: Compares only every ``downsample``-th pixel of every ``downsample``-th
+                row, which speeds up the comparison of large frames. Changes between the compared
+                pixels go unnoticed.
+
+        Returns:
+            True if the fraction of changed pixels exceeds ``threshold``.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame, or if
+                ``threshold`` or ``downsample`` is out of range.
+        """
+
+    def frame_age_ms(self) -> float:
+        """Return the time that has passed since the latest frame was rendered.
+
+        Real-time applications can use the age to detect that they are falling behind and should
+        drop work.
+
+        Returns:
+            The age of the latest frame in milliseconds.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_view(self) -> FrameView:
+        """Map the latest frame into memory and return a read-only view on its pixels.
+
+        Unlike :meth:`frame`, the pixels are not copied into a new array. The view supports the
+        buffer protocol, so ``np.asarray(view)`` returns an array with dimensions [h w 4] that
+        directly references the mapped frame memory. See :class:`.FrameView` for lifetime details.
+        The view covers the whole mapped frame, so ``client_region``, the crop of child windows and
+        flips are not applied.
+
+        Returns:
+            The view on the latest frame.
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_array_view(self) -> np.ndarray:
+        """Map the latest frame into memory and return an array that directly references it.
+
+        This is a shortcut for ``np.asarray(capture.frame_view())``. The array keeps its
+        :class:`.FrameView` alive as ``base``, which keeps the frame mapped until the array is
+        garbage collected. No pixels are copied. Like the view, the array covers the whole frame
+        without ``client_region``, the crop of child windows and flips.
+
+        Warning:
+            The array is read-only and does not change when new frames arrive. Holding on to
+            arrays of old frames keeps their staging textures on the GPU alive, so copy the pixels
+            if you need to keep them.
+
+        Returns:
+            The frame as a read-only 3D NumPy array with dimensions [h w 4].
+
+        Raises:
+            RuntimeError: If the capture thread has not yet picked up a frame.
+        """
+
+    def frame_async(self) -> asyncio.Future[np.ndarray]:
+        """Wait for the next frame without blocking the event loop.
+
+        The returned future resolves with the first frame that arrives after the call. Waiting
+        happens in a background thread, so the asyncio event loop stays responsive. Cancelling the
+        future stops the background wait.
+
+        Note:
+            This method has to be called from within a running asyncio event loop.
+
+        Returns:
+            A future that resolves to the frame as a 3D NumPy array with dimensions [h w 4].
+
+        Raises:
+            RuntimeError: If the capture is not running, or if it is stopped before a new frame
+                arrives.
+        """
+
+    @property
+    def active(self) -> bool:
+        """True if the capture thread is running, False otherwise.
+
+        The thread ends on its own if the target is closed or the capture fails.
+        """
+
+    @property
+    def target(self) -> Monitor | Window | VirtualDesktop | CaptureItem | None:
+        """The target of the running capture, or None if no capture is active."""
+
+    @property
+    def target_kind(self) -> str | None:
+        """The kind of the running capture's target, or None if no capture is active.
+
+        One of ``"monitor"``, ``"window"``, ``"virtual_desktop"`` or ``"item"`` for targets chosen
+        with :func:`pick_capture_target`. Cheaper than :attr:`target` if you only need to branch on
+        the kind, e.g. for logging.
+        """
+
+    @property
+    def recording(self) -> bool:
+        """True if a recording is running, False otherwise."""
+
+    @property
+    def piping(self) -> bool:
+        """True if a pipe is running, False otherwise."""
+
+    @property
+    def throttled_at_source(self) -> bool:
+        """True if the compositor applies ``min_update_interval_ms`` of the running capture.
+
+        False if no interval was set or if the Windows build does not support it, in which case
+        frames are skipped after they arrive. The value is set before the first frame arrives.
+        """
+
+    @property
+    def stale_frame_threshold_ms(self) -> float | None:
+        """The frame age in milliseconds above which :meth:`frame` and :meth:`read` emit a
+        ``RuntimeWarning``, or None to disable the warning.
+
+        Reading repeatedly returns the same frame if the capture stops delivering new ones, e.g.
+        because the target froze. The warning makes this visible. Frames only arrive when the
+        content of the target changes, so static content also ages. Disabled by default.
+        """
+
+    @stale_frame_threshold_ms.setter
+    def stale_frame_threshold_ms(self, threshold_ms: float | None): ...
+
+    @property
+    def channel_order(self) -> str:
+        """The order of the channels along the last axis of the frame arrays, e.g. ``"RGBA"``.
+
+        The order follows the ``color_format`` of the running capture, or of the last capture if it
+        was stopped. Check it before passing frames to libraries that expect a fixed order, e.g.
+        OpenCV, which uses BGR.
+        """
+
+    @property
+    def color_space(self) -> str:
+        """The color space of the frame arrays, ``"sRGB"`` or ``"scRGB"``.
+
+        ``Rgba8`` frames use gamma encoded sRGB, into which the compositor tone maps HDR and
+        wide-gamut content. ``Rgba16Float`` frames use linear scRGB, whose values exceed the range
+        [0, 1] for HDR content. Convert them before displaying them as sRGB. The color space
+        follows the ``color_format`` of the running capture, or of the last capture if it was
+        stopped.
+        """
+
+    @property
+    def feature_level(self) -> FeatureLevel | None:
+        """The Direct3D feature level the capture device achieved.
+
+        Useful to compare capture quality across machines. Refers to the running capture, or to the
+        last capture if it was stopped. None until the capture thread has created its device, or if
+        a device supplied with :meth:`with_device` has a Direct3D 12 level.
+        """
+
+    def frame_spec(self) -> tuple[str, int]:
+        """Return the NumPy dtype and the number of channels of the frame arrays.
+
+        The spec follows the ``color_format`` of the running capture, or of the last capture if it
+        was stopped, so arrays can be allocated before a frame is materialized.
+
+        Returns:
+            The tuple (dtype, channels), e.g. ``("uint8", 4)`` or ``("float16", 4)``.
+        """
+
+    @property
+    def width(self) -> int | None:
+        """The width of the latest frame, or None if no frame is available."""
+
+    @property
+    def height(self) -> int | None:
+        """The height of the latest frame, or None if no frame is available."""
+
+    @property
+    def content_width(self) -> int | None:
+        """The width of the captured content in the latest frame, or None if no frame is available.
+
+        The content can be smaller than the frame, e.g. right after the target was resized. Pixels
+        outside of the content area do not belong to the target.
+        """
+
+    @property
+    def content_height(self) -> int | None:
+        """The height of the captured content in the latest frame, or None if no frame is
+        available.
+
+        The content can be smaller than the frame, e.g. right after the target was resized. Pixels
+        outside of the content area do not belong to the target.
+        """
+
+    @property
+    def resize_count(self) -> int:
+        """The number of times the capture target changed its size since :meth:`start`.
+
+        Frames that arrive after a size change have the new dimensions. Compare the value against a
+        previously read one to detect that the frame dimensions changed.
+        """
+
+    @property
+    def monitor_change_count(self) -> int:
+        """The number of times the captured window moved to another monitor since :meth:`start`.
+
+        Windows that move to a monitor with a different DPI change their size and scale. Compare
+        the value against a previously read one and query :meth:`.Window.monitor` to adjust DPI
+        dependent coordinate transforms. Always 0 for targets other than windows.
+        """
+
+    @property
+    def rebind_count(self) -> int:
+        """The number of times a closed window target was replaced by a new window since
+        :meth:`start`.
+
+        Only captures started with ``sticky_window=True`` replace their target. Frames of the new
+        window can have different dimensions.
+        """
+
+    @property
+    def queue_depth(self) -> int:
+        """The maximum number of queued frames of the running or last capture, see the
+        ``queue_depth`` option of :meth:`start`.
+        """
+
+    @property
+    def resize_policy(self) -> ResizePolicy:
+        """The resize policy of the running or last capture, see the ``resize_policy`` option of
+        :meth:`start`.
+        """
+
+    @property
+    def alpha_mode(self) -> str:
+        """The alpha of the frames of the running or last capture, ``"straight"`` if the
+        ``unpremultiply_alpha`` option of :meth:`start` is set and ``"premultiplied"`` otherwise.
+        """
+
+    @property
+    def queued_frames(self) -> int:
+        """The number of frames that are queued and have not been received yet."""
+
+    @property
+    def queue_drop_count(self) -> int:
+        """The number of frames dropped from the full frame queue since :meth:`start`."""
+
+    @property
+    def frame_changed(self) -> bool:
+        """True if the content of the last read frame differs from the frame read before it.
+
+        Frames also arrive if only the cursor moves or the content is redrawn without changes. Use
+        this flag to skip expensive processing of unchanged frames. The comparison samples a sparse
+        grid of pixels, so very small changes may go unnoticed. The first read frame always counts
+        as changed.
+        """
+
+    @property
+    def verify_checksums(self) -> bool:
+        """Compute the CRC-32 checksum of every frame that is read, to tell corruption during the
+        capture apart from bugs in the processing of the frames.
+
+        The checksum of the last read frame is available as :attr:`last_checksum`. Reading the same
+        frame again must produce the same checksum, otherwise :attr:`checksum_mismatches` is
+        increased. Checksums cover every pixel, so they slow down reading. Disabled by default.
+        """
+
+    @verify_checksums.setter
+    def verify_checksums(self, enabled: bool): ...
+
+    @property
+    def last_checksum(self) -> int | None:
+        """The CRC-32 checksum of the pixels of the last read frame, or None if
+        :attr:`verify_checksums` is disabled or no frame has been read since it was enabled.
+
+        The checksum equals ``zlib.crc32`` of the tightly packed pixels of the unflipped frame,
+        e.g. of the array of :meth:`frame_contiguous`, so consumers can check their copy of the
+        frame.
+        """
+
+    @property
+    def checksum_mismatches(self) -> int:
+        """The number of reads since :meth:`start` that returned a frame with the same ID as the
+        previous read but a different checksum, see :attr:`verify_checksums`.
+
+        Any mismatch indicates that frames are corrupted while they are copied from the GPU.
+        """
+
+    @property
+    def is_protected(self) -> bool:
+        """True if the target likely shows protected content that captures as black frames.
+
+        Windows can exclude themselves from captures, and DRM protected video is blanked by the
+        compositor. Both cases produce all-black frames instead of an error. The flag is set if the
+        window target is protected, or if a sparse grid of pixels of the last read frame is black.
+        Legitimately black content is therefore reported as protected as well.
+        """
+
+    @property
+    def closed(self) -> bool:
+        """True if the captured window was closed or the monitor was disconnected.
+
+        The capture thread ends on its own once the target is closed, so the capture is no longer
+        :attr:`active`. Threads that wait in :meth:`wait_for_frame` return None and iterations
+        over :meth:`frames` end instead of waiting for frames that never arrive. The flag is reset
+        by :meth:`start`, see its ``on_target_closed`` option to be notified.
+        """
+
+    @property
+    def resized(self) -> bool:
+        """True if the capture ended because its window target was resized under
+        ``ResizePolicy.Error``.
+
+        The window is still open, so :attr:`closed` stays False and the ``on_target_closed``
+        callback of :meth:`start` is not called. The capture is no longer :attr:`active`, and
+        :meth:`try_stop` raises the error. The flag is reset by :meth:`start`.
+        """
+
+    def is_receiving(self, within_ms: int = 1000) -> bool:
+        """Check whether the latest frame arrived recently.
+
+        Unlike :attr:`active`, which only reports that the capture thread is running, this
+        distinguishes a healthy capture from one whose source stopped delivering frames. The
+        compositor only delivers frames when the target changes, so static targets, e.g. an idle
+        window or a paused video, produce no frames and are reported as not receiving as well. Pass
+        a longer ``within_ms`` for targets that update rarely. Captures started with
+        ``fixed_interval_ms`` repeat the latest content with new timestamps, so they keep receiving
+        while the target is static.
+
+        Args:
+            within_ms: The maximum age of the latest frame in milliseconds.
+
+        Returns:
+            True if the latest frame arrived within the last ``within_ms`` milliseconds, else
+            False.
+        """
+
+    @property
+    def has_new_frame(self) -> bool:
+        """True if a frame has arrived that has not been returned by :meth:`frame` yet."""
+
+    @property
+    def dirty_regions(self) -> list[tuple[int, int, int, int]] | None:
+        """The regions (x, y, width, height) of the latest frame that changed since the frame
+        stored before it.
+
+        Delta encoders and remote desktop streams can use the regions to only process changed
+        pixels. Regions are given in the coordinates of the returned frames, i.e. after scaling,
+        client region cropping and flipping, and may overlap. None if the changes are unknown, which
+        is the case for the first frame, after a resize, with ``fixed_interval_ms`` and on Windows
+        builds that do not report changed regions. Treat the whole frame as changed in that case.
+        """
+
+class CaptureConfig:
+    """The options of :meth:`.Capture.start` bundled in one object.
+
+    Each attribute is the option of the same name of :meth:`.Capture.start`, which also documents
+    their effects. Configurations can be created with keyword arguments, changed attribute by
+    attribute or built with chained calls of :meth:`set`, and are passed to :meth:`.Capture.start`
+    in place of the target:
+
+    ``capture.start(CaptureConfig(max_fps=30).set(color_format=ColorFormat.Rgba16Float))``
+
+    A configuration can start any number of captures. It holds no resources, so it can be kept
+    and reused after the captures were stopped.
+    """
+
+    capture_target: Monitor | Window | VirtualDesktop | CaptureItem | None
+    """The target to capture. If None, the target passed to the constructor of the :class:`.Capture`
+    is used."""
+    await_first_frame: bool
+    """Whether :meth:`.Capture.start` waits for the first frame."""
+    max_fps: int | None
+    """The maximum number of frames per second that are stored."""
+    restore_minimized: bool
+    """Whether a minimized window target is restored before the capture starts."""
+    min_feature_level: FeatureLevel
+    """The lowest feature level the DirectX device has to support."""
+    software_fallback: bool
+    """Whether the WARP software rasterizer is used if no GPU is available."""
+    color_format: ColorFormat
+    """The pixel format of the captured frames."""
+    buffer_count: int
+    """The number of frame buffers of the capture frame pool."""
+    adapter_index: int | None
+    """The index of the graphics adapter that runs the capture."""
+    flip_vertical: bool
+    """Whether frames are returned bottom-up."""
+    flip_horizontal: bool
+    """Whether frames are mirrored from left to right."""
+    scale: float | None
+    """The scale that frames are downscaled to on the GPU."""
+    min_update_interval_ms: int | None
+    """The minimum time in milliseconds between two delivered frames."""
+    apartment_type: ApartmentType
+    """The apartment in which the capture thread initializes the runtime."""
+    client_region: tuple[int, int, int, int] | None
+    """The region (x, y, width, height) of the client area of a window target that frames are
+    cropped to."""
+    thread_priority: ThreadPriority
+    """The priority of the capture thread."""
+    history_size: int
+    """The number of recent frames that are kept for :meth:`.Capture.recent_frames`."""
+    fixed_interval_ms: int | None
+    """The interval in milliseconds in which exactly one frame is stored."""
+    sticky_window: bool
+    """Whether the capture follows a replacement of a closed window target."""
+    device_flags: int | None
+    """The ``D3D11_CREATE_DEVICE_FLAG`` bit mask of the DirectX device."""
+    queue_depth: int
+    """The number of frames that are queued for :meth:`.Capture.recv`."""
+    resize_policy: ResizePolicy
+    """The handling of window targets that are resized during the capture."""
+    unpremultiply_alpha: bool
+    """Whether frames are converted to straight alpha."""
+    max_size: tuple[int, int] | None
+    """The (width, height) that frame buffers are allocated with at least."""
+    include_secondary_windows: bool
+    """Whether the secondary windows of a window target are captured."""
+    on_started: Callable[[], None] | None
+    """Called once the capture sessions have started."""
+    on_target_closed: Callable[[], None] | None
+    """Called from a background thread when the target closes."""
+
+    def __init__(
+        self,
+        *,
+        capture_target: Monitor | Window | VirtualDesktop | CaptureItem | None = None,
+        await_first_frame: bool = True,
+        max_fps: int | None = None,
+        restore_minimized: bool = False,
+        min_feature_level: FeatureLevel = ...,
+        software_fallback: bool = False,
+        color_format: ColorFormat = ...,
+        buffer_count: int = 1,
+        adapter_index: int | None = None,
+        flip_vertical: bool = False,
+        flip_horizontal: bool = False,
+        scale: float | None = None,
+        min_update_interval_ms: int | None = None,
+        apartment_type: ApartmentType = ...,
+        client_region: tuple[int, int, int, int] | None = None,
+        thread_priority: ThreadPriority = ...,
+        history_size: int = 0,
+        fixed_interval_ms: int | None = None,
+        sticky_window: bool = False,
+        device_flags: int | None = None,
+        queue_depth: int = 0,
+        resize_policy: ResizePolicy = ...,
+        unpremultiply_alpha: bool = False,
+        max_size: tuple[int, int] | None = None,
+        include_secondary_windows: bool = False,
+        on_started: Callable[[], None] | None = None,
+        on_target_closed: Callable[[], None] | None = None,
+    ) -> None: ...
+    def set(self, **options: Any) -> CaptureConfig:
+        """Set options by name and return the configuration, so calls can be chained.
+
+        Args:
+            options: The new values of the options, e.g. ``max_fps=30``.
+
+        Returns:
+            This configuration.
+
+        Raises:
+            TypeError: If an option does not exist or has a value of the wrong type.
+        """
+
+class FrameData:
+    """A frame and its metadata, returned by :meth:`.Capture.read`.
+
+    FrameData behaves like a named tuple, so it can be unpacked with
+    ``array, timestamp, frame_id, width, height = capture.read()``.
+    """
+
+    @property
+    def array(self) -> np.ndarray:
+        """The frame as a 3D NumPy array with dimensions [h w 4]."""
+
+    @property
+    def timestamp(self) -> float:
+        """The system relative time in seconds at which the frame was rendered."""
+
+    @property
+    def frame_id(self) -> int:
+        """The ID of the frame. IDs increase by one for each stored frame of a capture."""
+
+    @property
+    def width(self) -> int:
+        """The width of the frame."""
+
+    @property
+    def height(self) -> int:
+        """The height of the frame."""
+
+    @property
+    def content_width(self) -> int:
+        """The width of the captured content. Not part of the tuple."""
+
+    @property
+    def content_height(self) -> int:
+        """The height of the captured content. Not part of the tuple."""
+
+    @property
+    def crop(self) -> tuple[int, int, int, int] | None:
+        """The region (x, y, width, height) of the frame that the array was cropped to with the
+        ``auto_crop`` option of :meth:`.Capture.read`, or None if the frame was not cropped.
+
+        The offsets are the sizes of the left and top border inside the client region or child
+        window that frames are cropped to. Not part of the tuple.
+        """
+
+    @property
+    def missed_since_last(self) -> int | None:
+        """The number of frames the compositor skipped since the previous stored frame.
+
+        None if the refresh rate of the target is unknown or the frame was repeated by a
+        ``fixed_interval_ms`` capture. Unlike the dropped frames of :meth:`.Capture.stats`, this
+        counts skips at the source, e.g. stutter of the compositor. The count is estimated from the
+        gaps between frame times, and the compositor only delivers frames when the target changes,
+        so idle periods of a static target are counted as well. Not part of the tuple.
+        """
+
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> np.ndarray | float | int: ...
+
+class CaptureHandle:
+    """Read-only handle to a :class:`.Capture`, created by :meth:`.Capture.share`.
+
+    The handle reads the frames of the capture it was created from. It cannot start or stop the
+    capture, but it keeps the capture alive while the handle exists.
+    """
+
+    @property
+    def capture(self) -> Capture:
+        """The capture this handle reads from."""
+
+    @property
+    def active(self) -> bool:
+        """True if the capture is running and its target has not been closed."""
+
+    def frame(self, layout: str | None = None) -> np.ndarray:
+        """Convert the latest frame of the capture to an array and return it.
+
+        Args:
+            layout: The axis order of the array, see :meth:`.Capture.frame`. Defaults to ``"hwc"``.
+
+        Returns:
+            The frame as a 3D NumPy array with the dimensions of ``layout`` (RGBA channels).
+
+        Raises:
+            RuntimeError: If the capture is not running or has not picked up a frame yet.
+        """
+
+    def wait_for_frame(self, timeout_ms: int | None = None) -> np.ndarray | None:
+        """Wait until a frame arrives that this handle has not returned yet and return it.
+
+        Args:
+            timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+
+        Returns:
+            The frame as a 3D NumPy array with dimensions [h w 4], or None if no new frame arrived
+            within the timeout, the capture was stopped or failed or the target was closed.
+        """
+
+class FrameIterator:
+    """Iterator over the new frames of a :class:`.Capture`, created by :meth:`.Capture.frames`."""
+
+    def __iter__(self) -> FrameIterator: ...
+    def __next__(self) -> np.ndarray: ...
+
+class FrameView:
+    """Read-only view on the pixels of a frame that supports the Python buffer protocol.
+
+    The view exposes the mapped frame memory directly, so ``np.asarray(view)`` returns an array with
+    dimensions [h w 4] without copying the pixels. The memory stays valid as long as the view or any
+    array created from it is alive, and is released afterwards. New frames do not change the content
+    of an existing view. Frameworks that support DLPack import the view without copies as well, e.g.
+    ``torch.from_dlpack(view)`` or ``np.from_dlpack(view)``.
+
+    Warning:
+        Each view keeps its own staging texture on the GPU alive. Release views as soon as you are
+        done with them instead of holding on to views of old frames.
+    """
+
+    @property
+    def shape(self) -> tuple[int, int, int]:
+        """The dimensions of the view as (height, width, 4)."""
+
+    def __dlpack__(self, stream: None = None) -> Any:
+        """Export the view as a DLPack tensor with dimensions [h w 4].
+
+        The tensor references the mapped frame memory and keeps the view alive until the consumer
+        releases it.
+        """
+
+    def __dlpack_device__(self) -> tuple[int, int]:
+        """Return the DLPack device of the view, which is always the CPU."""
+
+class CaptureGroup:
+    """Group of captures that are started, stopped and read together.
+
+    CaptureGroup owns one :class:`.Capture` per target. All captures share the same settings and
+    their frames are returned in a single call, keyed by the target they belong to.
+
+    Args:
+        capture_targets: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop`
+            targets to capture.
+    """
+
+    def __init__(self, capture_targets: list[Monitor | Window | VirtualDesktop]): ...
+    def start(self, await_first_frame: bool = True, max_fps: int | None = None):
+        """Start the captures of all targets.
+
+        If any capture fails to start, all captures of the group are stopped again.
+
+        Args:
+            await_first_frame: Waits for the first frame of each capture to arrive if True.
+            max_fps: The maximum number of frames per second that are stored for each capture.
+
+        Raises:
+            RuntimeError: If any capture fails to start. The message lists the error of each
+                failed target.
+        """
+
+    def stop(self):
+        """Stop the captures of all targets."""
+
+    def frames(self) -> dict[Monitor | Window | VirtualDesktop, np.ndarray]:
+        """Convert the latest frame of each capture to an array and return them.
+
+        Returns:
+            A dictionary mapping each target to its latest frame.
+        """
+
+    @property
+    def active(self) -> bool:
+        """True if the captures of all targets are running, False otherwise."""
+
+    def __len__(self) -> int: ...
+
+class PixelForgeError(RuntimeError):
+    """Base class of all errors raised by pixel_forge.
+
+    All exceptions derive from ``RuntimeError``, so existing ``except RuntimeError`` handlers keep
+    catching them. Errors without a more specific class are raised as this class.
+    """
+
+class WindowNotFoundError(PixelForgeError):
+    """The requested window does not exist."""
+
+class MonitorNotFoundError(PixelForgeError):
+    """The requested monitor does not exist."""
+
+class DirectXError(PixelForgeError):
+    """Creating or using the DirectX device failed."""
+
+class CaptureThreadError(PixelForgeError):
+    """The capture thread failed to start or exited with an error."""
+
+class CaptureNotRunningError(PixelForgeError):
+    """The capture is not running, e.g. because it was never started or has been stopped."""
+
+class FrameUnavailableError(PixelForgeError):
+    """No frame is available yet or no frame arrived before the timeout."""
+
+class RecorderError(PixelForgeError):
+    """Starting, feeding or finishing a recording failed."""
+
+class PipeError(PixelForgeError):
+    """Starting, writing or stopping a raw frame pipe failed."""
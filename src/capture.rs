@@ -1,88 +1,186 @@
 // This code has been adapted from https://github.com/NiiightmareXD/windows-capture
 
 use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::thread::{self, sleep, JoinHandle};
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use pyo3::exceptions::PyRuntimeError;
+use numpy::PyArray3;
+use parking_lot::Mutex;
+use pyo3::exceptions::{PyIndexError, PyRuntimeWarning, PyTypeError};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyCFunction, PyDict};
 
-use windows::core::{IInspectable, Interface};
-use windows::Foundation::AsyncActionCompletedHandler;
-use windows::Foundation::TypedEventHandler;
-use windows::Graphics::Capture::{Direct3D11CaptureFramePool, GraphicsCaptureItem};
-use windows::Graphics::DirectX::DirectXPixelFormat;
-use windows::Win32::Foundation::{LPARAM, WPARAM};
-use windows::Win32::Graphics::Direct3D11::{ID3D11Texture2D, D3D11_TEXTURE2D_DESC};
-use windows::Win32::System::Threading::GetCurrentThreadId;
-use windows::Win32::System::WinRT::Direct3D11::IDirect3DDxgiInterfaceAccess;
-use windows::Win32::System::WinRT::{
-    CreateDispatcherQueueController, DispatcherQueueOptions, RoInitialize, RoUninitialize,
-    DQTAT_COM_NONE, DQTYPE_THREAD_CURRENT, RO_INIT_MULTITHREADED,
+use windows::core::{Interface, HSTRING};
+use windows::Foundation::Metadata::ApiInformation;
+use windows::Graphics::Capture::GraphicsCapturePicker;
+use windows::Wdk::System::SystemServices::RtlGetVersion;
+use windows::Win32::Graphics::Direct3D11::{ID3D11Texture2D, D3D11_CREATE_DEVICE_FLAG};
+use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+use windows::Win32::UI::Shell::IInitializeWithWindow;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+use crate::capture_core::{self, CaptureCore, CaptureOptions, FrameWatch};
+use crate::capture_utils::{
+    resolve_future, system_relative_time, ApartmentType, CaptureItem, CaptureStats, CaptureTarget,
+    ColorFormat, Diagnostics, ResizePolicy, ThreadPriority, WindowsBuild,
 };
-use windows::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageW, GetMessageW, PostQuitMessage, PostThreadMessageW, TranslateMessage, MSG,
-    WM_QUIT,
+use crate::direct_x::{
+    adapter_name, create_d3d_device, d3d_device_from_raw, supported_color_formats, FeatureLevel,
+    DEFAULT_DEVICE_FLAGS,
 };
-use windows_result::Error as WindowsError;
-
-use numpy::ndarray::{self, s};
-use numpy::PyArray3;
-use numpy::ToPyArray;
-use parking_lot::Mutex;
+use crate::frame::{
+    map_point, Flip, Frame, FrameAccumulator, FrameSample, FrameView, Layout, MappedFrame, Region,
+};
+use crate::monitor::{enumerate_monitors, Monitor};
+use crate::window::{foreground_window, Window};
 
-use crate::capture_utils::{CaptureTarget, ColorFormat};
-use crate::direct_x::{create_d3d_device, create_direct3d_device, DirectXError, SendDirectX};
-use crate::frame::{Frame, FrameError};
+pub use crate::capture_core::CaptureError;
 
-#[derive(thiserror::Error, Debug)]
-pub enum CaptureError {
-    #[error("No frame available yet.")]
-    NoFrameAvailable,
-    #[error("Windows error during Capture.")]
-    WindowsError(#[from] WindowsError),
-    #[error("DirectX error during Capture.")]
-    DirectXError(#[from] DirectXError),
-    #[error("Frame could not be materialized.")]
-    FrameConversionError(#[from] FrameError),
-    #[error("Capture thread exited unexpectedly with an error.")]
-    CaptureThreadError,
-    #[error("Invalid capture target.")]
-    InvalidCaptureTarget,
-}
+// Interval in which the thread that waits for a closed target checks if it was cancelled
+const CLOSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
-impl From<CaptureError> for PyErr {
-    fn from(error: CaptureError) -> PyErr {
-        PyRuntimeError::new_err(error.to_string())
-    }
-}
+// Region as the (x, y, width, height) tuple that is passed to and from Python
+type RegionTuple = (u32, u32, u32, u32);
 
+/// Capture(capture_target: CaptureTarget | None = None) -> Capture
 /// Capture class to capture frames from a monitor or a window.
 ///
 /// The idea is to get either a :class:`.Monitor` or a :class:`.Window` as target, create a Capture
 /// object, and then start a capture thread that will update the internal frame of the Capture
 /// object whenever a new frame is available. Frames are only materialized, converted to NumPy
 /// arrays and passed over to Python when the user requests it to avoid unnecessary copies.
+///
+/// Args:
+///     capture_target: The default target that :meth:`start` captures if it is called without a
+///         target.
 #[pyclass]
 pub struct Capture {
-    thread: Option<JoinHandle<Result<(), CaptureError>>>,
-    thread_id: Arc<Mutex<Option<u32>>>,
-    frame: Arc<Mutex<Option<Frame>>>,
+    capture_target: Option<CaptureTarget>,
+    core: CaptureCore,
+    flip: Flip,
+    // Region of the window client area that frames are cropped to
+    client_region: Option<Region>,
+    // Frame age in milliseconds above which reading a frame emits a warning
+    stale_frame_threshold_ms: Option<f64>,
+    // Sample of the frame compared by the last call of `frames_differ`
+    previous_sample: Mutex<Option<FrameSample>>,
+    // Texture behind the handle returned by the last call of `shared_texture_handle`
+    shared_texture: Mutex<Option<ID3D11Texture2D>>,
+    // Cancels the thread that calls the `on_target_closed` callback of the running capture
+    closed_callback_cancelled: Option<Arc<AtomicBool>>,
+    // Arrays that `frame` alternates writing into, set up by `configure_double_buffer`
+    double_buffer: Mutex<Option<DoubleBuffer>>,
+}
+
+// Pair of preallocated arrays. Frames are written into the back buffer, which becomes the front
+// buffer once the frame is complete
+struct DoubleBuffer {
+    buffers: [Py<PyArray3<u8>>; 2],
+    front: usize,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self::new(None)
+    }
 }
 
 #[pymethods]
 impl Capture {
     #[new]
-    pub fn new() -> Self {
+    #[pyo3(signature = (capture_target=None))]
+    pub fn new(capture_target: Option<CaptureTarget>) -> Self {
         Self {
-            thread: None,
-            thread_id: Arc::new(Mutex::new(None)),
-            frame: Arc::new(Mutex::new(None)),
+            capture_target,
+            core: CaptureCore::new(),
+            flip: Flip::default(),
+            client_region: None,
+            stale_frame_threshold_ms: None,
+            previous_sample: Mutex::new(None),
+            shared_texture: Mutex::new(None),
+            closed_callback_cancelled: None,
+            double_buffer: Mutex::new(None),
         }
     }
 
-    /// start(capture_target: CaptureTarget, await_first_frame: bool = True)
+    /// with_device(device: int, capture_target: CaptureTarget | None = None) -> Capture
+    ///
+    /// Create a :class:`.Capture` that runs on an existing DirectX device.
+    ///
+    /// Applications that already render with DirectX can share their device with the capture to
+    /// avoid copying frame textures between devices, e.g. if the captured content is rendered
+    /// again into the swapchain of the application. The ``min_feature_level``,
+    /// ``software_fallback``, ``adapter_index`` and ``device_flags`` options of :meth:`start` are
+    /// ignored for this capture.
+    ///
+    /// .. note::
+    ///    Direct3D 11 devices are free-threaded COM objects, so the device does not have to be
+    ///    created in the COM apartment that the capture thread joins (see ``apartment_type`` of
+    ///    :meth:`start`). The swapchain itself stays bound to the window thread of the
+    ///    application and is never touched by the capture.
+    ///
+    /// .. warning::
+    ///    The immediate context of the device is used from the capture thread and from the thread
+    ///    that reads the frames. Direct3D 11 immediate contexts are not thread-safe, so the
+    ///    application must not use the context concurrently unless it enabled multithread
+    ///    protection with ``ID3D11Multithread::SetMultithreadProtected``. The device must have been
+    ///    created with ``D3D11_CREATE_DEVICE_BGRA_SUPPORT``.
+    ///
+    /// Args:
+    ///     device: The address of a COM interface of the device, i.e. its ``ID3D11Device`` or the
+    ///         ``IDXGIDevice`` that a swapchain was created with. The capture holds its own
+    ///         reference to the device, so the application may release its reference afterwards.
+    ///     capture_target: The default target that :meth:`start` captures if it is called
+    ///         without a target.
+    #[staticmethod]
+    #[pyo3(signature = (device, capture_target=None))]
+    pub fn with_device(
+        device: usize,
+        capture_target: Option<CaptureTarget>,
+    ) -> Result<Self, CaptureError> {
+        // There is no way to validate an arbitrary address, so we have to trust the caller here
+        let d3d_device = unsafe { d3d_device_from_raw(device)? };
+        Ok(Self {
+            capture_target,
+            core: CaptureCore::with_device(d3d_device),
+            flip: Flip::default(),
+            client_region: None,
+            stale_frame_threshold_ms: None,
+            previous_sample: Mutex::new(None),
+            shared_texture: Mutex::new(None),
+            closed_callback_cancelled: None,
+            double_buffer: Mutex::new(None),
+        })
+    }
+
+    /// snapshot(capture_target: CaptureTarget, timeout_ms: int | None = None) -> numpy.ndarray
+    ///
+    /// Take a single screenshot of a capture target.
+    ///
+    /// Unlike :meth:`frame`, no running capture is required. A short-lived capture is started,
+    /// the first frame is copied into an array and the capture is stopped again, so no thread or
+    /// DirectX resources remain afterwards.
+    ///
+    /// Args:
+    ///     capture_target: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop` to
+    ///         capture.
+    ///     timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely
+    ///         if None.
+    ///
+    /// :returns: The frame as a 3D NumPy array with dimensions [h w 4].
+    #[staticmethod]
+    #[pyo3(signature = (capture_target, timeout_ms=None))]
+    pub fn snapshot(
+        py: Python,
+        capture_target: CaptureTarget,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<PyObject> {
+        grab(py, capture_target, timeout_ms)
+    }
+
+    /// start(capture_target: CaptureTarget | CaptureConfig | None = None, await_first_frame: bool = True, max_fps: int | None = None, restore_minimized: bool = False, min_feature_level: FeatureLevel = FeatureLevel.Level11_1, software_fallback: bool = False, color_format: ColorFormat = ColorFormat.Rgba8, buffer_count: int = 1, adapter_index: int | None = None, flip_vertical: bool = False, flip_horizontal: bool = False, scale: float | None = None, min_update_interval_ms: int | None = None, apartment_type: ApartmentType = ApartmentType.Multithreaded, client_region: tuple[int, int, int, int] | None = None, thread_priority: ThreadPriority = ThreadPriority.Normal, history_size: int = 0, fixed_interval_ms: int | None = None, sticky_window: bool = False, device_flags: int | None = None, queue_depth: int = 0, resize_policy: ResizePolicy = ResizePolicy.Recreate, unpremultiply_alpha: bool = False, max_size: tuple[int, int] | None = None, include_secondary_windows: bool = False, on_started: Callable[[], None] | None = None, on_target_closed: Callable[[], None] | None = None)
     ///
     /// Start the capture.
     ///
@@ -92,215 +190,2561 @@ impl Capture {
     /// frame is available before continuing, set ``await_first_frame`` to True. This will block the
     /// main thread until the first frame is available.
     ///
+    /// The method returns once the capture thread has created its device and started the capture
+    /// sessions, so errors during this setup are raised here. Errors after the setup end the
+    /// capture thread and are raised by :meth:`try_stop`.
+    ///
+    /// The options can also be bundled in a :class:`.CaptureConfig` that is passed in place of
+    /// the target. Options passed as keyword arguments in addition override the options of the
+    /// configuration.
+    ///
     /// .. note::
     ///    You have to call :meth:`start` before any frames become available.
     ///
     /// Args:
-    ///     capture_target: The :class:`.Monitor` or :class:`.Window` to capture.
-    ///     await_first_frame: Waits for the first frame to arrive if True.
+    ///     capture_target: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop` to
+    ///         capture, or a :class:`.CaptureConfig` with the target and all options. If None, the
+    ///         target passed to the constructor is used.
+    ///     await_first_frame: Waits for the first frame to arrive if True. Raises an error if the
+    ///         capture stops before any frame arrives, so a frame is guaranteed to be available
+    ///         after :meth:`start` returns.
+    ///     max_fps: The maximum number of frames per second that are stored. Frames arriving
+    ///         faster are skipped, but the latest skipped frame is stored once the interval
+    ///         elapsed. If None, every frame is stored, which the compositor delivers at most at
+    ///         the refresh rate of the monitor displaying the target.
+    ///     restore_minimized: Restores a minimized window target before starting the capture if
+    ///         True. Otherwise, capturing a minimized window raises an error.
+    ///     min_feature_level: The lowest :class:`.FeatureLevel` the DirectX device has to support.
+    ///     software_fallback: Falls back to the WARP software rasterizer if no hardware DirectX
+    ///         device can be created if True. Useful on machines without a GPU, e.g. CI runners.
+    ///     color_format: The :class:`.ColorFormat` of the captured frames. Use
+    ///         ``ColorFormat.Rgba16Float`` to capture HDR content without clipping. The format is
+    ///         checked against the capture device before the capture starts, and the error lists
+    ///         the formats the device supports instead.
+    ///     buffer_count: The number of frame buffers of the capture frame pool. More buffers let
+    ///         the compositor queue frames while a frame is being processed, at the cost of GPU
+    ///         memory.
+    ///     adapter_index: The index of the graphics adapter that runs the capture. On systems
+    ///         with several GPUs, this should be the adapter that renders the target. If None,
+    ///         the default adapter is used.
+    ///     flip_vertical: Returns frames with their rows in reverse order, i.e. bottom-up, if
+    ///         True.
+    ///     flip_horizontal: Returns frames mirrored from left to right if True.
+    ///     scale: Downscales frames on the GPU before they are copied to the CPU, which saves
+    ///         bandwidth if only thumbnails are needed. Frames can only be halved, so the actual
+    ///         scale is the largest power of two that does not fall below ``scale``, e.g. 0.5 for
+    ///         a scale of 0.3. Use the shape of the frames or :attr:`width` and :attr:`height` to
+    ///         get the output dimensions. Must be in the range (0, 1]. If None, frames are not
+    ///         scaled.
+    ///     min_update_interval_ms: The minimum time in milliseconds between two frames delivered
+    ///         by the compositor, which saves the work of producing frames that would be skipped.
+    ///         On Windows builds before 11 24H2, frames are skipped after they arrive instead. Use
+    ///         :attr:`throttled_at_source` to check which one is used.
+    ///     apartment_type: The :class:`.ApartmentType` in which the capture thread initializes
+    ///         the Windows Runtime.
+    ///     client_region: Crops the frames returned by :meth:`frame`, :meth:`read` and the other
+    ///         frame methods to the rectangle (x, y, width, height) inside the client area of a
+    ///         window target, e.g. to the viewport of a game without the title bar and borders.
+    ///         Methods that return the whole frame document it. The crop follows the client area
+    ///         if the window moves its decorations. If None, frames of child windows are cropped
+    ///         to their client area and other frames are not cropped.
+    ///     thread_priority: The :class:`.ThreadPriority` of the capture thread. Latency-sensitive
+    ///         captures at high frame rates can raise it to avoid stutters under load.
+    ///     history_size: The number of recent frames that are kept for :meth:`recent_frames`,
+    ///         e.g. for an instant replay. Each kept frame holds a full copy of its pixels in GPU
+    ///         memory, so a history of 60 frames of a 4K monitor takes about 2 GB.
+    ///     fixed_interval_ms: Stores exactly one frame every ``fixed_interval_ms`` milliseconds,
+    ///         repeating the latest content if the target did not change. This keeps the frame
+    ///         rate of recordings steady while the screen is static. The timer sets the pace, so
+    ///         ``max_fps`` is ignored. If None, frames are stored whenever the compositor delivers
+    ///         them.
+    ///     sticky_window: Keeps capturing if a window target is closed and its process opens a
+    ///         new top-level window of the same window class within 10 seconds, e.g. a game that
+    ///         recreates its window when switching to fullscreen. :attr:`target` returns the new
+    ///         window and :attr:`rebind_count` counts the replacements. If False, closed windows
+    ///         end the capture, see :attr:`closed`.
+    ///     device_flags: The ``D3D11_CREATE_DEVICE_FLAG`` bit mask the DirectX device is created
+    ///         with, e.g. ``0x22`` to add the debug layer (``0x2``) to the required BGRA support
+    ///         (``0x20``). The debug layer reports invalid API usage to the debugger output and
+    ///         requires the Graphics Tools optional feature of Windows. If None, the device is
+    ///         created with BGRA support only.
+    ///     queue_depth: The number of frames that are queued for :meth:`recv` in addition to the
+    ///         latest frame, so pipelines do not miss frames that arrive while they are busy. If
+    ///         the queue is full, the oldest frame is dropped and counted in
+    ///         :attr:`queue_drop_count`. Each queued frame holds a full copy of its pixels in GPU
+    ///         memory. If 0, no frames are queued.
+    ///     resize_policy: The :class:`.ResizePolicy` for window targets that are resized during
+    ///         the capture. ``Recreate`` follows the new size, ``Fixed`` keeps the initial frame
+    ///         size for recordings with constant dimensions, and ``Error`` ends the capture and
+    ///         sets :attr:`resized`.
+    ///     unpremultiply_alpha: The compositor delivers colors premultiplied by their alpha. If
+    ///         True, the colors are divided by the alpha when frames are copied to the CPU, so
+    ///         semi-transparent pixels have straight alpha, see :attr:`alpha_mode`. Only supported
+    ///         for ``ColorFormat.Rgba8``.
+    ///     max_size: The (width, height) that the frame buffers of window and monitor targets are
+    ///         allocated with at least. Windows that grow up to this size keep their buffers
+    ///         instead of recreating them on every resize, and frames are cropped to the window
+    ///         content. Only buffers of windows that outgrow the size are recreated. Use this for
+    ///         windows that you know will be enlarged. If None, the buffers have the target size.
+    ///     include_secondary_windows: Also captures the secondary windows of a window target,
+    ///         e.g. popups and dialogs that packaged apps render in separate windows. Requires
+    ///         Windows 11, see :attr:`.WindowsBuild.secondary_windows_supported`.
+    ///     on_started: A callable that is called without arguments once the capture sessions
+    ///         have started, before waiting for the first frame. The capture is still starting, so
+    ///         the callable must not use it. If it raises, the capture is stopped and the error is
+    ///         raised by :meth:`start`.
+    ///     on_target_closed: A callable that is called without arguments from a background thread
+    ///         when the captured window is closed or the monitor is disconnected. The capture
+    ///         thread ends on its own in that case, so the capture is no longer :attr:`active`.
+    ///         Errors raised by the callable are printed. Not called if the capture is stopped
+    ///         before the target closes.
+    // Every option is a keyword argument on the Python side
+    #[allow(clippy::too_many_arguments)]
     pub fn start(
         &mut self,
-        capture_target: CaptureTarget,
+        capture_target: Option<StartTarget>,
         await_first_frame: Option<bool>,
-    ) -> Result<(), CaptureError> {
-        // In case of a window capture, check if the window is valid
-        match capture_target {
-            CaptureTarget::Window(window) => {
-                if !window.valid() {
-                    return Err(CaptureError::InvalidCaptureTarget);
-                }
-            }
-            CaptureTarget::Monitor(_) => {}
+        max_fps: Option<u32>,
+        restore_minimized: Option<bool>,
+        min_feature_level: Option<FeatureLevel>,
+        software_fallback: Option<bool>,
+        color_format: Option<ColorFormat>,
+        buffer_count: Option<u32>,
+        adapter_index: Option<u32>,
+        flip_vertical: Option<bool>,
+        flip_horizontal: Option<bool>,
+        scale: Option<f32>,
+        min_update_interval_ms: Option<u64>,
+        apartment_type: Option<ApartmentType>,
+        client_region: Option<RegionTuple>,
+        thread_priority: Option<ThreadPriority>,
+        history_size: Option<usize>,
+        fixed_interval_ms: Option<u64>,
+        sticky_window: Option<bool>,
+        device_flags: Option<u32>,
+        queue_depth: Option<usize>,
+        resize_policy: Option<ResizePolicy>,
+        unpremultiply_alpha: Option<bool>,
+        max_size: Option<(u32, u32)>,
+        include_secondary_windows: Option<bool>,
+        on_started: Option<PyObject>,
+        on_target_closed: Option<PyObject>,
+    ) -> PyResult<()> {
+        let config = match capture_target {
+            Some(StartTarget::Config(config)) => config,
+            Some(StartTarget::Target(target)) => CaptureConfig {
+                capture_target: Some(target),
+                ..CaptureConfig::default()
+            },
+            None => CaptureConfig::default(),
+        };
+        // Options passed as keyword arguments override the options of the configuration
+        self.start_with(CaptureConfig {
+            capture_target: config.capture_target,
+            await_first_frame: await_first_frame.unwrap_or(config.await_first_frame),
+            max_fps: max_fps.or(config.max_fps),
+            restore_minimized: restore_minimized.unwrap_or(config.restore_minimized),
+            min_feature_level: min_feature_level.unwrap_or(config.min_feature_level),
+            software_fallback: software_fallback.unwrap_or(config.software_fallback),
+            color_format: color_format.unwrap_or(config.color_format),
+            buffer_count: buffer_count.unwrap_or(config.buffer_count),
+            adapter_index: adapter_index.or(config.adapter_index),
+            flip_vertical: flip_vertical.unwrap_or(config.flip_vertical),
+            flip_horizontal: flip_horizontal.unwrap_or(config.flip_horizontal),
+            scale: scale.or(config.scale),
+            min_update_interval_ms: min_update_interval_ms.or(config.min_update_interval_ms),
+            apartment_type: apartment_type.unwrap_or(config.apartment_type),
+            client_region: client_region.or(config.client_region),
+            thread_priority: thread_priority.unwrap_or(config.thread_priority),
+            history_size: history_size.unwrap_or(config.history_size),
+            fixed_interval_ms: fixed_interval_ms.or(config.fixed_interval_ms),
+            sticky_window: sticky_window.unwrap_or(config.sticky_window),
+            device_flags: device_flags.or(config.device_flags),
+            queue_depth: queue_depth.unwrap_or(config.queue_depth),
+            resize_policy: resize_policy.unwrap_or(config.resize_policy),
+            unpremultiply_alpha: unpremultiply_alpha.unwrap_or(config.unpremultiply_alpha),
+            max_size: max_size.or(config.max_size),
+            include_secondary_windows: include_secondary_windows
+                .unwrap_or(config.include_secondary_windows),
+            on_started: on_started.or(config.on_started),
+            on_target_closed: on_target_closed.or(config.on_target_closed),
+        })
+    }
+
+    /// :``bool``: True if the capture thread is running, False otherwise. The thread ends on its
+    /// own if the target is closed or the capture fails.
+    #[getter]
+    pub fn active(&self) -> bool {
+        self.core.is_active()
+    }
+
+    /// :``Monitor | Window | VirtualDesktop | None``: The target of the running capture, or None if
+    /// no capture is active.
+    #[getter]
+    pub fn target(&self) -> Option<CaptureTarget> {
+        self.core.target()
+    }
+
+    /// :``str | None``: The kind of the running capture's target, or None if no capture is active.
+    ///
+    /// One of ``"monitor"``, ``"window"``, ``"virtual_desktop"`` or ``"item"`` for targets chosen
+    /// with :func:`pick_capture_target`. Cheaper than :attr:`target` if you only need to branch on
+    /// the kind, e.g. for logging.
+    #[getter]
+    pub fn target_kind(&self) -> Option<&'static str> {
+        self.core.target().map(|target| target.kind())
+    }
+
+    /// Stop the capture thread, wait for it to join and invalidate the last frame.
+    ///
+    /// Threads that are blocked in :meth:`wait_for_frame` or in a :meth:`frames` iteration return
+    /// None first, so the capture can be stopped from another thread. Errors of the capture thread
+    /// are ignored, so stopping never fails. Use :meth:`try_stop` to receive them.
+    #[pyo3(name = "stop")]
+    pub fn py_stop(slf: &PyCell<Self>) -> PyResult<()> {
+        stop_waited(slf, Capture::stop)
+    }
+
+    /// try_stop()
+    /// Stop the capture like :meth:`stop` and raise the error the capture thread exited with.
+    ///
+    /// The capture is stopped and its resources are released even if an error is raised.
+    ///
+    /// Raises:
+    ///     RuntimeError: If the capture thread failed, e.g. while releasing the capture session
+    ///         of a target that was destroyed.
+    #[pyo3(name = "try_stop")]
+    pub fn py_try_stop(slf: &PyCell<Self>) -> PyResult<()> {
+        Ok(stop_waited(slf, Capture::try_stop)??)
+    }
+
+    /// start_recording(path: str, fps: int = 30)
+    /// Start encoding the captured frames into an H.264 MP4 file.
+    ///
+    /// Frames are encoded on the GPU as they arrive, so recording does not require reading frames.
+    /// The video has the size of the latest frame, rounded down to even dimensions. Frames that
+    /// arrive after the target was resized are skipped. Recording requires the ``rgba8`` color
+    /// format. The recording is finished by :meth:`stop_recording` or :meth:`stop`.
+    ///
+    /// Args:
+    ///     path: The path of the MP4 file. Existing files are overwritten.
+    ///     fps: The frame rate stored in the file.
+    #[pyo3(signature = (path, fps=30))]
+    pub fn start_recording(&self, path: &str, fps: u32) -> PyResult<()> {
+        Ok(self.core.start_recording(path, fps)?)
+    }
+
+    /// stop_recording()
+    /// Finish the running recording and close its file.
+    ///
+    /// Raises an error if no recording is running or if encoding any of the frames failed.
+    pub fn stop_recording(&self, py: Python) -> PyResult<()> {
+        Ok(py.allow_threads(|| self.core.stop_recording())?)
+    }
+
+    /// :``bool``: True if a recording is running, False otherwise.
+    #[getter]
+    pub fn recording(&self) -> bool {
+        self.core.is_recording()
+    }
+
+    /// start_pipe(path: str, fps: int = 30)
+    /// Start streaming the captured frames as raw video to a file, a named pipe or stdout.
+    ///
+    /// Frames are copied and written on the capture thread, so Python never touches the pixels and
+    /// the GIL is not involved. Each frame is written as ``height`` rows of ``width`` pixels, top
+    /// row first, with the four bytes R, G, B and A per pixel and without any padding, headers or
+    /// separators between frames. ``client_region`` and flips are not applied. The stream can be
+    /// read with ``ffmpeg -f rawvideo -pixel_format rgba -video_size {width}x{height} -framerate
+    /// {fps} -i {path}``.
+    ///
+    /// The stream has the size of the latest frame, and frames that arrive after the target was
+    /// resized are skipped. Frames are repeated or dropped according to their timestamps, so the
+    /// stream has exactly ``fps`` frames per second of capture time. Frames are only written
+    /// when the target changes, so combine the pipe with ``fixed_interval_ms`` if the reader needs
+    /// a steady stream while the screen is static. A slow reader delays the capture thread.
+    /// Requires the ``rgba8`` color format. The pipe is stopped by :meth:`stop_pipe` or
+    /// :meth:`stop`.
+    ///
+    /// Args:
+    ///     path: The path of the file or of an existing named pipe, e.g.
+    ///         ``\\.\pipe\frames``, or ``"-"`` for stdout. Existing files are overwritten.
+    ///     fps: The frame rate of the stream.
+    #[pyo3(signature = (path, fps=30))]
+    pub fn start_pipe(&self, path: &str, fps: u32) -> PyResult<()> {
+        Ok(self.core.start_pipe(path, fps)?)
+    }
+
+    /// stop_pipe()
+    /// Stop the running pipe and flush its output.
+    ///
+    /// Raises an error if no pipe is running or if writing any of the frames failed, e.g. because
+    /// the reader closed the pipe.
+    pub fn stop_pipe(&self, py: Python) -> PyResult<()> {
+        Ok(py.allow_threads(|| self.core.stop_pipe())?)
+    }
+
+    /// :``bool``: True if a pipe is running, False otherwise.
+    #[getter]
+    pub fn piping(&self) -> bool {
+        self.core.is_piping()
+    }
+
+    /// :``bool``: True if the compositor applies ``min_update_interval_ms`` of the running capture.
+    ///
+    /// False if no interval was set or if the Windows build does not support it, in which case
+    /// frames are skipped after they arrive. The value is set before the first frame arrives.
+    #[getter]
+    pub fn throttled_at_source(&self) -> bool {
+        self.core.is_throttled_at_source()
+    }
+
+    /// :``str``: The order of the channels along the last axis of the frame arrays, e.g. ``"RGBA"``.
+    ///
+    /// The order follows the ``color_format`` of the running capture, or of the last capture if
+    /// it was stopped. Check it before passing frames to libraries that expect a fixed order, e.g.
+    /// OpenCV, which uses BGR.
+    #[getter]
+    pub fn channel_order(&self) -> &'static str {
+        self.core.color_format().channel_order()
+    }
+
+    /// :``str``: The color space of the frame arrays, ``"sRGB"`` or ``"scRGB"``.
+    ///
+    /// ``Rgba8`` frames use gamma encoded sRGB, into which the compositor tone maps HDR and
+    /// wide-gamut content. ``Rgba16Float`` frames use linear scRGB, whose values exceed the range
+    /// [0, 1] for HDR content. Convert them before displaying them as sRGB. The color space follows
+    /// the ``color_format`` of the running capture, or of the last capture if it was stopped.
+    #[getter]
+    pub fn color_space(&self) -> &'static str {
+        self.core.color_format().color_space()
+    }
+
+    /// :``FeatureLevel | None``: The Direct3D feature level the capture device achieved. Useful to
+    /// compare capture quality across machines. Refers to the running capture, or to the last
+    /// capture if it was stopped. None until the capture thread has created its device, or if a
+    /// device supplied with :meth:`with_device` has a Direct3D 12 level.
+    #[getter]
+    pub fn feature_level(&self) -> Option<FeatureLevel> {
+        self.core.feature_level()
+    }
+
+    /// frame_spec() -> tuple[str, int]
+    /// Return the NumPy dtype and the number of channels of the frame arrays.
+    ///
+    /// The spec follows the ``color_format`` of the running capture, or of the last capture if it
+    /// was stopped, so arrays can be allocated before a frame is materialized.
+    ///
+    /// :returns: The tuple (dtype, channels), e.g. ``("uint8", 4)`` or ``("float16", 4)``.
+    pub fn frame_spec(&self) -> (&'static str, u32) {
+        let color_format = self.core.color_format();
+        (color_format.dtype(), color_format.channels())
+    }
+
+    /// :``float | None``: The frame age in milliseconds above which :meth:`frame` and :meth:`read`
+    /// emit a ``RuntimeWarning``, or None to disable the warning.
+    ///
+    /// Reading repeatedly returns the same frame if the capture stops delivering new ones, e.g.
+    /// because the target froze. The warning makes this visible. Frames only arrive when the
+    /// content of the target changes, so static content also ages. Disabled by default.
+    #[getter]
+    pub fn stale_frame_threshold_ms(&self) -> Option<f64> {
+        self.stale_frame_threshold_ms
+    }
+
+    #[setter]
+    pub fn set_stale_frame_threshold_ms(&mut self, threshold_ms: Option<f64>) {
+        self.stale_frame_threshold_ms = threshold_ms;
+    }
+
+    /// :``int | None``: The width of the latest frame, or None if no frame is available.
+    #[getter]
+    pub fn width(&self) -> Option<u32> {
+        self.core.latest_frame().map(|frame| frame.output_size().0)
+    }
+
+    /// :``int | None``: The height of the latest frame, or None if no frame is available.
+    #[getter]
+    pub fn height(&self) -> Option<u32> {
+        self.core.latest_frame().map(|frame| frame.output_size().1)
+    }
+
+    /// :``int | None``: The width of the captured content in the latest frame, or None if no frame
+    /// is available.
+    ///
+    /// The content can be smaller than the frame, e.g. right after the target was resized. Pixels
+    /// outside of the content area do not belong to the target.
+    #[getter]
+    pub fn content_width(&self) -> Option<u32> {
+        self.core
+            .latest_frame()
+            .map(|frame| frame.output_content_size().0)
+    }
+
+    /// :``int | None``: The height of the captured content in the latest frame, or None if no
+    /// frame is available.
+    ///
+    /// The content can be smaller than the frame, e.g. right after the target was resized. Pixels
+    /// outside of the content area do not belong to the target.
+    #[getter]
+    pub fn content_height(&self) -> Option<u32> {
+        self.core
+            .latest_frame()
+            .map(|frame| frame.output_content_size().1)
+    }
+
+    /// :``int``: The number of times the capture target changed its size since :meth:`start`.
+    ///
+    /// Frames that arrive after a size change have the new dimensions. Compare the value against a
+    /// previously read one to detect that the frame dimensions changed.
+    #[getter]
+    pub fn resize_count(&self) -> u64 {
+        self.core.frame_tracker.resizes()
+    }
+
+    /// :``int``: The number of times the captured window moved to another monitor since
+    /// :meth:`start`.
+    ///
+    /// Windows that move to a monitor with a different DPI change their size and scale. Compare
+    /// the value against a previously read one and query :meth:`.Window.monitor` to adjust DPI
+    /// dependent coordinate transforms. Always 0 for targets other than windows.
+    #[getter]
+    pub fn monitor_change_count(&self) -> u64 {
+        self.core.frame_tracker.monitor_changes()
+    }
+
+    /// :``int``: The number of times a closed window target was replaced by a new window since
+    /// :meth:`start`.
+    ///
+    /// Only captures started with ``sticky_window=True`` replace their target. Frames of the new
+    /// window can have different dimensions.
+    #[getter]
+    pub fn rebind_count(&self) -> u64 {
+        self.core.frame_tracker.rebinds()
+    }
+
+    /// :``int``: The maximum number of queued frames of the running or last capture, see the
+    /// ``queue_depth`` option of :meth:`start`.
+    #[getter]
+    pub fn queue_depth(&self) -> usize {
+        self.core.queue_depth()
+    }
+
+    /// :``ResizePolicy``: The resize policy of the running or last capture, see the
+    /// ``resize_policy`` option of :meth:`start`.
+    #[getter]
+    pub fn resize_policy(&self) -> ResizePolicy {
+        self.core.resize_policy()
+    }
+
+    /// :``str``: The alpha of the frames of the running or last capture, ``"straight"`` if the
+    /// ``unpremultiply_alpha`` option of :meth:`start` is set and ``"premultiplied"`` otherwise.
+    #[getter]
+    pub fn alpha_mode(&self) -> &'static str {
+        if self.core.unpremultiply_alpha() {
+            "straight"
+        } else {
+            "premultiplied"
         }
-        let gc_item: GraphicsCaptureItem = capture_target
-            .try_into()
-            .expect("Failed to convert CaptureTarget to GraphicsCaptureItem");
+    }
+
+    /// :``int``: The number of frames that are queued and have not been received yet.
+    #[getter]
+    pub fn queued_frames(&self) -> usize {
+        self.core.queued_frames()
+    }
 
-        self.thread_id.lock().take(); // Clear the thread_id when starting a new capture
+    /// :``int``: The number of frames dropped from the full frame queue since :meth:`start`.
+    #[getter]
+    pub fn queue_drop_count(&self) -> u64 {
+        self.core.frame_tracker.queue_drops()
+    }
 
-        // Clone Arc capture struct members to use them in thread without borrowing
-        let thread_id = self.thread_id.clone();
-        let frame = self.frame.clone();
+    /// :``bool``: True if the content of the last read frame differs from the frame read before it.
+    ///
+    /// Frames also arrive if only the cursor moves or the content is redrawn without changes. Use
+    /// this flag to skip expensive processing of unchanged frames. The comparison samples a sparse
+    /// grid of pixels, so very small changes may go unnoticed. The first read frame always counts
+    /// as changed.
+    #[getter]
+    pub fn frame_changed(&self) -> bool {
+        self.core.frame_tracker.content_changed()
+    }
 
-        // Create a thread to run the capture
-        let capture_thread = thread::spawn(move || -> Result<(), CaptureError> {
-            unsafe {
-                RoInitialize(RO_INIT_MULTITHREADED)?; // Initialize the Windows Runtime
-            };
-            // Create a dispatcher queue for the current thread
-            let options = DispatcherQueueOptions {
-                dwSize: u32::try_from(mem::size_of::<DispatcherQueueOptions>()).unwrap(),
-                threadType: DQTYPE_THREAD_CURRENT,
-                apartmentType: DQTAT_COM_NONE,
-            };
-            let controller = unsafe { CreateDispatcherQueueController(options)? };
-
-            // Create DirectX devices
-            let (d3d_device, d3d_device_context) = create_d3d_device()?;
-            let direct3d_device = create_direct3d_device(&d3d_device)?;
-            // Create frame pool and an associated capture session
-            let pixel_format = DirectXPixelFormat(ColorFormat::default() as i32);
-            let frame_pool = Arc::new(Direct3D11CaptureFramePool::Create(
-                &direct3d_device,
-                pixel_format,
-                1,
-                gc_item.Size()?,
-            )?);
-            let session = frame_pool.CreateCaptureSession(&gc_item)?;
-
-            // Set frame pool frame arrived event
-            let frame_arrived_event_token = frame_pool.FrameArrived(&TypedEventHandler::<
-                Direct3D11CaptureFramePool,
-                IInspectable,
-            >::new({
-                thread_id.lock().replace(unsafe { GetCurrentThreadId() });
-                let frame_pool = frame_pool.clone();
-                let d3d_device = d3d_device.clone();
-                let context = d3d_device_context.clone();
-                let capture_frame = frame.clone();
-
-                let mut last_size = gc_item.Size()?;
-                let direct3d_device_recreate = SendDirectX::new(direct3d_device.clone());
-
-                move |frame, _| {
-                    // Get frame
-                    let frame = frame
-                        .as_ref()
-                        .expect("FrameArrived parameter unexpectedly returned None.")
-                        .TryGetNextFrame()?;
-                    // Get frame time, content size and surface
-                    let frame_content_size = frame.ContentSize()?;
-                    let frame_surface = frame.Surface()?;
-                    // Convert surface to texture
-                    let frame_dxgi_interface =
-                        frame_surface.cast::<IDirect3DDxgiInterfaceAccess>()?;
-                    let frame_texture =
-                        unsafe { frame_dxgi_interface.GetInterface::<ID3D11Texture2D>()? };
-
-                    // Get texture settings
-                    let mut desc = D3D11_TEXTURE2D_DESC::default();
-                    unsafe { frame_texture.GetDesc(&mut desc) }
-
-                    // Check if the size has been changed, and recreate the frame pool if necessary
-                    if frame_content_size.Width != last_size.Width
-                        || frame_content_size.Height != last_size.Height
-                    {
-                        let direct3d_device_recreate = &direct3d_device_recreate;
-                        frame_pool.Recreate(
-                            &direct3d_device_recreate.0,
-                            pixel_format,
-                            1,
-                            frame_content_size,
-                        )?;
-                        last_size = frame_content_size;
-                        return Ok(());
-                    }
-                    // Set width & height
-                    let texture_width = desc.Width;
-                    let texture_height = desc.Height;
-                    // Create a frame
-                    *capture_frame.lock() = Some(Frame::new(
-                        frame_texture,
-                        texture_height,
-                        texture_width,
-                        d3d_device.clone(),
-                        context.clone(),
-                    ));
-                    Result::Ok(())
+    /// :``bool``: Compute the CRC-32 checksum of every frame that is read, to tell corruption
+    /// during the capture apart from bugs in the processing of the frames.
+    ///
+    /// The checksum of the last read frame is available as :attr:`last_checksum`. Reading the same
+    /// frame again must produce the same checksum, otherwise :attr:`checksum_mismatches` is
+    /// increased. Checksums cover every pixel, so they slow down reading. Disabled by default.
+    #[getter]
+    pub fn verify_checksums(&self) -> bool {
+        self.core.frame_tracker.checksums()
+    }
+
+    #[setter]
+    pub fn set_verify_checksums(&self, enabled: bool) {
+        self.core.frame_tracker.set_checksums(enabled);
+    }
+
+    /// :``int | None``: The CRC-32 checksum of the pixels of the last read frame, or None if
+    /// :attr:`verify_checksums` is disabled or no frame has been read since it was enabled. The
+    /// checksum equals ``zlib.crc32`` of the tightly packed pixels of the unflipped frame, e.g. of
+    /// the array of :meth:`frame_contiguous`, so consumers can check their copy of the frame.
+    #[getter]
+    pub fn last_checksum(&self) -> Option<u32> {
+        self.core.frame_tracker.last_checksum()
+    }
+
+    /// :``int``: The number of reads since :meth:`start` that returned a frame with the same ID as
+    /// the previous read but a different checksum, see :attr:`verify_checksums`. Any mismatch
+    /// indicates that frames are corrupted while they are copied from the GPU.
+    #[getter]
+    pub fn checksum_mismatches(&self) -> u64 {
+        self.core.frame_tracker.checksum_mismatches()
+    }
+
+    /// :``bool``: True if the target likely shows protected content that captures as black frames.
+    ///
+    /// Windows can exclude themselves from captures, and DRM protected video is blanked by the
+    /// compositor. Both cases produce all-black frames instead of an error. The flag is set if the
+    /// window target is protected, or if a sparse grid of pixels of the last read frame is black.
+    /// Legitimately black content is therefore reported as protected as well.
+    #[getter]
+    pub fn is_protected(&self) -> bool {
+        let window_protected = match self.core.target() {
+            Some(CaptureTarget::Window(window)) => window.is_protected(),
+            _ => false,
+        };
+        window_protected || self.core.frame_tracker.blank()
+    }
+
+    /// :``bool``: True if the captured window was closed or the monitor was disconnected.
+    ///
+    /// The capture thread ends on its own once the target is closed, so the capture is no longer
+    /// :attr:`active`. Threads that wait in :meth:`wait_for_frame` return None and iterations over
+    /// :meth:`frames` end instead of waiting for frames that never arrive. The flag is reset by
+    /// :meth:`start`, see its ``on_target_closed`` option to be notified.
+    #[getter]
+    pub fn closed(&self) -> bool {
+        self.core.is_closed()
+    }
+
+    /// :``bool``: True if the capture ended because its window target was resized under
+    /// ``ResizePolicy.Error``.
+    ///
+    /// The window is still open, so :attr:`closed` stays False and the ``on_target_closed``
+    /// callback of :meth:`start` is not called. The capture is no longer :attr:`active`, and
+    /// :meth:`try_stop` raises the error. The flag is reset by :meth:`start`.
+    #[getter]
+    pub fn resized(&self) -> bool {
+        self.core.is_resized()
+    }
+
+    /// is_receiving(within_ms: int = 1000) -> bool
+    /// Check whether the latest frame arrived recently.
+    ///
+    /// Unlike :attr:`active`, which only reports that the capture thread is running, this
+    /// distinguishes a healthy capture from one whose source stopped delivering frames. The
+    /// compositor only delivers frames when the target changes, so static targets, e.g. an idle
+    /// window or a paused video, produce no frames and are reported as not receiving as well. Pass
+    /// a longer ``within_ms`` for targets that update rarely. Captures started with
+    /// ``fixed_interval_ms`` repeat the latest content with new timestamps, so they keep receiving
+    /// while the target is static.
+    ///
+    /// Args:
+    ///     within_ms: The maximum age of the latest frame in milliseconds.
+    ///
+    /// :returns: True if the latest frame arrived within the last ``within_ms`` milliseconds, else
+    ///     False.
+    #[pyo3(signature = (within_ms=1000))]
+    pub fn is_receiving(&self, within_ms: u64) -> bool {
+        let Some(frame_time) = self.core.latest_frame().map(|frame| frame.time) else {
+            return false;
+        };
+        // Frame times are in 100ns units
+        let within = i64::try_from(within_ms.saturating_mul(10_000)).unwrap_or(i64::MAX);
+        system_relative_time().is_ok_and(|now| now - frame_time <= within)
+    }
+
+    /// :``bool``: True if a frame has arrived that has not been returned by :meth:`frame` yet.
+    #[getter]
+    pub fn has_new_frame(&self) -> bool {
+        self.core.frame_tracker.has_unread()
+    }
+
+    /// :``list[tuple[int, int, int, int]] | None``: The regions (x, y, width, height) of the latest
+    /// frame that changed since the frame stored before it.
+    ///
+    /// Delta encoders and remote desktop streams can use the regions to only process changed
+    /// pixels. Regions are given in the coordinates of the returned frames, i.e. after scaling,
+    /// client region cropping and flipping, and may overlap. None if the changes are unknown, which
+    /// is the case for the first frame, after a resize, with ``fixed_interval_ms`` and on Windows
+    /// builds that do not report changed regions. Treat the whole frame as changed in that case.
+    #[getter]
+    pub fn dirty_regions(&self) -> PyResult<Option<Vec<RegionTuple>>> {
+        let Some(frame) = self.core.latest_frame() else {
+            return Ok(None);
+        };
+        let Some(dirty_regions) = &frame.dirty_regions else {
+            return Ok(None);
+        };
+        let (width, height) = frame.output_size();
+        let crop = self.client_crop(&frame)?.unwrap_or(Region {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+        let regions = dirty_regions
+            .iter()
+            .filter_map(|&region| {
+                // Clip the region to the crop and move it into the crop coordinates
+                let region = frame.output_bounds(region);
+                let x = region.x.max(crop.x);
+                let y = region.y.max(crop.y);
+                let end_x = (region.x + region.width).min(crop.x + crop.width);
+                let end_y = (region.y + region.height).min(crop.y + crop.height);
+                if end_x <= x || end_y <= y {
+                    return None;
                 }
-            }))?;
-            session.StartCapture()?;
-
-            // Create message loops. Pump messages while the message is not WM_QUIT
-            let mut msg = MSG::default();
-            unsafe {
-                while GetMessageW(&mut msg, None, 0, 0).as_bool() {
-                    TranslateMessage(&msg);
-                    DispatchMessageW(&msg);
+                let (mut x, mut y, w, h) = (x - crop.x, y - crop.y, end_x - x, end_y - y);
+                if self.flip.horizontal {
+                    x = crop.width - x - w;
                 }
-            }
-            // Shutdown dispatcher queue
-            let async_shutdown = controller.ShutdownQueueAsync()?;
-            async_shutdown.SetCompleted(&AsyncActionCompletedHandler::new(
-                move |_, _| -> Result<(), windows::core::Error> {
-                    unsafe { PostQuitMessage(0) };
-                    Ok(())
-                },
-            ))?;
-
-            // Remove event handlers and close the frame pool and capture session
-            frame_pool
-                .RemoveFrameArrived(frame_arrived_event_token)
-                .expect("Failed to remove Frame Arrived event handler");
-            frame_pool.Close().expect("Failed to Close Frame Pool");
-            session.Close().expect("Failed to Close Capture Session");
-            unsafe { RoUninitialize() };
-            Ok(())
-        });
-        self.thread = Some(capture_thread);
+                if self.flip.vertical {
+                    y = crop.height - y - h;
+                }
+                Some((x, y, w, h))
+            })
+            .collect();
+        Ok(Some(regions))
+    }
 
-        // Wait for the first frame to be ready if await_first_frame is set to true or None
-        if await_first_frame.unwrap_or(true) {
-            while self.frame.lock().is_none() & self.thread.is_some() {
-                sleep(Duration::from_millis(10));
-            }
+    /// ping(timeout_ms: int = 1000) -> bool
+    /// Check that the message loop of the capture thread is alive and responsive.
+    ///
+    /// Posts a message to the capture thread and waits for its acknowledgment. Use this to detect
+    /// a deadlocked capture thread in long-running captures, which :attr:`active` still reports as
+    /// running.
+    ///
+    /// Args:
+    ///     timeout_ms: The maximum time to wait for the acknowledgment in milliseconds.
+    ///
+    /// :returns: True if the capture thread responded within the timeout, else False.
+    #[pyo3(signature = (timeout_ms=1000))]
+    pub fn ping(&self, py: Python, timeout_ms: u64) -> bool {
+        py.allow_threads(|| self.core.ping(Duration::from_millis(timeout_ms)))
+    }
+
+    /// stats() -> CaptureStats
+    /// Return the frame statistics since the last call of :meth:`start`.
+    ///
+    /// :returns: The number of produced, consumed and dropped frames.
+    pub fn stats(&self) -> CaptureStats {
+        self.core.stats()
+    }
+
+    /// frame(layout: str | None = None) -> np.ndarray
+    /// Convert the latest frame to an array and return it.
+    ///
+    /// Args:
+    ///     layout: The axis order of the array. ``"hwc"`` returns the channels of each pixel next
+    ///         to each other with dimensions [h w 4]. ``"chw"`` returns planar channels with
+    ///         dimensions [4 h w] as expected by many ML frameworks. The transpose happens during
+    ///         the copy, so the array is C-contiguous in both layouts. Defaults to ``"hwc"``.
+    ///
+    /// :returns: The frame as a 3D NumPy array with the dimensions of ``layout``. One of the
+    ///     buffers of :meth:`configure_double_buffer` if they are configured.
+    #[pyo3(name = "frame", signature = (layout=None))]
+    pub fn py_frame(&self, py: Python, layout: Option<&str>) -> PyResult<PyObject> {
+        let parsed_layout: Layout = layout.map(str::parse).transpose()?.unwrap_or_default();
+        let back_buffer = match (&*self.double_buffer.lock(), parsed_layout) {
+            (Some(buffer), Layout::Hwc) => Some(buffer.buffers[1 - buffer.front].clone_ref(py)),
+            _ => None,
+        };
+        let Some(back_buffer) = back_buffer else {
+            return self
+                .read_frame(py, layout, true)
+                .map(|(img_array, _)| img_array);
+        };
+        // The lock is not held during the copy, which can emit a stale frame warning that runs
+        // arbitrary Python code
+        self.frame_into(py, back_buffer.as_ref(py))?;
+        if let Some(buffer) = self.double_buffer.lock().as_mut() {
+            buffer.front = 1 - buffer.front;
         }
-        Ok(())
+        Ok(back_buffer.into_py(py))
     }
 
-    /// :``bool``: True if the capture thread is running, False otherwise.
-    #[getter]
-    pub fn active(&self) -> bool {
-        self.thread.is_some()
+    /// configure_double_buffer(shape: tuple[int, int, int] | None) -> tuple[np.ndarray, np.ndarray] | None
+    /// Let :meth:`frame` write into two preallocated arrays in turn instead of new arrays.
+    ///
+    /// Each call of :meth:`frame` copies the latest frame into the array that was not returned
+    /// last, and returns it once the copy is complete. The array returned by the previous call is
+    /// left untouched, so it can be processed while the next frame is read, without allocating an
+    /// array per frame. Unlike :meth:`frame_into`, the arrays are managed by the capture. An array
+    /// is overwritten by the second call of :meth:`frame` after it was returned, so it has to be
+    /// processed or copied before then.
+    ///
+    /// The buffers are kept across :meth:`stop` and :meth:`start`. They only hold ``Rgba8`` frames
+    /// with the ``"hwc"`` layout, frames with the ``"chw"`` layout are still returned in new
+    /// arrays. Reading fails if the frame shape differs from the buffers, e.g. after the target
+    /// was resized, so configure the buffers again with the new shape in that case.
+    ///
+    /// Args:
+    ///     shape: The shape (height, width, 4) of the frames, e.g. ``(capture.height,
+    ///         capture.width, 4)``. If None, the buffers are released and :meth:`frame` returns
+    ///         new arrays again.
+    ///
+    /// :returns: The two buffers, or None if they were released.
+    pub fn configure_double_buffer(
+        &self,
+        py: Python,
+        shape: Option<(usize, usize, usize)>,
+    ) -> PyResult<Option<(PyObject, PyObject)>> {
+        let Some(shape) = shape else {
+            *self.double_buffer.lock() = None;
+            return Ok(None);
+        };
+        let (height, width, channels) = shape;
+        if height == 0 || width == 0 || channels != 4 {
+            return Err(CaptureError::InvalidDoubleBufferShape(shape).into());
+        }
+        let buffers: [Py<PyArray3<u8>>; 2] =
+            [(); 2].map(|()| PyArray3::zeros(py, [height, width, 4], false).into());
+        let arrays = (
+            buffers[0].clone_ref(py).into_py(py),
+            buffers[1].clone_ref(py).into_py(py),
+        );
+        // The first frame is written into the second buffer
+        *self.double_buffer.lock() = Some(DoubleBuffer { buffers, front: 0 });
+        Ok(Some(arrays))
     }
 
-    /// Stop the capture thread, wait for it to join and invalidate the last frame.
-    pub fn stop(&mut self) {
-        // If the thread_id is set, send a WM_QUIT message to the message pumping thread. The
-        // message pumping thread will receive the WM_QUIT message, stop its loop and close the
-        // dispatcher queue
-        if let Some(thread_id) = self.thread_id.lock().take() {
-            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+    /// share() -> CaptureHandle
+    /// Create a read-only handle to this capture for another consumer, e.g. another thread.
+    ///
+    /// Handles read the frames of this capture without borrowing it, so they can wait for frames
+    /// while the capture is stopped or restarted from another thread. Each handle tracks the frames
+    /// it has returned on its own, so handles do not take new frames away from each other or from
+    /// :meth:`wait_for_frame` of the capture. Handles keep the capture alive, so the capture thread
+    /// runs until :meth:`stop` is called or the capture and all of its handles are deleted.
+    ///
+    /// :returns: A new :class:`.CaptureHandle`.
+    pub fn share(slf: &PyCell<Self>) -> CaptureHandle {
+        CaptureHandle {
+            watch: slf.borrow().core.watch(),
+            capture: slf.into(),
+            last_frame_time: Mutex::new(None),
+        }
+    }
+
+    /// frame_on_monitor(monitor: Monitor, layout: str | None = None) -> np.ndarray
+    /// Return the part of the latest frame of a window target that is shown on ``monitor``.
+    ///
+    /// Windows that span several monitors are captured as a whole. This crops the frame to the
+    /// intersection of the window and the monitor, e.g. to process only the half of a window on
+    /// the secondary display. The intersection is computed for each call so it follows the window
+    /// when it moves. Frames cropped to a ``client_region`` are cropped to the part of the region
+    /// on the monitor.
+    ///
+    /// Args:
+    ///     monitor: The :class:`.Monitor` whose part of the window is returned.
+    ///     layout: The axis order of the array, see :meth:`frame`. Defaults to ``"hwc"``.
+    ///
+    /// :returns: The part of the frame on the monitor as a 3D NumPy array.
+    #[pyo3(signature = (monitor, layout=None))]
+    pub fn frame_on_monitor(
+        &self,
+        py: Python,
+        monitor: &Monitor,
+        layout: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let layout: Layout = layout.map(str::parse).transpose()?.unwrap_or_default();
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
         }
-        if let Some(thread) = self.thread.take() {
-            let _ = thread.join().expect("Failed to join capture thread");
+        let Some(CaptureTarget::Window(window)) = self.core.target() else {
+            return Err(CaptureError::MonitorPortionWithoutWindow.into());
+        };
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        let (width, height) = frame.output_size();
+        let frame_region = Region {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let crop = self.client_crop(&frame)?.unwrap_or(frame_region);
+        let region = monitor_region(&window, monitor)
+            .map(|region| frame.output_bounds(region))
+            .and_then(|region| region.intersection(crop))
+            .ok_or(CaptureError::WindowNotOnMonitor)?;
+        let img_array = mapped_frame.region_to_pyarray(py, region, self.flip, layout)?;
+        self.core.frame_tracker.frame_read(frame.id, &mapped_frame);
+        Ok(img_array.into())
+    }
+
+    /// frame_at_size(size: tuple[int, int], preserve_aspect: bool = False, pad_color: tuple[int, int, int, int] = (0, 0, 0, 255)) -> np.ndarray
+    /// Resize the latest frame to a fixed size and return it as an array.
+    ///
+    /// Useful for ML models with a fixed input size. The frame is resampled with nearest neighbor
+    /// interpolation while it is copied out, so no full size array is allocated in between. Only
+    /// ``Rgba8`` frames can be resized.
+    ///
+    /// Args:
+    ///     size: The (width, height) of the returned array.
+    ///     preserve_aspect: If True, the frame is scaled to fit into ``size`` without distortion
+    ///         and centered, and the remaining bars are filled with ``pad_color``. If False, the
+    ///         frame is stretched to ``size``.
+    ///     pad_color: The RGBA color of the letterbox bars.
+    ///
+    /// :returns: The frame as a 3D NumPy array with dimensions [height width 4].
+    #[pyo3(signature = (size, preserve_aspect=false, pad_color=(0, 0, 0, 255)))]
+    pub fn frame_at_size(
+        &self,
+        py: Python,
+        size: (u32, u32),
+        preserve_aspect: bool,
+        pad_color: (u8, u8, u8, u8),
+    ) -> PyResult<PyObject> {
+        let (red, green, blue, alpha) = pad_color;
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            let img_array = mapped_frame.region_to_sized_pyarray(
+                py,
+                region,
+                self.flip,
+                size,
+                preserve_aspect,
+                [red, green, blue, alpha],
+            )?;
+            Ok(img_array.into())
+        })
+    }
+
+    /// desktop_to_frame(x: int, y: int) -> tuple[int, int] | None
+    /// Convert a virtual desktop point to the pixel of the frames of a monitor capture.
+    ///
+    /// Pixel (0, 0) of a monitor capture is the top left corner of the monitor, while window
+    /// positions, cursor positions and most automation tools use virtual desktop coordinates. The
+    /// conversion accounts for the position of the monitor, the ``scale`` option and flips.
+    /// Processes that are not DPI aware see scaled desktop coordinates, which are converted to the
+    /// physical pixels of the frames as well.
+    ///
+    /// Args:
+    ///     x: The horizontal position on the virtual desktop.
+    ///     y: The vertical position on the virtual desktop.
+    ///
+    /// :returns: The (column, row) of the pixel in the frame arrays, or None if the point is not on
+    ///     the monitor.
+    pub fn desktop_to_frame(&self, x: i32, y: i32) -> PyResult<Option<(u32, u32)>> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
         }
-        self.frame.lock().take(); // Clear the frame when the capture is stopped
+        let Some(CaptureTarget::Monitor(monitor)) = self.core.target() else {
+            return Err(CaptureError::PointMappingWithoutMonitor.into());
+        };
+        let rect = monitor.rect()?;
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let (width, height) = frame.output_size();
+        let monitor_size = (
+            (rect.right - rect.left).unsigned_abs(),
+            (rect.bottom - rect.top).unsigned_abs(),
+        );
+        let offset = (x - rect.left, y - rect.top);
+        Ok(
+            map_point(offset, monitor_size, (width, height)).map(|(column, row)| {
+                let column = if self.flip.horizontal {
+                    width - 1 - column
+                } else {
+                    column
+                };
+                let row = if self.flip.vertical {
+                    height - 1 - row
+                } else {
+                    row
+                };
+                (column, row)
+            }),
+        )
     }
 
-    /// frame() -> np.ndarray
-    /// Convert the latest frame to an array and return it.
+    /// frame_pil(alpha: bool = True) -> PIL.Image.Image
+    /// Return the latest frame as a Pillow image.
     ///
-    /// :returns: The frame as a 3D NumPy array with dimensions [h w 4].
-    #[pyo3(name = "frame")]
-    pub fn py_frame(&self, py: Python) -> PyResult<Py<PyArray3<u8>>> {
-        if self.thread.is_none() {
-            return Err(PyRuntimeError::new_err("Capture thread is not running."));
-        }
-        let frame_guard = self.frame.lock();
-        let frame = frame_guard.as_ref().ok_or(CaptureError::NoFrameAvailable)?;
-        let data = frame.materialize()?;
-        let img_array = ndarray::arr1(data);
-        // For some reason, only the height of the frame is correct and the texture includes a white
-        // border. We calculate the width according to the number of available elements and later
-        // crop the frame back to the intended size
-        let height: usize = frame.height.try_into()?;
-        let dims: [usize; 3] = [height, data.len() / height / 4, 4];
-        let img_array = img_array
-            .into_shape(dims)
-            .expect("Failed to reshape frame into the correct dimensions");
-        let width: usize = frame.width.try_into()?;
-        // Crop image into the correct dimensions and discard any borders
-        let img_array = img_array.slice(s![0..height, 0..width, ..]).to_pyarray(py);
-        Ok(img_array.to_owned())
-    }
-}
-
-// Drop trait implementation to stop the capture thread when the Capture struct is dropped. This
-// trait is also executed when the Capture struct goes out of scope in Python, making sure that the
-// capture thread is stopped
-impl Drop for Capture {
-    fn drop(&mut self) {
-        self.stop();
+    /// The image is created from the frame data directly, so no NumPy array is allocated in
+    /// between. Only ``Rgba8`` frames can be converted, since Pillow has no mode for 16 bit float
+    /// channels. Requires Pillow to be installed.
+    ///
+    /// Args:
+    ///     alpha: Returns an ``RGBA`` image if True, or an ``RGB`` image without the alpha channel
+    ///         if False.
+    ///
+    /// Returns:
+    ///     The frame as a ``PIL.Image.Image``.
+    #[pyo3(signature = (alpha=true))]
+    pub fn frame_pil(&self, py: Python, alpha: bool) -> PyResult<PyObject> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            Ok(mapped_frame
+                .region_to_pil(py, region, self.flip, alpha)?
+                .into())
+        })
+    }
+
+    /// frame_alpha() -> np.ndarray
+    /// Extract the alpha channel of the latest frame and return it.
+    ///
+    /// The channel is extracted without creating the RGBA array, so this is cheaper than
+    /// slicing the array of :meth:`frame`, e.g. to build a hit-test mask of a window with
+    /// transparent regions. Opaque sources such as monitors and most windows have a uniform alpha
+    /// of 255. Only ``Rgba8`` frames are supported.
+    ///
+    /// :returns: The alpha channel as a 2D ``uint8`` NumPy array with dimensions [h w].
+    pub fn frame_alpha(&self, py: Python) -> PyResult<PyObject> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            Ok(mapped_frame.region_to_alpha(py, region, self.flip)?.into())
+        })
     }
+
+    /// frame_rgb565() -> np.ndarray
+    /// Convert the latest frame to a 16 bit RGB565 array and return it.
+    ///
+    /// Each pixel is packed into a ``uint16`` with 5 bits of red in the high bits, 6 bits of green
+    /// and 5 bits of blue in the low bits, i.e. ``(r >> 3) << 11 | (g >> 2) << 5 | b >> 3``. The
+    /// alpha channel is dropped. The array takes half the bytes of :meth:`frame`, which helps when
+    /// frames are streamed over a network. Only ``Rgba8`` frames can be converted.
+    ///
+    /// :returns: The frame as a 2D ``uint16`` NumPy array with dimensions [h w].
+    pub fn frame_rgb565(&self, py: Python) -> PyResult<PyObject> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            Ok(mapped_frame.region_to_rgb565(py, region, self.flip)?.into())
+        })
+    }
+
+    /// shared_texture_handle() -> int
+    /// Copy the latest frame into a shareable GPU texture and return its shared handle.
+    ///
+    /// The handle lets other Direct3D devices, e.g. of a rendering engine or a CUDA interop
+    /// pipeline, open the frame with ``ID3D11Device::OpenSharedResource`` without copying it to
+    /// the CPU. The texture has the size of the full frame and the DXGI format of the capture's
+    /// ``color_format``. ``client_region`` and flips are not applied.
+    ///
+    /// The handle is a legacy DXGI shared handle, not an NT handle, so it must not be closed. The
+    /// capture keeps the texture alive until the next call of this method or until the capture is
+    /// stopped, after which the handle becomes invalid. Resources opened from the handle stay
+    /// valid on their own. The copy is submitted before the handle is returned, but consumers on
+    /// other devices are responsible for any further synchronization.
+    ///
+    /// :returns: The shared handle of the texture as an integer.
+    pub fn shared_texture_handle(&self) -> PyResult<isize> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let (texture, handle) = frame.shared_texture().map_err(CaptureError::from)?;
+        *self.shared_texture.lock() = Some(texture);
+        Ok(handle)
+    }
+
+    /// frame_into(out: np.ndarray)
+    /// Copy the latest frame into an existing array.
+    ///
+    /// Reusing the same array for every frame avoids allocating a new array per frame, which
+    /// reduces garbage collection churn in high frame rate loops. Only ``Rgba8`` frames can be
+    /// copied, since the array must have dtype uint8.
+    ///
+    /// Args:
+    ///     out: A writeable uint8 array with the dimensions [h w 4] of the frame. Frames change
+    ///         their dimensions if the target is resized, so check :attr:`width` and
+    ///         :attr:`height` and reallocate the array if the copy fails.
+    pub fn frame_into(&self, py: Python, out: &PyArray3<u8>) -> PyResult<()> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            mapped_frame.copy_region_into(region, self.flip, out)
+        })
+    }
+
+    /// recent_frames(count: int | None = None) -> list[np.ndarray]
+    /// Convert the most recent frames to arrays and return them, oldest first.
+    ///
+    /// Frames are only kept if ``history_size`` was passed to :meth:`start`. The latest frame is
+    /// the last element. The frames are not marked as read by this method.
+    ///
+    /// Args:
+    ///     count: The maximum number of frames to return. Returns all kept frames if None.
+    ///
+    /// :returns: The frames as 3D NumPy arrays with dimensions [h w 4].
+    #[pyo3(signature = (count=None))]
+    pub fn recent_frames(&self, py: Python, count: Option<usize>) -> PyResult<Vec<PyObject>> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frames = self.core.recent_frames(count.unwrap_or(usize::MAX));
+        frames
+            .iter()
+            .map(|frame| self.frame_to_pyarray(py, frame))
+            .collect()
+    }
+
+    /// frame_averaged(n: int) -> np.ndarray
+    /// Average the most recent frames and return the mean as an array.
+    ///
+    /// Averaging reduces noise of static content, e.g. of low-light camera previews or dithered
+    /// video, at the cost of latency and smearing: moving content leaves trails across the
+    /// averaged frames, and changes only show up fully after ``n`` new frames. The frames are
+    /// summed one at a time in Rust, which avoids stacking them into a large array in NumPy.
+    ///
+    /// Frames are taken from the history, so ``history_size`` has to be passed to :meth:`start`.
+    /// Fewer frames are averaged while the history holds less than ``n`` frames. Frames with a
+    /// different size than the latest frame, e.g. from before the target was resized, are left
+    /// out. Only ``Rgba8`` frames can be averaged. The frames are not marked as read by this
+    /// method.
+    ///
+    /// Args:
+    ///     n: The number of recent frames to average.
+    ///
+    /// :returns: The rounded mean as a 3D NumPy array with dimensions [h w 4].
+    pub fn frame_averaged(&self, py: Python, n: usize) -> PyResult<PyObject> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        if n == 0 {
+            return Err(CaptureError::InvalidAverageCount.into());
+        }
+        let frames = self.core.recent_frames(n);
+        let latest = frames.last().ok_or(CaptureError::NoFrameAvailable)?;
+        let latest_size = latest.output_size();
+        let region = self.client_crop(latest)?.unwrap_or(Region {
+            x: 0,
+            y: 0,
+            width: latest_size.0,
+            height: latest_size.1,
+        });
+        let mut accumulator = FrameAccumulator::new(
+            usize::try_from(region.height)?,
+            usize::try_from(region.width)?,
+        );
+        for frame in &frames {
+            // The crop region of the latest frame can exceed older frames of a smaller size
+            if frame.output_size() != latest_size {
+                continue;
+            }
+            let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+            mapped_frame.accumulate_region(&mut accumulator, region)?;
+        }
+        Ok(accumulator.average_to_pyarray(py, self.flip).into())
+    }
+
+    /// recv(timeout_ms: int | None = None) -> np.ndarray | None
+    /// Block until a queued frame is available, remove it from the queue and return it.
+    ///
+    /// Frames are only queued if ``queue_depth`` was passed to :meth:`start`. Frames are received
+    /// in the order they arrived, and queued frames are still received after the target was
+    /// closed. The frames are not marked as read by this method.
+    ///
+    /// Args:
+    ///     timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+    ///
+    /// :returns: The oldest queued frame as a 3D NumPy array with dimensions [h w 4], or None if
+    ///     no frame was queued within the timeout, or the queue is empty and the capture was
+    ///     stopped, failed or the target was closed.
+    #[pyo3(signature = (timeout_ms=None))]
+    pub fn recv(&self, py: Python, timeout_ms: Option<u64>) -> PyResult<Option<PyObject>> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+        // Release the GIL while waiting so that other Python threads can continue
+        let frame = py.allow_threads(|| self.core.recv_queued(deadline));
+        frame
+            .map(|frame| self.frame_to_pyarray(py, &frame))
+            .transpose()
+    }
+
+    /// try_recv() -> np.ndarray | None
+    /// Remove the oldest queued frame from the queue and return it without blocking.
+    ///
+    /// :returns: The oldest queued frame as a 3D NumPy array with dimensions [h w 4], or None if
+    ///     the queue is empty.
+    pub fn try_recv(&self, py: Python) -> PyResult<Option<PyObject>> {
+        self.recv(py, Some(0))
+    }
+
+    /// read(auto_crop: bool = False, tolerance: int = 16) -> FrameData
+    /// Convert the latest frame to an array and return it together with its metadata.
+    ///
+    /// The array and the metadata are taken from the same acquired frame, so they are always
+    /// consistent, unlike separate calls of :meth:`frame` and the properties.
+    ///
+    /// Args:
+    ///     auto_crop: Crop uniform black borders from the array, e.g. the letterbox bars of
+    ///         fullscreen games. The borders are detected for each frame, and the region of the
+    ///         frame inside the borders is returned as :attr:`.FrameData.crop`. Frames that are
+    ///         black as a whole are not cropped. Only supported for ``Rgba8`` frames.
+    ///     tolerance: The largest color value of a pixel in the range [0, 255] that still counts
+    ///         as black, so near-black bars of compressed video are cropped as well.
+    ///
+    /// :returns: The frame array, timestamp, frame ID, width and height.
+    #[pyo3(signature = (auto_crop=false, tolerance=16))]
+    pub fn read(&self, py: Python, auto_crop: bool, tolerance: u8) -> PyResult<FrameData> {
+        self.read_latest(py, true, |frame, mapped_frame, region| {
+            let (array, crop) = if auto_crop {
+                let content = mapped_frame
+                    .content_region(region, tolerance)
+                    .map_err(CaptureError::from)?;
+                let array = mapped_frame.region_to_pyarray(py, content, self.flip, Layout::Hwc)?;
+                // The crop is reported in the coordinates of the flipped and client cropped frame
+                let mut crop = (
+                    content.x - region.x,
+                    content.y - region.y,
+                    content.width,
+                    content.height,
+                );
+                if self.flip.horizontal {
+                    crop.0 = region.width - crop.0 - content.width;
+                }
+                if self.flip.vertical {
+                    crop.1 = region.height - crop.1 - content.height;
+                }
+                (array, Some(crop))
+            } else {
+                let array = mapped_frame.region_to_pyarray(py, region, self.flip, Layout::Hwc)?;
+                (array, None)
+            };
+            // The content may only partly overlap the client crop
+            let (content_width, content_height) = frame.output_content_size();
+            Ok(FrameData {
+                array: array.into(),
+                // Frame times are in 100ns units
+                timestamp: frame.time as f64 / 10_000_000.0,
+                frame_id: frame.id,
+                width: region.width,
+                height: region.height,
+                content_width: content_width.saturating_sub(region.x).min(region.width),
+                content_height: content_height.saturating_sub(region.y).min(region.height),
+                crop,
+                missed_since_last: frame.missed_frames,
+            })
+        })
+    }
+
+    /// frame_contiguous() -> np.ndarray
+    /// Convert the latest frame to a tightly packed array and return it.
+    ///
+    /// The frame is copied row by row, so the array is C-contiguous and never contains the row
+    /// padding of the underlying texture. Use this for libraries that cannot handle strided data.
+    ///
+    /// :returns: The frame as a C-contiguous 3D NumPy array with dimensions [h w 4].
+    pub fn frame_contiguous(&self, py: Python) -> PyResult<PyObject> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            Ok(mapped_frame
+                .region_to_contiguous_pyarray(py, region, self.flip)?
+                .into())
+        })
+    }
+
+    /// wait_for_frame(timeout_ms: int | None = None) -> np.ndarray | None
+    /// Block until a new frame is available and return it.
+    ///
+    /// A frame is new if it has not been returned by :meth:`frame` or any other frame method yet.
+    /// If such a frame is already available, it is returned immediately.
+    ///
+    /// Args:
+    ///     timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+    ///
+    /// :returns: The frame as a 3D NumPy array with dimensions [h w 4], or None if no new frame
+    ///     arrived within the timeout, the capture was stopped or failed or the target was closed.
+    pub fn wait_for_frame(
+        &self,
+        py: Python,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Option<PyObject>> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+        // Release the GIL while waiting so that other Python threads can continue
+        let frame_available = py.allow_threads(|| {
+            let mut frame_guard = self.core.frame.lock();
+            while !self.core.frame_tracker.has_unread()
+                && self.core.running.load(Ordering::SeqCst)
+                && !self.core.has_thread_exited()
+                && !self.core.is_closed()
+            {
+                match deadline {
+                    Some(deadline) => {
+                        if self
+                            .core
+                            .frame_arrived
+                            .wait_until(&mut frame_guard, deadline)
+                            .timed_out()
+                        {
+                            break;
+                        }
+                    }
+                    None => self.core.frame_arrived.wait(&mut frame_guard),
+                }
+            }
+            self.core.frame_tracker.has_unread()
+        });
+        if !frame_available {
+            return Ok(None);
+        }
+        self.py_frame(py, None).map(Some)
+    }
+
+    /// frames(timeout_ms: int | None = None) -> FrameIterator
+    /// Iterate over new frames as they arrive.
+    ///
+    /// Each iteration blocks until a new frame is available, see :meth:`wait_for_frame`. The
+    /// iteration stops once the capture is stopped or no new frame arrives within the timeout.
+    ///
+    /// Args:
+    ///     timeout_ms: The maximum time to wait for each frame in milliseconds. Waits indefinitely
+    ///         if None.
+    ///
+    /// :returns: An iterator over the frames as 3D NumPy arrays with dimensions [h w 4].
+    pub fn frames(slf: PyRef<'_, Self>, timeout_ms: Option<u64>) -> FrameIterator {
+        FrameIterator {
+            capture: slf.into(),
+            timeout_ms,
+        }
+    }
+
+    /// frame_bytes() -> tuple[bytes, int, int, int]
+    /// Copy the latest frame into a bytes object.
+    ///
+    /// The bytes contain the RGBA pixels row by row. Rows are ``stride`` bytes apart, which can be
+    /// more than ``4 * width`` because of padding at the end of each row. Frames that are cropped
+    /// to a client region or a child window are packed without padding. Flips are not applied.
+    ///
+    /// :returns: The tuple (data, width, height, stride).
+    pub fn frame_bytes(&self, py: Python) -> PyResult<(Py<PyBytes>, u32, u32, u32)> {
+        self.read_latest(py, true, |_, mapped_frame, region| {
+            let full_frame =
+                (region.width, region.height) == (mapped_frame.width, mapped_frame.height);
+            if full_frame {
+                let data = PyBytes::new(py, mapped_frame.data());
+                return Ok((
+                    data.into(),
+                    region.width,
+                    region.height,
+                    mapped_frame.row_pitch,
+                ));
+            }
+            let data = PyBytes::new(py, &mapped_frame.packed_region(region));
+            let stride = region.width * mapped_frame.color_format.pixel_size();
+            Ok((data.into(), region.width, region.height, stride))
+        })
+    }
+
+    /// frame_info() -> tuple[int, int, int, int]
+    /// Return the memory layout of the latest frame.
+    ///
+    /// The stride is the number of bytes between the starts of two rows. It can be larger than the
+    /// width times the pixel size because rows may be padded for alignment. The frame is not marked
+    /// as read by this method. The layout is the one of the whole mapped frame, so
+    /// ``client_region`` and the crop of child windows are not applied.
+    ///
+    /// :returns: The tuple (width, height, stride, channels).
+    pub fn frame_info(&self) -> PyResult<(u32, u32, u32, u32)> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        // The row pitch is only known once the frame has been mapped
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        Ok((
+            mapped_frame.width,
+            mapped_frame.height,
+            mapped_frame.row_pitch,
+            4,
+        ))
+    }
+
+    /// frame_hash() -> int
+    /// Return a hash of the pixels of the latest frame.
+    ///
+    /// The hash covers every pixel (FNV-1a over the frame bytes without row padding), so any pixel
+    /// change produces a different hash with high probability. Hashes are stable across runs and
+    /// can be used to skip identical frames without copying them into an array. The frame is not
+    /// marked as read by this method. The hash covers the whole frame, so ``client_region`` and the
+    /// crop of child windows are not applied.
+    ///
+    /// :returns: The 64-bit hash of the latest frame.
+    pub fn frame_hash(&self, py: Python) -> PyResult<u64> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        Ok(py.allow_threads(move || mapped_frame.pixel_hash()))
+    }
+
+    /// frames_differ(threshold: float = 0.01, downsample: int = 1) -> bool
+    /// Check whether the latest frame differs from the frame of the previous call.
+    ///
+    /// The pixels are compared in Rust, so motion-triggered recordings do not have to copy two
+    /// arrays into Python and diff them there. The first call after :meth:`start` has no frame to
+    /// compare with and returns True, as do calls after the frame size changed. The frame is not
+    /// marked as read by this method. The whole frame is compared, so ``client_region`` and the
+    /// crop of child windows are not applied.
+    ///
+    /// Args:
+    ///     threshold: The fraction of pixels in the range [0, 1] that has to be exceeded for the
+    ///         frames to differ. A pixel changes if any of its channels changes.
+    ///     downsample: Compares only every ``downsample``-th pixel of every ``downsample``-th
+    ///         row, which speeds up the comparison of large frames. Changes between the compared
+    ///         pixels go unnoticed.
+    ///
+    /// :returns: True if the fraction of changed pixels exceeds ``threshold``.
+    #[pyo3(signature = (threshold=0.01, downsample=1))]
+    pub fn frames_differ(&self, py: Python, threshold: f64, downsample: u32) -> PyResult<bool> {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(CaptureError::InvalidDifferenceThreshold(threshold).into());
+        }
+        if downsample == 0 {
+            return Err(CaptureError::InvalidDownsample.into());
+        }
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        let sample = py.allow_threads(move || mapped_frame.sample(downsample));
+        let mut previous_sample = self.previous_sample.lock();
+        let differ = previous_sample
+            .as_ref()
+            .is_none_or(|previous| sample.changed_fraction(previous) > threshold);
+        *previous_sample = Some(sample);
+        Ok(differ)
+    }
+
+    /// frame_age_ms() -> float
+    /// Return the time that has passed since the latest frame was rendered.
+    ///
+    /// Real-time applications can use the age to detect that they are falling behind and should
+    /// drop work.
+    ///
+    /// :returns: The age of the latest frame in milliseconds.
+    pub fn frame_age_ms(&self) -> PyResult<f64> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame_time = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?
+            .time;
+        let now = system_relative_time().map_err(CaptureError::from)?;
+        // Times are in 100ns units
+        Ok((now - frame_time) as f64 / 10_000.0)
+    }
+
+    /// frame_view() -> FrameView
+    /// Map the latest frame into memory and return a read-only view on its pixels.
+    ///
+    /// Unlike :meth:`frame`, the pixels are not copied into a new array. The view supports the
+    /// buffer protocol, so ``np.asarray(view)`` returns an array with dimensions [h w 4] that
+    /// directly references the mapped frame memory. See :class:`.FrameView` for lifetime details.
+    /// The view covers the whole mapped frame, so ``client_region``, the crop of child windows and
+    /// flips are not applied.
+    ///
+    /// :returns: The view on the latest frame.
+    pub fn frame_view(&self) -> PyResult<FrameView> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        self.core.frame_tracker.frame_read(frame.id, &mapped_frame);
+        Ok(FrameView::new(mapped_frame))
+    }
+
+    /// frame_array_view() -> np.ndarray
+    /// Map the latest frame into memory and return an array that directly references it.
+    ///
+    /// This is a shortcut for ``np.asarray(capture.frame_view())``. The array keeps its
+    /// :class:`.FrameView` alive as ``base``, which keeps the frame mapped until the array is
+    /// garbage collected. No pixels are copied. Like the view, the array covers the whole frame
+    /// without ``client_region``, the crop of child windows and flips.
+    ///
+    /// .. warning::
+    ///    The array is read-only and does not change when new frames arrive. Holding on to arrays
+    ///    of old frames keeps their staging textures on the GPU alive, so copy the pixels if you
+    ///    need to keep them.
+    ///
+    /// :returns: The frame as a read-only 3D NumPy array with dimensions [h w 4].
+    pub fn frame_array_view(&self, py: Python) -> PyResult<PyObject> {
+        let frame_view = Py::new(py, self.frame_view()?)?;
+        let array = py.import("numpy")?.call_method1("asarray", (frame_view,))?;
+        Ok(array.into())
+    }
+
+    /// frame_async() -> asyncio.Future[np.ndarray]
+    /// Wait for the next frame without blocking the event loop.
+    ///
+    /// The returned future resolves with the first frame that arrives after the call. Waiting
+    /// happens in a background thread, so the asyncio event loop stays responsive. Cancelling the
+    /// future stops the background wait. If the capture is stopped before a new frame arrives, the
+    /// future raises a RuntimeError.
+    ///
+    /// .. note::
+    ///    This method has to be called from within a running asyncio event loop.
+    ///
+    /// :returns: A future that resolves to the frame as a 3D NumPy array with dimensions [h w 4].
+    pub fn frame_async(slf: PyRef<'_, Self>) -> PyResult<PyObject> {
+        let py = slf.py();
+        if !slf.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let event_loop: PyObject = py
+            .import("asyncio")?
+            .call_method0("get_running_loop")?
+            .into();
+        let future: PyObject = event_loop.call_method0(py, "create_future")?;
+        // Mark the wait as cancelled as soon as the future is done, e.g. because it got cancelled
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let on_done = {
+            let cancelled = cancelled.clone();
+            PyCFunction::new_closure(py, None, None, move |_, _| {
+                cancelled.store(true, Ordering::SeqCst);
+            })?
+        };
+        future.call_method1(py, "add_done_callback", (on_done,))?;
+
+        let last_id = slf.core.latest_frame().map(|frame| frame.id);
+        let frame_slot = slf.core.frame.clone();
+        let frame_arrived = slf.core.frame_arrived.clone();
+        let running = slf.core.running.clone();
+        let thread_exited = slf.core.thread_exited.clone();
+        let waiter_future = future.clone_ref(py);
+        // The frame is converted like the frames of `frame` once it arrives
+        let capture: Py<Capture> = slf.into();
+        thread::spawn(move || loop {
+            if cancelled.load(Ordering::SeqCst) {
+                return;
+            }
+            if !running.load(Ordering::SeqCst) || thread_exited.load(Ordering::SeqCst) {
+                let err = PyErr::from(CaptureError::StoppedBeforeFrame);
+                Python::with_gil(|py| resolve_future(py, &event_loop, waiter_future, Err(err)));
+                return;
+            }
+            // Release the signal lock before acquiring the GIL. Holding both at once would
+            // deadlock with threads that hold the GIL and wait for the lock
+            let next_frame = {
+                let new_frame = || frame_slot.load().filter(|frame| Some(frame.id) != last_id);
+                let mut frame_guard = frame_slot.lock();
+                if new_frame().is_none() {
+                    // Wake up regularly to check if the future has been cancelled
+                    frame_arrived.wait_for(&mut frame_guard, Duration::from_millis(10));
+                }
+                new_frame()
+            };
+            if let Some(frame) = next_frame {
+                Python::with_gil(|py| {
+                    let result = capture
+                        .try_borrow(py)
+                        .map_err(PyErr::from)
+                        .and_then(|capture| {
+                            capture.convert_frame(py, &frame, true, |_, mapped_frame, region| {
+                                let array = mapped_frame.region_to_pyarray(
+                                    py,
+                                    region,
+                                    capture.flip,
+                                    Layout::Hwc,
+                                )?;
+                                Ok(array.to_object(py))
+                            })
+                        });
+                    resolve_future(py, &event_loop, waiter_future, result);
+                });
+                return;
+            }
+        });
+        Ok(future)
+    }
+}
+
+// Convert a region of the client area of a window into a region of the frames of the window
+fn client_area_region(window: &Window, region: Region) -> Result<Region, CaptureError> {
+    let (x, y, width, height) = window
+        .client_rect()
+        .map_err(|_| CaptureError::InvalidCaptureTarget)?;
+    if region.width == 0
+        || region.height == 0
+        || region.x.saturating_add(region.width) > width
+        || region.y.saturating_add(region.height) > height
+    {
+        return Err(CaptureError::InvalidClientRegion(region));
+    }
+    Ok(Region {
+        x: x.max(0).unsigned_abs() + region.x,
+        y: y.max(0).unsigned_abs() + region.y,
+        ..region
+    })
+}
+
+// The part of the frames of a window that is shown on a monitor, or None if the window is not on
+// the monitor
+fn monitor_region(window: &Window, monitor: &Monitor) -> Option<Region> {
+    let frame_rect = window.root().frame_bounds().ok()?;
+    let monitor_rect = monitor.rect().ok()?;
+    let left = frame_rect.left.max(monitor_rect.left);
+    let top = frame_rect.top.max(monitor_rect.top);
+    let right = frame_rect.right.min(monitor_rect.right);
+    let bottom = frame_rect.bottom.min(monitor_rect.bottom);
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(Region {
+        x: (left - frame_rect.left).unsigned_abs(),
+        y: (top - frame_rect.top).unsigned_abs(),
+        width: (right - left).unsigned_abs(),
+        height: (bottom - top).unsigned_abs(),
+    })
+}
+
+/// CaptureConfig(*, capture_target: CaptureTarget | None = None, await_first_frame: bool = True, max_fps: int | None = None, restore_minimized: bool = False, min_feature_level: FeatureLevel = FeatureLevel.Level11_1, software_fallback: bool = False, color_format: ColorFormat = ColorFormat.Rgba8, buffer_count: int = 1, adapter_index: int | None = None, flip_vertical: bool = False, flip_horizontal: bool = False, scale: float | None = None, min_update_interval_ms: int | None = None, apartment_type: ApartmentType = ApartmentType.Multithreaded, client_region: tuple[int, int, int, int] | None = None, thread_priority: ThreadPriority = ThreadPriority.Normal, history_size: int = 0, fixed_interval_ms: int | None = None, sticky_window: bool = False, device_flags: int | None = None, queue_depth: int = 0, resize_policy: ResizePolicy = ResizePolicy.Recreate, unpremultiply_alpha: bool = False, max_size: tuple[int, int] | None = None, include_secondary_windows: bool = False, on_started: Callable[[], None] | None = None, on_target_closed: Callable[[], None] | None = None) -> CaptureConfig
+/// The options of :meth:`.Capture.start` bundled in one object.
+///
+/// Each attribute is the option of the same name of :meth:`.Capture.start`, which also documents
+/// their effects. Configurations can be created with keyword arguments, changed attribute by
+/// attribute or built with chained calls of :meth:`set`, and are passed to :meth:`.Capture.start`
+/// in place of the target:
+///
+/// ``capture.start(CaptureConfig(max_fps=30).set(color_format=ColorFormat.Rgba16Float))``
+///
+/// A configuration can start any number of captures. It holds no resources, so it can be kept
+/// and reused after the captures were stopped.
+#[pyclass(get_all, set_all)]
+#[derive(Clone)]
+pub struct CaptureConfig {
+    /// :``Monitor | Window | VirtualDesktop | CaptureItem | None``: The target to capture. If None,
+    /// the target passed to the constructor of the :class:`.Capture` is used.
+    pub capture_target: Option<CaptureTarget>,
+    /// :``bool``: Whether :meth:`.Capture.start` waits for the first frame.
+    pub await_first_frame: bool,
+    /// :``int | None``: The maximum number of frames per second that are stored.
+    pub max_fps: Option<u32>,
+    /// :``bool``: Whether a minimized window target is restored before the capture starts.
+    pub restore_minimized: bool,
+    /// :``FeatureLevel``: The lowest feature level the DirectX device has to support.
+    pub min_feature_level: FeatureLevel,
+    /// :``bool``: Whether the WARP software rasterizer is used if no GPU is available.
+    pub software_fallback: bool,
+    /// :``ColorFormat``: The pixel format of the captured frames.
+    pub color_format: ColorFormat,
+    /// :``int``: The number of frame buffers of the capture frame pool.
+    pub buffer_count: u32,
+    /// :``int | None``: The index of the graphics adapter that runs the capture.
+    pub adapter_index: Option<u32>,
+    /// :``bool``: Whether frames are returned bottom-up.
+    pub flip_vertical: bool,
+    /// :``bool``: Whether frames are mirrored from left to right.
+    pub flip_horizontal: bool,
+    /// :``float | None``: The scale that frames are downscaled to on the GPU.
+    pub scale: Option<f32>,
+    /// :``int | None``: The minimum time in milliseconds between two delivered frames.
+    pub min_update_interval_ms: Option<u64>,
+    /// :``ApartmentType``: The apartment in which the capture thread initializes the runtime.
+    pub apartment_type: ApartmentType,
+    /// :``tuple[int, int, int, int] | None``: The region (x, y, width, height) of the client area
+    /// of a window target that frames are cropped to.
+    pub client_region: Option<RegionTuple>,
+    /// :``ThreadPriority``: The priority of the capture thread.
+    pub thread_priority: ThreadPriority,
+    /// :``int``: The number of recent frames that are kept for :meth:`.Capture.recent_frames`.
+    pub history_size: usize,
+    /// :``int | None``: The interval in milliseconds in which exactly one frame is stored.
+    pub fixed_interval_ms: Option<u64>,
+    /// :``bool``: Whether the capture follows a replacement of a closed window target.
+    pub sticky_window: bool,
+    /// :``int | None``: The ``D3D11_CREATE_DEVICE_FLAG`` bit mask of the DirectX device.
+    pub device_flags: Option<u32>,
+    /// :``int``: The number of frames that are queued for :meth:`.Capture.recv`.
+    pub queue_depth: usize,
+    /// :``ResizePolicy``: The handling of window targets that are resized during the capture.
+    pub resize_policy: ResizePolicy,
+    /// :``bool``: Whether frames are converted to straight alpha.
+    pub unpremultiply_alpha: bool,
+    /// :``tuple[int, int] | None``: The (width, height) that frame buffers are allocated with at
+    /// least.
+    pub max_size: Option<(u32, u32)>,
+    /// :``bool``: Whether the secondary windows of a window target are captured.
+    pub include_secondary_windows: bool,
+    /// :``Callable[[], None] | None``: Called once the capture sessions have started.
+    pub on_started: Option<PyObject>,
+    /// :``Callable[[], None] | None``: Called from a background thread when the target closes.
+    pub on_target_closed: Option<PyObject>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            capture_target: None,
+            await_first_frame: true,
+            max_fps: None,
+            restore_minimized: false,
+            min_feature_level: FeatureLevel::default(),
+            software_fallback: false,
+            color_format: ColorFormat::default(),
+            buffer_count: 1,
+            adapter_index: None,
+            flip_vertical: false,
+            flip_horizontal: false,
+            scale: None,
+            min_update_interval_ms: None,
+            apartment_type: ApartmentType::default(),
+            client_region: None,
+            thread_priority: ThreadPriority::default(),
+            history_size: 0,
+            fixed_interval_ms: None,
+            sticky_window: false,
+            device_flags: None,
+            queue_depth: 0,
+            resize_policy: ResizePolicy::default(),
+            unpremultiply_alpha: false,
+            max_size: None,
+            include_secondary_windows: false,
+            on_started: None,
+            on_target_closed: None,
+        }
+    }
+}
+
+#[pymethods]
+impl CaptureConfig {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        capture_target=None,
+        await_first_frame=true,
+        max_fps=None,
+        restore_minimized=false,
+        min_feature_level=FeatureLevel::default(),
+        software_fallback=false,
+        color_format=ColorFormat::default(),
+        buffer_count=1,
+        adapter_index=None,
+        flip_vertical=false,
+        flip_horizontal=false,
+        scale=None,
+        min_update_interval_ms=None,
+        apartment_type=ApartmentType::default(),
+        client_region=None,
+        thread_priority=ThreadPriority::default(),
+        history_size=0,
+        fixed_interval_ms=None,
+        sticky_window=false,
+        device_flags=None,
+        queue_depth=0,
+        resize_policy=ResizePolicy::default(),
+        unpremultiply_alpha=false,
+        max_size=None,
+        include_secondary_windows=false,
+        on_started=None,
+        on_target_closed=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        capture_target: Option<CaptureTarget>,
+        await_first_frame: bool,
+        max_fps: Option<u32>,
+        restore_minimized: bool,
+        min_feature_level: FeatureLevel,
+        software_fallback: bool,
+        color_format: ColorFormat,
+        buffer_count: u32,
+        adapter_index: Option<u32>,
+        flip_vertical: bool,
+        flip_horizontal: bool,
+        scale: Option<f32>,
+        min_update_interval_ms: Option<u64>,
+        apartment_type: ApartmentType,
+        client_region: Option<RegionTuple>,
+        thread_priority: ThreadPriority,
+        history_size: usize,
+        fixed_interval_ms: Option<u64>,
+        sticky_window: bool,
+        device_flags: Option<u32>,
+        queue_depth: usize,
+        resize_policy: ResizePolicy,
+        unpremultiply_alpha: bool,
+        max_size: Option<(u32, u32)>,
+        include_secondary_windows: bool,
+        on_started: Option<PyObject>,
+        on_target_closed: Option<PyObject>,
+    ) -> Self {
+        Self {
+            capture_target,
+            await_first_frame,
+            max_fps,
+            restore_minimized,
+            min_feature_level,
+            software_fallback,
+            color_format,
+            buffer_count,
+            adapter_index,
+            flip_vertical,
+            flip_horizontal,
+            scale,
+            min_update_interval_ms,
+            apartment_type,
+            client_region,
+            thread_priority,
+            history_size,
+            fixed_interval_ms,
+            sticky_window,
+            device_flags,
+            queue_depth,
+            resize_policy,
+            unpremultiply_alpha,
+            max_size,
+            include_secondary_windows,
+            on_started,
+            on_target_closed,
+        }
+    }
+
+    /// set(**options) -> CaptureConfig
+    /// Set options by name and return the configuration, so calls can be chained.
+    ///
+    /// Args:
+    ///     options: The new values of the options, e.g. ``max_fps=30``.
+    ///
+    /// :returns: This configuration.
+    #[pyo3(signature = (**options))]
+    pub fn set<'py>(
+        slf: &'py PyCell<Self>,
+        options: Option<&PyDict>,
+    ) -> PyResult<&'py PyCell<Self>> {
+        for (name, value) in options.into_iter().flatten() {
+            let name: &str = name.extract()?;
+            // Attributes of the Python object, e.g. methods, are not options
+            if name.starts_with('_') || name == "set" || !slf.hasattr(name)? {
+                return Err(PyTypeError::new_err(format!(
+                    "unknown capture option '{name}'"
+                )));
+            }
+            slf.setattr(name, value)?;
+        }
+        Ok(slf)
+    }
+}
+
+// First argument of `Capture.start`, which is either the target or a configuration of the capture
+#[derive(FromPyObject)]
+pub enum StartTarget {
+    Target(CaptureTarget),
+    Config(CaptureConfig),
+}
+
+/// A frame and its metadata, returned by :meth:`.Capture.read`.
+///
+/// FrameData behaves like a named tuple, so it can be unpacked with
+/// ``array, timestamp, frame_id, width, height = capture.read()``.
+#[pyclass(get_all)]
+pub struct FrameData {
+    /// :``np.ndarray``: The frame as a 3D NumPy array with dimensions [h w 4].
+    pub array: PyObject,
+    /// :``float``: The system relative time in seconds at which the frame was rendered.
+    pub timestamp: f64,
+    /// :``int``: The ID of the frame. IDs increase by one for each stored frame of a capture.
+    pub frame_id: u64,
+    /// :``int``: The width of the frame.
+    pub width: u32,
+    /// :``int``: The height of the frame.
+    pub height: u32,
+    /// :``int``: The width of the captured content. Not part of the tuple.
+    pub content_width: u32,
+    /// :``int``: The height of the captured content. Not part of the tuple.
+    pub content_height: u32,
+    /// :``tuple[int, int, int, int] | None``: The region (x, y, width, height) of the frame that
+    /// the array was cropped to with the ``auto_crop`` option of :meth:`.Capture.read`, or None if
+    /// the frame was not cropped. The offsets are the sizes of the left and top border inside the
+    /// client region or child window that frames are cropped to. Not part of the tuple.
+    pub crop: Option<RegionTuple>,
+    /// :``int | None``: The number of frames the compositor skipped since the previous stored
+    /// frame, or None if the refresh rate of the target is unknown or the frame was repeated by a
+    /// ``fixed_interval_ms`` capture. Unlike the dropped frames of :meth:`.Capture.stats`, this
+    /// counts skips at the source, e.g. stutter of the compositor. The count is estimated from the
+    /// gaps between frame times, and the compositor only delivers frames when the target changes,
+    /// so idle periods of a static target are counted as well. Not part of the tuple.
+    pub missed_since_last: Option<u64>,
+}
+
+#[pymethods]
+impl FrameData {
+    fn __len__(&self) -> usize {
+        5
+    }
+
+    fn __getitem__(&self, py: Python, index: isize) -> PyResult<PyObject> {
+        // Negative indices count from the end like for tuples
+        let index = if index < 0 { index + 5 } else { index };
+        match index {
+            0 => Ok(self.array.clone_ref(py)),
+            1 => Ok(self.timestamp.into_py(py)),
+            2 => Ok(self.frame_id.into_py(py)),
+            3 => Ok(self.width.into_py(py)),
+            4 => Ok(self.height.into_py(py)),
+            _ => Err(PyIndexError::new_err("FrameData index out of range")),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FrameData(timestamp={}, frame_id={}, width={}, height={})",
+            self.timestamp, self.frame_id, self.width, self.height
+        )
+    }
+}
+
+impl Capture {
+    /// Start a capture with the options of `config`.
+    ///
+    /// `start` merges its keyword arguments into a configuration and delegates to this method, so
+    /// Rust callers only set the options they need instead of passing every argument of the
+    /// Python signature.
+    pub fn start_with(&mut self, config: CaptureConfig) -> PyResult<()> {
+        let callbacks = [
+            ("on_started", &config.on_started),
+            ("on_target_closed", &config.on_target_closed),
+        ];
+        for (name, callback) in callbacks {
+            let Some(callback) = callback else {
+                continue;
+            };
+            if !Python::with_gil(|py| callback.as_ref(py).is_callable()) {
+                return Err(PyTypeError::new_err(format!("{name} must be callable")));
+            }
+        }
+        let capture_target = config
+            .capture_target
+            .or_else(|| self.capture_target.clone())
+            .ok_or(CaptureError::NoCaptureTarget)?;
+        let client_region = config.client_region.map(|(x, y, width, height)| Region {
+            x,
+            y,
+            width,
+            height,
+        });
+        if let Some(region) = client_region {
+            let CaptureTarget::Window(window) = &capture_target else {
+                return Err(CaptureError::ClientRegionWithoutWindow.into());
+            };
+            client_area_region(window, region)?;
+        }
+        if config.include_secondary_windows && !session_property_present("IncludeSecondaryWindows")
+        {
+            return Err(CaptureError::SecondaryWindowsUnsupported.into());
+        }
+        let options = CaptureOptions {
+            max_fps: config.max_fps,
+            restore_minimized: config.restore_minimized,
+            min_feature_level: config.min_feature_level,
+            software_fallback: config.software_fallback,
+            color_format: config.color_format,
+            buffer_count: config.buffer_count,
+            adapter_index: config.adapter_index,
+            scale: config.scale,
+            min_update_interval: config.min_update_interval_ms.map(Duration::from_millis),
+            apartment_type: config.apartment_type,
+            thread_priority: config.thread_priority,
+            history_size: config.history_size,
+            fixed_interval: config.fixed_interval_ms.map(Duration::from_millis),
+            sticky_window: config.sticky_window,
+            device_flags: config
+                .device_flags
+                .map_or(DEFAULT_DEVICE_FLAGS, D3D11_CREATE_DEVICE_FLAG),
+            queue_depth: config.queue_depth,
+            resize_policy: config.resize_policy,
+            unpremultiply_alpha: config.unpremultiply_alpha,
+            max_size: config.max_size,
+            include_secondary_windows: config.include_secondary_windows,
+        };
+        self.core.start(capture_target, &options)?;
+        // The callback of a previous capture must not fire for the new one. A start that fails
+        // leaves the running capture and its callback untouched
+        if let Some(cancelled) = self.closed_callback_cancelled.take() {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+        self.flip = Flip {
+            vertical: config.flip_vertical,
+            horizontal: config.flip_horizontal,
+        };
+        self.client_region = client_region;
+        *self.previous_sample.get_mut() = None;
+        if let Some(on_started) = config.on_started {
+            // A failed callback must not leave a capture running that the caller does not expect
+            if let Err(error) = Python::with_gil(|py| on_started.call0(py)) {
+                self.core.stop();
+                return Err(error);
+            }
+        }
+
+        // Wait for the first frame to be ready if await_first_frame is set. A capture that ends
+        // without any frame would only fail on the first read otherwise
+        if config.await_first_frame && !self.core.await_first_frame() {
+            self.core.stop();
+            return Err(CaptureError::NoFirstFrame.into());
+        }
+        if let Some(on_target_closed) = config.on_target_closed {
+            self.watch_target_closed(on_target_closed);
+        }
+        Ok(())
+    }
+
+    /// Stop the capture thread, wait for it to join and invalidate the last frame.
+    pub fn stop(&mut self) {
+        let _ = self.try_stop();
+    }
+
+    /// Stop the capture and return the error the capture thread exited with.
+    pub fn try_stop(&mut self) -> Result<(), CaptureError> {
+        if let Some(cancelled) = self.closed_callback_cancelled.take() {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+        *self.shared_texture.get_mut() = None;
+        self.core.try_stop()
+    }
+
+    // Call `callback` from a background thread once the target of the running capture is closed.
+    // The capture thread must not wait for the GIL, since `stop` joins it while holding the GIL
+    fn watch_target_closed(&mut self, callback: PyObject) {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.closed_callback_cancelled = Some(cancelled.clone());
+        let watch = self.core.watch();
+        thread::spawn(move || loop {
+            let closed = watch.wait_for_close(Instant::now() + CLOSE_POLL_INTERVAL);
+            if cancelled.load(Ordering::SeqCst) {
+                return;
+            }
+            if closed {
+                Python::with_gil(|py| {
+                    if let Err(error) = callback.call0(py) {
+                        error.print(py);
+                    }
+                });
+                return;
+            }
+            // Stopped captures and threads that exited with an error never close their target
+            if !watch.is_receiving() {
+                return;
+            }
+        });
+    }
+
+    // Convert a frame into an array, cropped and flipped like the arrays of `py_frame`
+    fn frame_to_pyarray(&self, py: Python, frame: &Frame) -> PyResult<PyObject> {
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        let img_array = match self.client_crop(frame)? {
+            Some(region) => mapped_frame.region_to_pyarray(py, region, self.flip, Layout::Hwc)?,
+            None => mapped_frame.to_pyarray(py, self.flip)?,
+        };
+        Ok(img_array.into())
+    }
+
+    // The region of the frame inside the client region, or None if frames are not cropped
+    fn client_crop(&self, frame: &Frame) -> Result<Option<Region>, CaptureError> {
+        let Some(CaptureTarget::Window(window)) = self.core.target() else {
+            return Ok(None);
+        };
+        let region = match self.client_region {
+            Some(region) => region,
+            // Child windows are cropped from the frames of their top-level window
+            None if window.is_child() => {
+                let (_, _, width, height) = window
+                    .client_rect()
+                    .map_err(|_| CaptureError::InvalidCaptureTarget)?;
+                Region {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                }
+            }
+            None => return Ok(None),
+        };
+        // The client area is looked up for every frame so that the crop follows it
+        let region = frame.output_region(client_area_region(&window, region)?);
+        let (width, height) = frame.output_size();
+        if region.x + region.width > width || region.y + region.height > height {
+            return Err(CaptureError::InvalidClientRegion(region));
+        }
+        Ok(Some(region))
+    }
+
+    // Convert the latest frame like `frame` and return it with its timestamp. Frames read by
+    // handles are not marked as read so that the capture still reports them as new
+    fn read_frame(
+        &self,
+        py: Python,
+        layout: Option<&str>,
+        mark_read: bool,
+    ) -> PyResult<(PyObject, i64)> {
+        let layout: Layout = layout.map(str::parse).transpose()?.unwrap_or_default();
+        self.read_latest(py, mark_read, |frame, mapped_frame, region| {
+            let img_array = mapped_frame.region_to_pyarray(py, region, self.flip, layout)?;
+            Ok((img_array.into(), frame.time))
+        })
+    }
+
+    // Convert the latest frame with `convert`, see `convert_frame`
+    fn read_latest<T>(
+        &self,
+        py: Python,
+        mark_read: bool,
+        convert: impl FnOnce(&Frame, &MappedFrame, Region) -> PyResult<T>,
+    ) -> PyResult<T> {
+        if !self.core.is_active() {
+            return Err(CaptureError::NotRunning.into());
+        }
+        let frame = self
+            .core
+            .latest_frame()
+            .ok_or(CaptureError::NoFrameAvailable)?;
+        self.convert_frame(py, &frame, mark_read, convert)
+    }
+
+    // Convert a frame with `convert`, which receives the mapped frame and its region inside the
+    // client crop. Marks the frame as read if `mark_read` is set and warns if it is stale
+    fn convert_frame<T>(
+        &self,
+        py: Python,
+        frame: &Frame,
+        mark_read: bool,
+        convert: impl FnOnce(&Frame, &MappedFrame, Region) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let mapped_frame = frame.materialize().map_err(CaptureError::from)?;
+        let region = self.client_crop(frame)?.unwrap_or(Region {
+            x: 0,
+            y: 0,
+            width: mapped_frame.width,
+            height: mapped_frame.height,
+        });
+        let converted = convert(frame, &mapped_frame, region)?;
+        if mark_read {
+            self.core.frame_tracker.frame_read(frame.id, &mapped_frame);
+        }
+        self.warn_if_stale(py, frame.time)?;
+        Ok(converted)
+    }
+
+    // Emit a RuntimeWarning if a frame rendered at `frame_time` exceeds the stale frame threshold
+    fn warn_if_stale(&self, py: Python, frame_time: i64) -> PyResult<()> {
+        let Some(threshold_ms) = self.stale_frame_threshold_ms else {
+            return Ok(());
+        };
+        let now = system_relative_time().map_err(CaptureError::from)?;
+        // Times are in 100ns units
+        let age_ms = (now - frame_time) as f64 / 10_000.0;
+        if age_ms > threshold_ms {
+            let message = format!(
+                "The frame is {age_ms:.0} ms old, which exceeds the stale frame threshold of \
+                 {threshold_ms} ms. The capture target may have stopped updating."
+            );
+            PyErr::warn(py, py.get_type::<PyRuntimeWarning>(), &message, 1)?;
+        }
+        Ok(())
+    }
+}
+
+// Run a stop method on a capture that other threads may be waiting on. Waiting threads hold a
+// shared borrow of the capture until they return, so they are woken up first and the capture is
+// stopped as soon as the borrows are released
+fn stop_waited<R>(cell: &PyCell<Capture>, stop: impl FnOnce(&mut Capture) -> R) -> PyResult<R> {
+    cell.try_borrow()?.core.interrupt_waits();
+    loop {
+        if let Ok(mut capture) = cell.try_borrow_mut() {
+            return Ok(stop(&mut capture));
+        }
+        // Mutable borrows by other threads are not released by the interrupt
+        drop(cell.try_borrow()?);
+        cell.py()
+            .allow_threads(|| thread::sleep(Duration::from_millis(1)));
+    }
+}
+
+/// Iterator over the new frames of a :class:`.Capture`, created by :meth:`.Capture.frames`.
+#[pyclass]
+pub struct FrameIterator {
+    capture: Py<Capture>,
+    timeout_ms: Option<u64>,
+}
+
+#[pymethods]
+impl FrameIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let capture = self.capture.borrow(py);
+        if !capture.active() {
+            return Ok(None);
+        }
+        capture.wait_for_frame(py, self.timeout_ms)
+    }
+}
+
+/// Read-only handle to a :class:`.Capture`, created by :meth:`.Capture.share`.
+///
+/// The handle reads the frames of the capture it was created from. It cannot start or stop the
+/// capture, but it keeps the capture alive while the handle exists.
+#[pyclass]
+pub struct CaptureHandle {
+    capture: Py<Capture>,
+    watch: FrameWatch,
+    // Timestamp of the last frame returned by this handle
+    last_frame_time: Mutex<Option<i64>>,
+}
+
+#[pymethods]
+impl CaptureHandle {
+    /// :``Capture``: The capture this handle reads from.
+    #[getter]
+    pub fn capture(&self, py: Python) -> Py<Capture> {
+        self.capture.clone_ref(py)
+    }
+
+    /// :``bool``: True if the capture is running and its target has not been closed.
+    #[getter]
+    pub fn active(&self) -> bool {
+        self.watch.is_receiving()
+    }
+
+    /// frame(layout: str | None = None) -> np.ndarray
+    /// Convert the latest frame of the capture to an array and return it.
+    ///
+    /// Args:
+    ///     layout: The axis order of the array, see :meth:`.Capture.frame`. Defaults to ``"hwc"``.
+    ///
+    /// :returns: The frame as a 3D NumPy array with the dimensions of ``layout``.
+    #[pyo3(signature = (layout=None))]
+    pub fn frame(&self, py: Python, layout: Option<&str>) -> PyResult<PyObject> {
+        let (img_array, frame_time) = self.capture_ref(py)?.read_frame(py, layout, false)?;
+        *self.last_frame_time.lock() = Some(frame_time);
+        Ok(img_array)
+    }
+
+    /// wait_for_frame(timeout_ms: int | None = None) -> np.ndarray | None
+    /// Wait until a frame arrives that this handle has not returned yet and return it.
+    ///
+    /// Args:
+    ///     timeout_ms: The maximum time to wait in milliseconds. Waits indefinitely if None.
+    ///
+    /// :returns: The frame as a 3D NumPy array with dimensions [h w 4], or None if no new frame
+    ///     arrived within the timeout, the capture was stopped or failed or the target was closed.
+    pub fn wait_for_frame(
+        &self,
+        py: Python,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<Option<PyObject>> {
+        let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+        let seen = *self.last_frame_time.lock();
+        // Wait without borrowing the capture so that it can be stopped in the meantime
+        if !py.allow_threads(|| self.watch.wait_for_new(seen, deadline)) {
+            return Ok(None);
+        }
+        self.frame(py, None).map(Some)
+    }
+}
+
+impl CaptureHandle {
+    fn capture_ref<'py>(&'py self, py: Python<'py>) -> PyResult<PyRef<'py, Capture>> {
+        // The capture is mutably borrowed while it starts or stops
+        self.capture
+            .try_borrow(py)
+            .map_err(|_| CaptureError::NotRunning.into())
+    }
+}
+
+/// capture_size(capture_target: CaptureTarget) -> tuple[int, int]
+///
+/// Get the pixel size of the frames of a capture target without starting a capture.
+///
+/// Args:
+///     capture_target: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop`.
+///
+/// Returns:
+///     The frame size as (width, height).
+#[pyfunction]
+pub fn capture_size(capture_target: CaptureTarget) -> Result<(u32, u32), CaptureError> {
+    capture_target
+        .size()
+        .map_err(|_| CaptureError::InvalidCaptureTarget)
+}
+
+/// supported_formats(capture_target: CaptureTarget, adapter_index: int | None = None) -> list[ColorFormat]
+///
+/// Get the color formats that a capture of the target can use, without starting a capture.
+///
+/// Creates the device that :meth:`Capture.start` would create with the same ``adapter_index`` and
+/// queries it with ``CheckFormatSupport``. Use the result to choose the ``color_format`` before
+/// starting the capture instead of handling the error of an unsupported format.
+///
+/// Args:
+///     capture_target: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop`.
+///     adapter_index: The index of the graphics adapter. If None, the default adapter is used.
+///
+/// Returns:
+///     The supported color formats in the order of :class:`.ColorFormat`.
+#[pyfunction]
+#[pyo3(signature = (capture_target, adapter_index=None))]
+pub fn supported_formats(
+    capture_target: CaptureTarget,
+    adapter_index: Option<u32>,
+) -> Result<Vec<ColorFormat>, CaptureError> {
+    // Fail for closed windows and disconnected monitors like `Capture.start` would
+    capture_target
+        .size()
+        .map_err(|_| CaptureError::InvalidCaptureTarget)?;
+    let (device, _) = create_d3d_device(
+        FeatureLevel::default(),
+        false,
+        adapter_index,
+        DEFAULT_DEVICE_FLAGS,
+    )?;
+    Ok(supported_color_formats(&device))
+}
+
+/// pick_capture_target(owner: Window | None = None) -> CaptureItem | None
+///
+/// Show the system picker to let the user choose a window or monitor to capture.
+///
+/// The call blocks until the user confirms or cancels the picker. This is the standard way for
+/// screen sharing applications to select a target without enumerating windows and monitors.
+///
+/// Args:
+///     owner: The window that owns the picker dialog. If None, the foreground window is used.
+///
+/// Returns:
+///     The selected :class:`.CaptureItem`, or None if the user canceled the picker.
+#[pyfunction]
+#[pyo3(signature = (owner=None))]
+pub fn pick_capture_target(
+    py: Python,
+    owner: Option<Window>,
+) -> Result<Option<CaptureItem>, CaptureError> {
+    let owner = owner.map_or_else(
+        || unsafe { GetForegroundWindow() },
+        |window| window.as_handle(),
+    );
+    let picker = GraphicsCapturePicker::new()?;
+    // Desktop applications have to tie the picker to a window because they have no CoreWindow
+    unsafe { picker.cast::<IInitializeWithWindow>()?.Initialize(owner)? };
+    let operation = picker.PickSingleItemAsync()?;
+    match py.allow_threads(|| operation.get()) {
+        Ok(item) => Ok(Some(CaptureItem::new(item))),
+        // Canceling the picker completes the operation with a null item
+        Err(error) if error.code().is_ok() => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// is_supported() -> bool
+///
+/// Check if the system supports graphics capture.
+///
+/// Graphics capture requires Windows 10, version 1803 or later. Use this function to check for
+/// support before creating a :class:`.Capture`.
+///
+/// Returns:
+///     True if graphics capture is supported, False otherwise.
+#[pyfunction]
+pub fn is_supported() -> bool {
+    capture_core::is_supported()
+}
+
+/// diagnostics() -> Diagnostics
+///
+/// Collect information about the capture support of the system.
+///
+/// Attach the result to bug reports if captures fail without an apparent reason. Creating the
+/// device to query the feature level and adapter takes a few milliseconds.
+///
+/// Returns:
+///     The :class:`.Diagnostics` of the system.
+#[pyfunction]
+pub fn diagnostics() -> Diagnostics {
+    // Accept any device so that the achieved feature level is reported
+    let device = create_d3d_device(FeatureLevel::Level9_1, true, None, DEFAULT_DEVICE_FLAGS)
+        .ok()
+        .map(|(device, _)| device);
+    Diagnostics {
+        graphics_capture_supported: capture_core::is_supported(),
+        feature_level: device
+            .as_ref()
+            .and_then(|device| FeatureLevel::from_d3d(unsafe { device.GetFeatureLevel() })),
+        adapter_name: device.and_then(|device| adapter_name(&device).ok()),
+        os_build: os_version().ok().map(|version| version.dwBuildNumber),
+        cursor_toggle_supported: supports_cursor_toggle(),
+        border_toggle_supported: supports_border_toggle(),
+    }
+}
+
+/// windows_build() -> WindowsBuild
+///
+/// Query the version of Windows and the capture features it provides.
+///
+/// Newer capture features are only available on recent builds, e.g. hiding the cursor since
+/// Windows 10, version 2004 (build 19041), hiding the border since build 20348, including
+/// secondary windows since Windows 11 (build 22000) and the minimum update interval since
+/// Windows 11, version 24H2 (build 26100). Apps can use the result to show
+/// which options are available instead of failing when the capture starts.
+///
+/// Returns:
+///     The :class:`.WindowsBuild` of the system.
+#[pyfunction]
+pub fn windows_build() -> Result<WindowsBuild, CaptureError> {
+    let version = os_version()?;
+    Ok(WindowsBuild {
+        major: version.dwMajorVersion,
+        minor: version.dwMinorVersion,
+        build: version.dwBuildNumber,
+        graphics_capture_supported: capture_core::is_supported(),
+        cursor_toggle_supported: supports_cursor_toggle(),
+        border_toggle_supported: supports_border_toggle(),
+        min_update_interval_supported: session_property_present("MinUpdateInterval"),
+        secondary_windows_supported: session_property_present("IncludeSecondaryWindows"),
+    })
+}
+
+/// supports_cursor_toggle() -> bool
+///
+/// Check if the system can exclude the cursor from captures.
+///
+/// Hiding the cursor requires Windows 10, version 2004 or later. Apps can use this function to
+/// disable options that would not work instead of failing when the capture starts.
+///
+/// Returns:
+///     True if the cursor capture can be toggled, False otherwise.
+#[pyfunction]
+pub fn supports_cursor_toggle() -> bool {
+    session_property_present("IsCursorCaptureEnabled")
+}
+
+/// supports_border_toggle() -> bool
+///
+/// Check if the system can hide the yellow border around captured targets.
+///
+/// Hiding the border requires Windows 11 or Windows Server 2022 (build 20348) or later. Apps can
+/// use this function to disable options that would not work instead of failing when the capture
+/// starts.
+///
+/// Returns:
+///     True if the capture border can be toggled, False otherwise.
+#[pyfunction]
+pub fn supports_border_toggle() -> bool {
+    session_property_present("IsBorderRequired")
+}
+
+// Check if the capture session of this Windows build has the property `name`. Older builds lack
+// the newer session properties, and setting them fails at runtime
+fn session_property_present(name: &str) -> bool {
+    ApiInformation::IsPropertyPresent(
+        &HSTRING::from("Windows.Graphics.Capture.GraphicsCaptureSession"),
+        &HSTRING::from(name),
+    )
+    .unwrap_or(false)
+}
+
+// The version of Windows. Unlike `GetVersionEx`, `RtlGetVersion` reports the real version even if
+// the application manifest does not declare support for it
+fn os_version() -> windows::core::Result<OSVERSIONINFOW> {
+    let mut version = OSVERSIONINFOW {
+        dwOSVersionInfoSize: mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+    unsafe { RtlGetVersion(&mut version).ok()? };
+    Ok(version)
+}
+
+/// grab(capture_target: CaptureTarget, timeout_ms: int | None = None) -> np.ndarray
+///
+/// Capture a single frame of a capture target.
+///
+/// Starts a capture, waits for the first frame, converts it to an array and stops the capture
+/// again. Use a :class:`.Capture` instead if you need more than one frame.
+///
+/// Args:
+///     capture_target: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop`.
+///     timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely if
+///         None.
+///
+/// Returns:
+///     The frame as a 3D NumPy array with dimensions [h w 4].
+#[pyfunction]
+pub fn grab(
+    py: Python,
+    capture_target: CaptureTarget,
+    timeout_ms: Option<u64>,
+) -> PyResult<PyObject> {
+    let mut capture = Capture::new(Some(capture_target));
+    capture.start_with(CaptureConfig {
+        await_first_frame: false,
+        ..CaptureConfig::default()
+    })?;
+    let frame = capture.wait_for_frame(py, timeout_ms);
+    // Stop the capture before handling the result so the thread is cleaned up on errors as well
+    capture.stop();
+    frame?.ok_or_else(|| CaptureError::FrameTimeout.into())
+}
+
+/// grab_monitors(timeout_ms: int | None = None) -> list[tuple[Monitor, np.ndarray]]
+///
+/// Capture a single frame of each connected monitor.
+///
+/// Starts one capture per monitor, each on its own thread, waits for the first frames, converts
+/// them to arrays and stops the captures again. The monitors are captured in parallel, so the call
+/// takes about as long as the slowest monitor instead of the sum of all monitors.
+///
+/// Args:
+///     timeout_ms: The maximum time to wait for the frames in milliseconds. Waits indefinitely if
+///         None.
+///
+/// Returns:
+///     A list of the monitors and their frames as 3D NumPy arrays with dimensions [h w 4], in the
+///     order of :func:`enumerate_monitors`.
+///
+/// Raises:
+///     RuntimeError: If the capture of any monitor fails. The error lists the failures of all
+///         monitors.
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=None))]
+pub fn grab_monitors(py: Python, timeout_ms: Option<u64>) -> PyResult<Vec<(Monitor, PyObject)>> {
+    let monitors = enumerate_monitors()?;
+    let deadline = timeout_ms.map(|ms| Instant::now() + Duration::from_millis(ms));
+    let results = py.allow_threads(|| {
+        thread::scope(|scope| {
+            let threads: Vec<_> = monitors
+                .iter()
+                .map(|&monitor| scope.spawn(move || grab_frame(monitor, deadline)))
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| {
+                    thread
+                        .join()
+                        .unwrap_or(Err(CaptureError::CaptureThreadError))
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    let mut frames = Vec::with_capacity(monitors.len());
+    let mut errors = Vec::new();
+    for (monitor, result) in monitors.into_iter().zip(results) {
+        match result {
+            Ok(mapped_frame) => {
+                let array = mapped_frame.to_pyarray(py, Flip::default())?;
+                frames.push((monitor, array.into()));
+            }
+            Err(error) => {
+                let name = monitor.device_name().unwrap_or_default();
+                errors.push(format!("{name}: {error}"));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(CaptureError::GroupError(errors.join("; ")).into());
+    }
+    Ok(frames)
+}
+
+// Capture the first frame of a monitor and copy it to the CPU without the GIL
+fn grab_frame(monitor: Monitor, deadline: Option<Instant>) -> Result<MappedFrame, CaptureError> {
+    let mut core = CaptureCore::new();
+    core.start(CaptureTarget::Monitor(monitor), &CaptureOptions::default())?;
+    let frame = core
+        .watch()
+        .wait_for_new(None, deadline)
+        .then(|| core.latest_frame())
+        .flatten();
+    let mapped_frame = frame.map(|frame| frame.materialize()).transpose();
+    // Captures that end before the first frame report the error of their thread
+    let stopped = core.try_stop();
+    match mapped_frame? {
+        Some(mapped_frame) => Ok(mapped_frame),
+        None => {
+            stopped?;
+            Err(CaptureError::FrameTimeout)
+        }
+    }
+}
+
+/// screenshot_foreground(timeout_ms: int | None = None) -> np.ndarray
+///
+/// Capture a single frame of the foreground window.
+///
+/// Resolves the window with :func:`foreground_window` and captures it with :func:`grab`, so the
+/// capture thread is stopped again whether or not a frame arrives.
+///
+/// Args:
+///     timeout_ms: The maximum time to wait for the frame in milliseconds. Waits indefinitely if
+///         None.
+///
+/// Returns:
+///     The frame as a 3D NumPy array with dimensions [h w 4].
+///
+/// Raises:
+///     WindowNotFoundError: No window is in the foreground.
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=None))]
+pub fn screenshot_foreground(py: Python, timeout_ms: Option<u64>) -> PyResult<PyObject> {
+    let window = foreground_window()?;
+    grab(py, CaptureTarget::Window(window), timeout_ms)
+}
+
+/// capture_when_available(title_or_class: str, timeout_ms: int) -> Capture
+///
+/// Wait for a window to appear and start capturing it.
+///
+/// Useful for capturing an application that was just launched. The window is matched by its
+/// title first and by its class name if no window has that title. The capture is started with the
+/// default options of :meth:`.Capture.start`. Use :class:`.Window` with a timeout and start the
+/// capture yourself if you need other options.
+///
+/// Args:
+///     title_or_class: The title or the class name of the window.
+///     timeout_ms: The maximum time to wait for the window in milliseconds.
+///
+/// Returns:
+///     The running :class:`.Capture` of the window.
+///
+/// Raises:
+///     WindowNotFoundError: No matching window appeared before the timeout.
+#[pyfunction]
+pub fn capture_when_available(
+    py: Python,
+    title_or_class: &str,
+    timeout_ms: u64,
+) -> PyResult<Capture> {
+    let window = Window::wait_for(py, title_or_class, true, timeout_ms)?;
+    let mut capture = Capture::new(Some(CaptureTarget::Window(window)));
+    capture.start_with(CaptureConfig::default())?;
+    Ok(capture)
 }
@@ -0,0 +1,1804 @@
+// This code has been adapted from https://github.com/NiiightmareXD/windows-capture
+
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::num::TryFromIntError;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::{iter, mem, ptr};
+
+use pyo3::prelude::*;
+
+use windows::core::{IInspectable, IInspectable_Vtbl, IUnknown, Interface, GUID, HRESULT};
+use windows::Foundation::AsyncActionCompletedHandler;
+use windows::Foundation::Collections::IVectorView;
+use windows::Foundation::{TimeSpan, TypedEventHandler};
+use windows::Graphics::Capture::{
+    Direct3D11CaptureFrame, Direct3D11CaptureFramePool, GraphicsCaptureItem, GraphicsCaptureSession,
+};
+use windows::Graphics::{RectInt32, SizeInt32};
+use windows::Win32::Foundation::{E_NOINTERFACE, HWND, LPARAM, RPC_E_CHANGED_MODE, WPARAM};
+use windows::Win32::Graphics::Direct3D11::{
+    ID3D11Device, ID3D11Texture2D, D3D11_CREATE_DEVICE_FLAG, D3D11_TEXTURE2D_DESC,
+};
+use windows::Win32::System::Threading::{GetCurrentThread, GetCurrentThreadId, SetThreadPriority};
+use windows::Win32::System::WinRT::Direct3D11::IDirect3DDxgiInterfaceAccess;
+use windows::Win32::System::WinRT::{
+    CreateDispatcherQueueController, DispatcherQueueOptions, RoInitialize, RoUninitialize,
+    DQTAT_COM_NONE, DQTYPE_THREAD_CURRENT,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DispatchMessageW, GetMessageW, KillTimer, PeekMessageW, PostQuitMessage, PostThreadMessageW,
+    SetTimer, TranslateMessage, MSG, PM_NOREMOVE, WM_APP, WM_QUIT, WM_TIMER,
+};
+use windows_result::Error as WindowsError;
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::capture_utils::{
+    system_relative_time, ApartmentType, CaptureStats, CaptureTarget, CaptureTargetError,
+    ColorFormat, FrameSlot, FrameTracker, ResizePolicy, ThreadPriority,
+};
+use crate::direct_x::{
+    check_color_format, create_d3d_device, create_direct3d_device, find_adapter, DirectXError,
+    FeatureLevel, SendDirectX, DEFAULT_DEVICE_FLAGS,
+};
+use crate::errors;
+use crate::frame::{downscale_level, missed_frames, Frame, FrameError, FrameTile, Region};
+use crate::pipe::{PipeError, PipeWriter};
+use crate::recorder::{Recorder, RecorderError};
+use crate::window::{enumerate_top_level_windows, Window};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CaptureError {
+    #[error("Capture thread is not running.")]
+    NotRunning,
+    #[error("Capture was stopped before a new frame.")]
+    StoppedBeforeFrame,
+    #[error("No frame available yet.")]
+    NoFrameAvailable,
+    #[error("No frame arrived before the timeout.")]
+    FrameTimeout,
+    #[error("The capture stopped before the first frame arrived.")]
+    NoFirstFrame,
+    #[error("Windows error during Capture.")]
+    WindowsError(#[from] WindowsError),
+    #[error("DirectX error during Capture: {0}")]
+    DirectXError(#[from] DirectXError),
+    #[error("Frame could not be materialized.")]
+    FrameConversionError(#[from] FrameError),
+    #[error("Capture thread exited unexpectedly with an error.")]
+    CaptureThreadError,
+    #[error("Failed to spawn the capture thread: {0}")]
+    ThreadSpawnError(#[from] std::io::Error),
+    #[error("Invalid capture target.")]
+    InvalidCaptureTarget,
+    #[error("Failed to create the capture item: {0}")]
+    CaptureItemError(#[from] CaptureTargetError),
+    #[error("No capture target was given to either the constructor or start.")]
+    NoCaptureTarget,
+    #[error("Capture is already running. Stop it before starting a new one.")]
+    AlreadyStarted,
+    #[error("Graphics capture is not supported on this system.")]
+    Unsupported,
+    #[error("Window is minimized. The window must be visible to be captured.")]
+    WindowMinimized,
+    #[error("Capture failed for some targets of the group: {0}")]
+    GroupError(String),
+    #[error("Scale {0} is not in the range (0, 1].")]
+    InvalidScale(f32),
+    #[error("Client regions can only be captured from window targets.")]
+    ClientRegionWithoutWindow,
+    #[error("Client region {0:?} is empty or exceeds the client area of the window.")]
+    InvalidClientRegion(Region),
+    #[error("Double buffers need a nonzero shape of (height, width, 4), got {0:?}.")]
+    InvalidDoubleBufferShape((usize, usize, usize)),
+    #[error("Monitor portions can only be captured from window targets.")]
+    MonitorPortionWithoutWindow,
+    #[error("The window does not intersect with the monitor.")]
+    WindowNotOnMonitor,
+    #[error("Desktop points can only be mapped to the frames of monitor targets.")]
+    PointMappingWithoutMonitor,
+    #[error("Failed to convert size: {0}")]
+    SizeConversionError(#[from] TryFromIntError),
+    #[error("The minimum update interval requires Windows 11 24H2 or newer.")]
+    MinUpdateIntervalUnsupported,
+    #[error("Including secondary windows requires Windows 11 or newer.")]
+    SecondaryWindowsUnsupported,
+    #[error("Fixed interval {0:?} must be at least 1 ms and fit into a 32-bit timer.")]
+    InvalidFixedInterval(Duration),
+    #[error("Difference threshold {0} is not in the range [0, 1].")]
+    InvalidDifferenceThreshold(f64),
+    #[error("Downsample factor must be at least 1.")]
+    InvalidDownsample,
+    #[error("At least one frame has to be averaged.")]
+    InvalidAverageCount,
+    #[error("The capture target was resized while the resize policy was Error.")]
+    TargetResized,
+    #[error("Alpha can only be unpremultiplied for the Rgba8 color format.")]
+    UnpremultiplyFormatUnsupported,
+    #[error("Recording failed: {0}")]
+    RecorderError(#[from] RecorderError),
+    #[error("Pipe failed: {0}")]
+    PipeError(#[from] PipeError),
+}
+
+impl From<CaptureError> for PyErr {
+    fn from(error: CaptureError) -> PyErr {
+        let message = error.to_string();
+        match error {
+            CaptureError::DirectXError(_) => errors::DirectXError::new_err(message),
+            CaptureError::RecorderError(_) => errors::RecorderError::new_err(message),
+            CaptureError::PipeError(_) => errors::PipeError::new_err(message),
+            CaptureError::CaptureThreadError
+            | CaptureError::ThreadSpawnError(_)
+            | CaptureError::NoFirstFrame
+            | CaptureError::TargetResized => errors::CaptureThreadError::new_err(message),
+            CaptureError::NotRunning | CaptureError::StoppedBeforeFrame => {
+                errors::CaptureNotRunningError::new_err(message)
+            }
+            CaptureError::NoFrameAvailable | CaptureError::FrameTimeout => {
+                errors::FrameUnavailableError::new_err(message)
+            }
+            _ => errors::PixelForgeError::new_err(message),
+        }
+    }
+}
+
+/// Options of a capture session started with `CaptureCore::start`.
+#[derive(Clone, Copy, Debug)]
+pub struct CaptureOptions {
+    /// The maximum number of frames per second that are stored. If `None`, the refresh rate of the
+    /// monitor displaying the target is used
+    pub max_fps: Option<u32>,
+    /// Restore a minimized window target instead of failing with `CaptureError::WindowMinimized`
+    pub restore_minimized: bool,
+    /// The lowest feature level the DirectX device has to support
+    pub min_feature_level: FeatureLevel,
+    /// Fall back to the WARP software rasterizer if no hardware device can be created
+    pub software_fallback: bool,
+    /// The pixel format of the captured frames
+    pub color_format: ColorFormat,
+    /// The number of frame buffers of the capture frame pool
+    pub buffer_count: u32,
+    /// The index of the graphics adapter that runs the capture. If `None`, the default adapter is
+    /// used
+    pub adapter_index: Option<u32>,
+    /// Downscale frames on the GPU by this factor in the range (0, 1]. If `None`, frames are not
+    /// scaled
+    pub scale: Option<f32>,
+    /// The minimum time between two frames delivered by the compositor. Builds without support
+    /// for the interval skip frames in the frame handler instead. If `None`, the compositor
+    /// delivers frames whenever the target changes
+    pub min_update_interval: Option<Duration>,
+    /// The COM apartment in which the capture thread initializes the Windows Runtime
+    pub apartment_type: ApartmentType,
+    /// The scheduling priority of the capture thread
+    pub thread_priority: ThreadPriority,
+    /// The number of recent frames that are kept in addition to the latest frame. Each kept frame
+    /// holds a copy of its texture in GPU memory
+    pub history_size: usize,
+    /// Store exactly one frame per interval from a timer on the capture thread. Frames repeat the
+    /// latest content if the target did not change. Replaces the `max_fps` limit. If `None`,
+    /// frames are stored when the compositor delivers them
+    pub fixed_interval: Option<Duration>,
+    /// Rebind a closed window target to a new top-level window of the same process and window
+    /// class, e.g. a game that recreates its window when switching to fullscreen
+    pub sticky_window: bool,
+    /// The `D3D11_CREATE_DEVICE_FLAG` values the DirectX device is created with, e.g. to enable
+    /// the debug layer. Must include `D3D11_CREATE_DEVICE_BGRA_SUPPORT`
+    pub device_flags: D3D11_CREATE_DEVICE_FLAG,
+    /// The number of frames that are queued for `CaptureCore::recv_queued` in addition to the
+    /// latest frame. Each queued frame holds a copy of its texture in GPU memory. If the queue is
+    /// full, the oldest frame is dropped. If 0, no frames are queued
+    pub queue_depth: usize,
+    /// How the capture reacts to a resized target
+    pub resize_policy: ResizePolicy,
+    /// Convert the premultiplied alpha delivered by the compositor to straight alpha when frames
+    /// are copied to the CPU. Only supported for `ColorFormat::Rgba8`
+    pub unpremultiply_alpha: bool,
+    /// The (width, height) that the frame pool buffers of window and monitor targets are at least
+    /// allocated with. Targets that grow up to this size do not recreate the frame pool, and
+    /// frames are cropped to the content instead, unless the resize policy is `Fixed`. If `None`,
+    /// the buffers have the target size
+    pub max_size: Option<(u32, u32)>,
+    /// Also capture the secondary windows of a window target, e.g. the popups and dialogs that
+    /// packaged apps render in separate windows. Fails with
+    /// `CaptureError::SecondaryWindowsUnsupported` on builds without support
+    pub include_secondary_windows: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            max_fps: None,
+            restore_minimized: false,
+            min_feature_level: FeatureLevel::default(),
+            software_fallback: false,
+            color_format: ColorFormat::default(),
+            buffer_count: 1,
+            adapter_index: None,
+            scale: None,
+            min_update_interval: None,
+            apartment_type: ApartmentType::default(),
+            thread_priority: ThreadPriority::default(),
+            history_size: 0,
+            fixed_interval: None,
+            sticky_window: false,
+            device_flags: DEFAULT_DEVICE_FLAGS,
+            queue_depth: 0,
+            resize_policy: ResizePolicy::default(),
+            unpremultiply_alpha: false,
+            max_size: None,
+            include_secondary_windows: false,
+        }
+    }
+}
+
+/// A consumer of the frames stored by a capture, e.g. to encode, stream or tee them.
+///
+/// Sinks are added with `CaptureCore::add_sink` and run on the capture thread after the latest
+/// frame was updated, so slow sinks delay the following frames. The frame references the buffers
+/// of the frame pool, which are overwritten by later frames. Sinks that keep frames beyond
+/// `on_frame` have to keep a copy from `Frame::detached` instead.
+pub trait FrameSink: Send + Sync {
+    /// Process a newly stored frame.
+    fn on_frame(&self, frame: &Frame);
+}
+
+/// The capture engine behind the Python `Capture` class.
+///
+/// `CaptureCore` does not depend on a Python interpreter, so Rust applications can use it
+/// directly. The capture runs in a background thread that keeps the latest frame, which can be
+/// retrieved with `latest_frame` and copied to the CPU with `Frame::materialize`. The capture is
+/// stopped when the core is dropped.
+pub struct CaptureCore {
+    thread: Option<JoinHandle<Result<(), CaptureError>>>,
+    thread_id: Arc<Mutex<Option<u32>>>,
+    pub(crate) frame: Arc<FrameSlot>,
+    // Signaled whenever a new frame is stored or the capture is stopped
+    pub(crate) frame_arrived: Arc<Condvar>,
+    pub(crate) frame_tracker: Arc<FrameTracker>,
+    pub(crate) running: Arc<AtomicBool>,
+    // Set under the signal lock of the frame slot once the capture thread exits for any reason,
+    // including errors and panics, so waiting threads do not wait for frames that never arrive
+    pub(crate) thread_exited: Arc<AtomicBool>,
+    // True if the compositor applies the minimum update interval of the running capture
+    throttled_at_source: Arc<AtomicBool>,
+    // Set once a target of the running capture has been closed, e.g. a closed window
+    pub(crate) closed: Arc<AtomicBool>,
+    // Set once the running capture ended because its window was resized under `ResizePolicy::Error`
+    target_resized: Arc<AtomicBool>,
+    // Id of the last ping posted to the capture thread
+    last_ping: AtomicUsize,
+    // Id of the last ping acknowledged by the message loop, signaled on every acknowledgment
+    ping_acknowledged: Arc<(Mutex<usize>, Condvar)>,
+    // Device supplied by the user that replaces the device created for each capture
+    device: Option<SendDirectX<ID3D11Device>>,
+    // Target of the running capture. Sticky window targets are replaced by the capture thread
+    target: Arc<Mutex<Option<CaptureTarget>>>,
+    // Pixel format of the running or last capture
+    color_format: ColorFormat,
+    // Feature level of the device of the running or last capture, set once the device exists
+    feature_level: Arc<Mutex<Option<FeatureLevel>>>,
+    // Encoder that receives every stored frame while a recording is running
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    // Writer that streams raw frames while a pipe is running
+    pipe: Arc<Mutex<Option<PipeWriter>>>,
+    // Copies of the most recent frames, oldest first
+    history: Arc<Mutex<VecDeque<Frame>>>,
+    // Copies of the frames that have not been received yet, oldest first. Waiters are woken by
+    // `frame_arrived`, so frames are queued while holding the signal lock of the frame slot
+    queue: Arc<Mutex<VecDeque<Frame>>>,
+    // Maximum number of queued frames of the running or last capture
+    queue_depth: usize,
+    // Resize policy of the running or last capture
+    resize_policy: ResizePolicy,
+    // Whether the frames of the running or last capture are converted to straight alpha
+    unpremultiply_alpha: bool,
+    // Sinks that receive every stored frame in addition to the latest frame sink
+    sinks: Vec<Arc<dyn FrameSink>>,
+}
+
+impl Default for CaptureCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureCore {
+    /// Create a capture core that creates its own DirectX device for each capture.
+    pub fn new() -> Self {
+        Self {
+            thread: None,
+            thread_id: Arc::new(Mutex::new(None)),
+            frame: Arc::new(FrameSlot::default()),
+            frame_arrived: Arc::new(Condvar::new()),
+            frame_tracker: Arc::new(FrameTracker::default()),
+            running: Arc::new(AtomicBool::new(false)),
+            thread_exited: Arc::new(AtomicBool::new(false)),
+            throttled_at_source: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            target_resized: Arc::new(AtomicBool::new(false)),
+            last_ping: AtomicUsize::new(0),
+            ping_acknowledged: Arc::new((Mutex::new(0), Condvar::new())),
+            device: None,
+            target: Arc::new(Mutex::new(None)),
+            color_format: ColorFormat::default(),
+            feature_level: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
+            pipe: Arc::new(Mutex::new(None)),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            queue_depth: 0,
+            resize_policy: ResizePolicy::default(),
+            unpremultiply_alpha: false,
+            sinks: Vec::new(),
+        }
+    }
+
+    /// Create a capture core that captures with an existing DirectX device.
+    ///
+    /// The `min_feature_level`, `software_fallback`, `adapter_index` and `device_flags` options are
+    /// ignored. The immediate context of the device is used from the capture thread and from the
+    /// threads that materialize frames, so it must not be used concurrently without multithread
+    /// protection.
+    pub fn with_device(device: ID3D11Device) -> Self {
+        let mut core = Self::new();
+        core.device = Some(SendDirectX::new(device));
+        core
+    }
+
+    /// Start capturing `capture_target` in a background thread.
+    ///
+    /// Returns once the capture sessions of the thread have started, so errors during the setup of
+    /// the device and the sessions are returned here. Errors after the setup end the thread and
+    /// are returned by `try_stop`.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::Unsupported`: The system does not support graphics capture.
+    /// `CaptureError::WindowMinimized`: The window target is minimized and `restore_minimized` is
+    /// not set.
+    /// `CaptureError::InvalidCaptureTarget`: The target is no longer valid, or it has a zero size,
+    /// e.g. a rolled up window.
+    /// `CaptureError::InvalidScale`: The scale is not in the range (0, 1].
+    /// `CaptureError::UnpremultiplyFormatUnsupported`: `unpremultiply_alpha` is set for a color
+    /// format other than `Rgba8`.
+    /// `CaptureError::DirectXError`: The device does not support the color format.
+    /// `CaptureError::InvalidFixedInterval`: The fixed interval is shorter than a millisecond or
+    /// does not fit into a timer.
+    /// Any error that ended the capture thread during its setup, e.g. a failed creation of the
+    /// frame pool or the capture session.
+    pub fn start(
+        &mut self,
+        capture_target: CaptureTarget,
+        options: &CaptureOptions,
+    ) -> Result<(), CaptureError> {
+        // Starting again would overwrite the thread handle and leave the old thread running
+        if self.is_active() {
+            return Err(CaptureError::AlreadyStarted);
+        }
+        // The thread of a capture whose target was closed has exited on its own and is joined here
+        if self.thread.is_some() {
+            let _ = self.try_stop();
+        }
+        // Fail early instead of deep inside the capture thread if the OS cannot capture at all
+        if !is_supported() {
+            return Err(CaptureError::Unsupported);
+        }
+        // Check that window and monitor targets are still valid. Minimized windows have a zero
+        // size capture item and never produce frames, so we either restore them or refuse to start
+        match &capture_target {
+            CaptureTarget::Window(window) => {
+                // Child windows are captured through their top-level window
+                let root = window.root();
+                if root.is_minimized() && options.restore_minimized {
+                    root.restore();
+                }
+                if root.is_minimized() {
+                    return Err(CaptureError::WindowMinimized);
+                }
+                if !root.valid() || !window.is_visible() {
+                    return Err(CaptureError::InvalidCaptureTarget);
+                }
+            }
+            // Unplugged monitors leave a stale handle that never produces frames
+            CaptureTarget::Monitor(monitor) => {
+                if !monitor.valid() {
+                    return Err(CaptureError::InvalidCaptureTarget);
+                }
+            }
+            CaptureTarget::VirtualDesktop(_) | CaptureTarget::Item(_) => {}
+        }
+        let composite_size = capture_target.composite_size();
+        // Composite frames copy whole tiles into a texture of fixed size, so their frame pools
+        // cannot be larger than the sources
+        let max_size = options.max_size.filter(|_| composite_size.is_none());
+        let refresh_rate = capture_target.refresh_rate();
+        let sources = capture_target.capture_sources()?;
+        // Frame pools cannot be created for empty items, and the error they fail with does not
+        // name the cause. Rolled up windows and misbehaving monitors report such items
+        for source in &sources {
+            let size = source.item.Size()?;
+            if size.Width <= 0 || size.Height <= 0 {
+                return Err(CaptureError::InvalidCaptureTarget);
+            }
+        }
+        // Window targets are watched for moves to another monitor
+        let window = match &capture_target {
+            CaptureTarget::Window(window) => Some(*window),
+            _ => None,
+        };
+        // The process and class of a closed window identify the window that replaces it. Both
+        // have to be looked up while the window still exists
+        let sticky_window = window
+            .filter(|window| options.sticky_window && !window.is_child())
+            .map(|window| {
+                let class_name = window
+                    .class_name()
+                    .map_err(|_| CaptureError::InvalidCaptureTarget)?;
+                Ok::<_, CaptureError>((window.process_id(), class_name))
+            })
+            .transpose()?;
+        let downscale_level = match options.scale {
+            Some(scale) if scale > 0.0 && scale <= 1.0 => downscale_level(scale),
+            Some(scale) => return Err(CaptureError::InvalidScale(scale)),
+            None => 0,
+        };
+        if options.unpremultiply_alpha && options.color_format != ColorFormat::Rgba8 {
+            return Err(CaptureError::UnpremultiplyFormatUnsupported);
+        }
+        // Check the adapter before spawning the thread so a wrong index is reported to the caller
+        if let Some(index) = options.adapter_index {
+            find_adapter(index)?;
+        }
+        // Create the DirectX device unless the user supplied one. Frame pools fail with an opaque
+        // error on pixel formats the device does not support, so we check the format on the device
+        // before spawning the thread, which then runs the capture on the same device
+        let device = match &self.device {
+            Some(device) => device.0.clone(),
+            None => {
+                create_d3d_device(
+                    options.min_feature_level,
+                    options.software_fallback,
+                    options.adapter_index,
+                    options.device_flags,
+                )?
+                .0
+            }
+        };
+        check_color_format(&device, options.color_format)?;
+        let fixed_interval_ms = options
+            .fixed_interval
+            .map(|interval| match u32::try_from(interval.as_millis()) {
+                Ok(ms) if ms > 0 => Ok(ms),
+                _ => Err(CaptureError::InvalidFixedInterval(interval)),
+            })
+            .transpose()?;
+
+        self.thread_id.lock().take(); // Clear the thread_id when starting a new capture
+        self.frame_tracker.reset(); // Statistics are collected per capture session
+        self.throttled_at_source.store(false, Ordering::SeqCst);
+        self.closed.store(false, Ordering::SeqCst);
+        self.target_resized.store(false, Ordering::SeqCst);
+        self.thread_exited.store(false, Ordering::SeqCst);
+        self.history.lock().clear();
+        self.queue.lock().clear();
+        self.queue_depth = options.queue_depth;
+        self.resize_policy = options.resize_policy;
+        self.unpremultiply_alpha = options.unpremultiply_alpha;
+        self.feature_level.lock().take();
+
+        // Clone Arc capture struct members to use them in thread without borrowing
+        let thread_id = self.thread_id.clone();
+        let frame = self.frame.clone();
+        let frame_arrived = self.frame_arrived.clone();
+        let frame_tracker = self.frame_tracker.clone();
+        let exit_guard = ExitGuard {
+            frame: self.frame.clone(),
+            frame_arrived: self.frame_arrived.clone(),
+            exited: self.thread_exited.clone(),
+        };
+        let latest_frame_sink = LatestFrameSink {
+            frame: self.frame.clone(),
+            frame_arrived: self.frame_arrived.clone(),
+            frame_tracker: self.frame_tracker.clone(),
+            recorder: self.recorder.clone(),
+            pipe: self.pipe.clone(),
+            history: self.history.clone(),
+            history_size: options.history_size,
+            queue: self.queue.clone(),
+            queue_depth: options.queue_depth,
+        };
+        let sinks: Vec<Arc<dyn FrameSink>> = iter::once(Arc::new(latest_frame_sink) as _)
+            .chain(self.sinks.iter().cloned())
+            .collect();
+        let sinks: Arc<[Arc<dyn FrameSink>]> = sinks.into();
+        let throttled_at_source = self.throttled_at_source.clone();
+        let closed = self.closed.clone();
+        let target_resized = self.target_resized.clone();
+        let ping_acknowledged = self.ping_acknowledged.clone();
+        let target = self.target.clone();
+        let feature_level = self.feature_level.clone();
+        let device = SendDirectX::new(device);
+        // Minimum time between two stored frames. Frames arriving faster are skipped. Throttling is
+        // opt-in, since the compositor never delivers frames faster than the refresh rate anyway.
+        // The timer of a fixed interval capture sets the pace instead, so it keeps every frame
+        let min_frame_interval = options
+            .max_fps
+            .filter(|fps| *fps > 0 && fixed_interval_ms.is_none())
+            // Frames delivered at exactly the limit rate jitter slightly. We allow a tolerance of
+            // 10% to avoid skipping every other frame in this case
+            .map(|fps| Duration::from_secs(1) * 9 / fps.saturating_mul(10));
+        // Frame pools need at least one buffer
+        let buffer_count = i32::try_from(options.buffer_count.max(1)).unwrap_or(i32::MAX);
+        let CaptureOptions {
+            color_format,
+            min_update_interval,
+            apartment_type,
+            thread_priority,
+            resize_policy,
+            unpremultiply_alpha,
+            include_secondary_windows,
+            ..
+        } = *options;
+
+        // Create a thread to run the capture. Named threads also get a matching thread description
+        // on Windows, so the capture is easy to find in debuggers and profilers
+        let thread_name = format!("pixel_forge-capture-{}", capture_target.label());
+        let builder = thread::Builder::new().name(thread_name);
+        let (started_sender, started_receiver) = mpsc::channel();
+        let capture_thread = builder.spawn(move || -> Result<(), CaptureError> {
+            // Wakes up waiting threads however the thread exits
+            let _exit_guard = exit_guard;
+            // Dropped without a message if the setup fails
+            let mut started = Some(started_sender);
+            if thread_priority != ThreadPriority::Normal {
+                unsafe { SetThreadPriority(GetCurrentThread(), thread_priority.into())? };
+            }
+            // Initialize the Windows Runtime. Hosting applications may have initialized COM on
+            // this thread with a different apartment type already, which is fine for capturing
+            let ro_initialized = match unsafe { RoInitialize(apartment_type.into()) } {
+                Ok(()) => true,
+                Err(error) if error.code() == RPC_E_CHANGED_MODE => false,
+                Err(error) => return Err(error.into()),
+            };
+            // Create a dispatcher queue for the current thread
+            let options = DispatcherQueueOptions {
+                dwSize: u32::try_from(mem::size_of::<DispatcherQueueOptions>())?,
+                threadType: DQTYPE_THREAD_CURRENT,
+                apartmentType: DQTAT_COM_NONE,
+            };
+            let controller = unsafe { CreateDispatcherQueueController(options)? };
+            thread_id.lock().replace(unsafe { GetCurrentThreadId() });
+
+            // The device was created or supplied before the thread was spawned
+            let d3d_device = device.0;
+            let d3d_device_context = unsafe { d3d_device.GetImmediateContext()? };
+            *feature_level.lock() = FeatureLevel::from_d3d(unsafe { d3d_device.GetFeatureLevel() });
+            let direct3d_device = create_direct3d_device(&d3d_device)?;
+            let pixel_format = color_format.to_directx_pixel_format();
+
+            // State shared by the frame handlers of all sources. A frame is only stored once every
+            // source has delivered at least one tile
+            let frame_state = Arc::new(Mutex::new(FrameState {
+                tiles: vec![None; sources.len()],
+                last_frame_time: None,
+                frame_id: 0,
+                // The first frame changes the whole frame
+                dirty_regions: None,
+                missed_frames: vec![0; sources.len()],
+                counts_missed_frames: refresh_rate.is_some_and(|fps| fps > 0),
+                skipped_frame: None,
+                skipped_frame_timer: None,
+            }));
+            // Copy of the latest content that the timer of a fixed interval capture stores
+            let latest_content: Arc<Mutex<Option<Frame>>> = Arc::new(Mutex::new(None));
+
+            // Sticky window targets are rebound to a new window of the same process and class
+            // once they are closed. The sessions are created again for the new window
+            let rebind_thread_id = sticky_window
+                .is_some()
+                .then(|| unsafe { GetCurrentThreadId() });
+            // Other closed targets end the message loop, so the thread releases the capture
+            let capture_thread_id = unsafe { GetCurrentThreadId() };
+            let mut sources = sources;
+            let mut window = window;
+            let mut teardown = Ok(());
+            let mut resized = false;
+            let mut target_closed = false;
+            loop {
+                // Create a frame pool and an associated capture session for each source
+                let mut sessions = Vec::with_capacity(sources.len());
+                for (index, source) in sources.into_iter().enumerate() {
+                    let pool_size = frame_pool_size(source.item.Size()?, max_size);
+                    let frame_pool = Arc::new(Direct3D11CaptureFramePool::Create(
+                        &direct3d_device,
+                        pixel_format,
+                        buffer_count,
+                        pool_size,
+                    )?);
+                    let session = frame_pool.CreateCaptureSession(&source.item)?;
+                    if include_secondary_windows {
+                        set_include_secondary_windows(&session)?;
+                    }
+                    // Let the compositor skip frames if the build supports it. Otherwise, the frame
+                    // handler skips frames that arrive faster than the interval
+                    let min_frame_interval = match min_update_interval {
+                        Some(interval) => match set_min_update_interval(&session, interval) {
+                            Ok(()) => {
+                                throttled_at_source.store(true, Ordering::SeqCst);
+                                min_frame_interval
+                            }
+                            Err(CaptureError::MinUpdateIntervalUnsupported) => Some(
+                                min_frame_interval.map_or(interval, |limit| limit.max(interval)),
+                            ),
+                            Err(error) => return Err(error),
+                        },
+                        None => min_frame_interval,
+                    };
+                    // Frames are skipped based on their frame times in 100ns units
+                    let min_frame_interval = min_frame_interval.map(|interval| {
+                        i64::try_from(interval.as_nanos() / 100).unwrap_or(i64::MAX)
+                    });
+                    // The compositor delivers frames at most once per refresh of the monitor, or
+                    // once per minimum update interval if it throttles the session. Frame times
+                    // are in 100ns units
+                    let delivery_interval = refresh_rate
+                        .filter(|fps| *fps > 0)
+                        .map(|fps| 10_000_000 / i64::from(fps))
+                        .map(|interval| match min_update_interval {
+                            Some(min_interval) if throttled_at_source.load(Ordering::SeqCst) => {
+                                let min_interval = i64::try_from(min_interval.as_nanos() / 100)
+                                    .unwrap_or(i64::MAX);
+                                interval.max(min_interval)
+                            }
+                            _ => interval,
+                        });
+
+                    // Set frame pool frame arrived event
+                    let frame_arrived_event_token =
+                        frame_pool.FrameArrived(&TypedEventHandler::<
+                            Direct3D11CaptureFramePool,
+                            IInspectable,
+                        >::new({
+                            let frame_pool = frame_pool.clone();
+                            let d3d_device = d3d_device.clone();
+                            let context = d3d_device_context.clone();
+                            let frame_tracker = frame_tracker.clone();
+                            let frame_state = frame_state.clone();
+                            let sinks = sinks.clone();
+                            let latest_content = latest_content.clone();
+
+                            let mut last_size = source.item.Size()?;
+                            let mut pool_size = pool_size;
+                            let mut last_delivery_time = None;
+                            let mut last_monitor = window.and_then(|window| window.monitor());
+                            let direct3d_device_recreate =
+                                SendDirectX::new(direct3d_device.clone());
+
+                            move |frame, _| {
+                                // Get frame. The sender can be missing while the pool is closed
+                                let Some(sender) = frame.as_ref() else {
+                                    return Ok(());
+                                };
+                                // A drained frame pool returns a null frame, which surfaces as an
+                                // error without a failure code. The next event brings a new frame
+                                let frame = match sender.TryGetNextFrame() {
+                                    Ok(frame) => frame,
+                                    Err(error) if error.code().is_ok() => return Ok(()),
+                                    Err(error) => return Err(error),
+                                };
+                                // Get frame time, content size, changed regions and surface
+                                let frame_time = frame.SystemRelativeTime()?.Duration;
+                                let dirty_regions = frame_dirty_regions(&frame)?;
+                                let frame_content_size = frame.ContentSize()?;
+                                let frame_surface = frame.Surface()?;
+                                // Convert surface to texture
+                                let frame_dxgi_interface =
+                                    frame_surface.cast::<IDirect3DDxgiInterfaceAccess>()?;
+                                let frame_texture = unsafe {
+                                    frame_dxgi_interface.GetInterface::<ID3D11Texture2D>()?
+                                };
+
+                                // Get texture settings
+                                let mut desc = D3D11_TEXTURE2D_DESC::default();
+                                unsafe { frame_texture.GetDesc(&mut desc) }
+
+                                // Windows that move to a monitor with a different DPI change their
+                                // scale, so users have to know when to query the DPI of the new
+                                // monitor
+                                if let Some(window) = window {
+                                    let monitor = window.monitor();
+                                    if monitor != last_monitor {
+                                        last_monitor = monitor;
+                                        frame_tracker.monitor_changed();
+                                    }
+                                }
+
+                                // Check if the size has been changed, and recreate the frame pool
+                                // if necessary. Fixed size captures keep the frame pool, so the
+                                // compositor fits the content into the original buffers
+                                if (frame_content_size.Width != last_size.Width
+                                    || frame_content_size.Height != last_size.Height)
+                                    && resize_policy != ResizePolicy::Fixed
+                                {
+                                    if resize_policy == ResizePolicy::Error {
+                                        // Let the message loop end the capture
+                                        last_size = frame_content_size;
+                                        unsafe {
+                                            PostThreadMessageW(
+                                                GetCurrentThreadId(),
+                                                WM_TARGET_RESIZED,
+                                                WPARAM(0),
+                                                LPARAM(0),
+                                            )?
+                                        };
+                                        return Ok(());
+                                    }
+                                    last_size = frame_content_size;
+                                    frame_tracker.frame_resized();
+                                    frame_state.lock().dirty_regions = None;
+                                    // Pre-sized frame pools are only recreated once the content
+                                    // outgrows their buffers
+                                    if max_size.is_none()
+                                        || frame_content_size.Width > pool_size.Width
+                                        || frame_content_size.Height > pool_size.Height
+                                    {
+                                        pool_size = frame_pool_size(frame_content_size, max_size);
+                                        let direct3d_device_recreate = &direct3d_device_recreate;
+                                        frame_pool.Recreate(
+                                            &direct3d_device_recreate.0,
+                                            pixel_format,
+                                            buffer_count,
+                                            pool_size,
+                                        )?;
+                                        return Ok(());
+                                    }
+                                }
+                                let mut state = frame_state.lock();
+                                // Count the frames the compositor skipped since the last delivery
+                                // of this source until a frame is stored
+                                if let (Some(interval), Some(last_time)) =
+                                    (delivery_interval, last_delivery_time.replace(frame_time))
+                                {
+                                    state.missed_frames[index] +=
+                                        missed_frames(frame_time - last_time, interval);
+                                }
+                                // Collect the changes of all tiles and skipped frames until a frame
+                                // is stored. Tiles without known changes make the whole frame
+                                // unknown
+                                state.dirty_regions =
+                                    match (state.dirty_regions.take(), dirty_regions) {
+                                        (Some(mut regions), Some(tile_regions)) => {
+                                            regions.extend(tile_regions.into_iter().map(
+                                                |region| Region {
+                                                    x: region.x + source.x,
+                                                    y: region.y + source.y,
+                                                    ..region
+                                                },
+                                            ));
+                                            Some(regions)
+                                        }
+                                        _ => None,
+                                    };
+                                state.tiles[index] = Some(FrameTile {
+                                    texture: frame_texture,
+                                    x: source.x,
+                                    y: source.y,
+                                });
+                                // Wait until all sources have delivered a tile
+                                if state.tiles.iter().any(Option::is_none) {
+                                    return Ok(());
+                                }
+                                // Skip the frame if the last frame was stored less than the minimum
+                                // interval ago. Frame times are compared, so delays of the handler do
+                                // not change which frames are skipped
+                                let remaining = match (min_frame_interval, state.last_frame_time) {
+                                    (Some(interval), Some(last_time)) => {
+                                        interval - (frame_time - last_time)
+                                    }
+                                    _ => 0,
+                                };
+                                // Set width & height. Composite frames have a fixed size, and frames
+                                // of pre-sized frame pools are cropped to their content
+                                let (texture_width, texture_height) = match max_size {
+                                    Some(_) if resize_policy != ResizePolicy::Fixed => (
+                                        frame_content_size.Width.unsigned_abs().min(desc.Width),
+                                        frame_content_size.Height.unsigned_abs().min(desc.Height),
+                                    ),
+                                    _ => composite_size.unwrap_or((desc.Width, desc.Height)),
+                                };
+                                // Create a frame
+                                let tiles = state.tiles.iter().flatten().cloned().collect();
+                                // The content of fixed size captures can exceed the texture
+                                let content_size = composite_size.unwrap_or((
+                                    frame_content_size.Width.unsigned_abs().min(desc.Width),
+                                    frame_content_size.Height.unsigned_abs().min(desc.Height),
+                                ));
+                                let mut new_frame = Frame::new(
+                                    tiles,
+                                    0,
+                                    texture_height,
+                                    texture_width,
+                                    content_size,
+                                    color_format,
+                                    frame_time,
+                                    downscale_level,
+                                    d3d_device.clone(),
+                                    context.clone(),
+                                );
+                                new_frame.unpremultiply_alpha = unpremultiply_alpha;
+                                // The last change before the content settles would be lost with a
+                                // skipped frame, since the compositor sends no further frames. The
+                                // latest skipped frame is kept as a copy, because the frame pool
+                                // reuses its buffers, and the message loop stores it once the
+                                // interval elapsed
+                                if remaining > 0 {
+                                    if let Ok(copy) = new_frame.detached() {
+                                        state.skipped_frame = Some(copy);
+                                        if state.skipped_frame_timer.is_none() {
+                                            let ms = u32::try_from((remaining + 9_999) / 10_000)
+                                                .unwrap_or(u32::MAX);
+                                            let timer = unsafe { SetTimer(None, 0, ms, None) };
+                                            state.skipped_frame_timer =
+                                                (timer != 0).then_some(timer);
+                                        }
+                                    }
+                                    return Ok(());
+                                }
+                                state.discard_skipped_frame();
+                                state.store(&mut new_frame);
+                                // The timer stores the content of fixed interval captures. The
+                                // frame pool reuses its buffers, so the content is kept as a copy
+                                if fixed_interval_ms.is_some() {
+                                    if let Ok(copy) = new_frame.detached() {
+                                        *latest_content.lock() = Some(copy);
+                                    }
+                                    return Ok(());
+                                }
+                                dispatch(&sinks, &new_frame);
+                                Result::Ok(())
+                            }
+                        }))?;
+
+                    // Windows raise the event when they are closed, monitors when they are
+                    // unplugged
+                    let closed_event_token = source.item.Closed(&TypedEventHandler::<
+                        GraphicsCaptureItem,
+                        IInspectable,
+                    >::new({
+                        let closed = closed.clone();
+                        let frame = frame.clone();
+                        let frame_arrived = frame_arrived.clone();
+                        move |_, _| {
+                            // Sticky windows are rebound by the message loop instead
+                            if let Some(thread_id) = rebind_thread_id {
+                                unsafe {
+                                    PostThreadMessageW(thread_id, WM_REBIND, WPARAM(0), LPARAM(0))?
+                                };
+                                return Ok(());
+                            }
+                            // Set the flag under the signal lock so waiting threads cannot miss
+                            // the notification
+                            let _frame_guard = frame.lock();
+                            closed.store(true, Ordering::SeqCst);
+                            frame_arrived.notify_all();
+                            unsafe {
+                                PostThreadMessageW(
+                                    capture_thread_id,
+                                    WM_TARGET_CLOSED,
+                                    WPARAM(0),
+                                    LPARAM(0),
+                                )?
+                            };
+                            Ok(())
+                        }
+                    }))?;
+                    sessions.push((
+                        frame_pool,
+                        session,
+                        frame_arrived_event_token,
+                        source.item,
+                        closed_event_token,
+                    ));
+                }
+                for (_, session, ..) in &sessions {
+                    session.StartCapture()?;
+                }
+
+                // Thread timers post WM_TIMER messages without a window to the message loop
+                let timer = fixed_interval_ms.map(|ms| unsafe { SetTimer(None, 0, ms, None) });
+                if timer == Some(0) {
+                    return Err(WindowsError::from_win32().into());
+                }
+                // Sessions of rebound sticky windows are set up again, but only the first setup
+                // is reported
+                if let Some(started) = started.take() {
+                    let _ = started.send(());
+                }
+                let mut timer_frame_id = 0;
+
+                // Create message loops. Pump messages while the message is not WM_QUIT, or until a
+                // closed sticky window has to be rebound
+                let mut rebind = false;
+                let mut msg = MSG::default();
+                unsafe {
+                    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                        if msg.message == WM_REBIND && msg.hwnd.0 == 0 {
+                            rebind = true;
+                            break;
+                        }
+                        if msg.message == WM_TARGET_RESIZED && msg.hwnd.0 == 0 {
+                            resized = true;
+                            break;
+                        }
+                        if msg.message == WM_TARGET_CLOSED && msg.hwnd.0 == 0 {
+                            target_closed = true;
+                            break;
+                        }
+                        if msg.message == WM_PING && msg.hwnd.0 == 0 {
+                            let (last_acknowledged, acknowledged) = &*ping_acknowledged;
+                            let mut last_acknowledged = last_acknowledged.lock();
+                            *last_acknowledged = (*last_acknowledged).max(msg.wParam.0);
+                            acknowledged.notify_all();
+                            continue;
+                        }
+                        if msg.message == WM_TIMER && msg.hwnd.0 == 0 && timer != Some(msg.wParam.0)
+                        {
+                            // The interval after a skipped frame elapsed without a newer frame
+                            let skipped_frame = {
+                                let mut state = frame_state.lock();
+                                if state.skipped_frame_timer == Some(msg.wParam.0) {
+                                    state.skipped_frame_timer = None;
+                                }
+                                let _ = KillTimer(None, msg.wParam.0);
+                                state.skipped_frame.take().map(|mut skipped_frame| {
+                                    state.store(&mut skipped_frame);
+                                    skipped_frame
+                                })
+                            };
+                            if let Some(skipped_frame) = skipped_frame {
+                                dispatch(&sinks, &skipped_frame);
+                            }
+                            continue;
+                        }
+                        if msg.message == WM_TIMER && msg.hwnd.0 == 0 {
+                            // Store the latest content again if the target did not change. Ticks
+                            // before the first content arrived are skipped
+                            let content = latest_content.lock().clone();
+                            if let Some(mut new_frame) = content {
+                                timer_frame_id += 1;
+                                new_frame.id = timer_frame_id;
+                                new_frame.time = system_relative_time()?;
+                                // Repeated content and content of several frames cannot be told
+                                // apart
+                                new_frame.dirty_regions = None;
+                                new_frame.missed_frames = None;
+                                dispatch(&sinks, &new_frame);
+                            }
+                            continue;
+                        }
+                        TranslateMessage(&msg);
+                        DispatchMessageW(&msg);
+                    }
+                    if let Some(timer) = timer {
+                        let _ = KillTimer(None, timer);
+                    }
+                }
+                // Skipped frames of the old sessions are not stored anymore
+                frame_state.lock().discard_skipped_frame();
+
+                // Remove event handlers and close the frame pools and capture sessions. Closed
+                // targets can fail some of these calls, so the remaining resources are released
+                // anyway and the first error is reported
+                for (frame_pool, session, frame_arrived_event_token, item, closed_event_token) in
+                    sessions
+                {
+                    let results = [
+                        frame_pool.RemoveFrameArrived(frame_arrived_event_token),
+                        item.RemoveClosed(closed_event_token),
+                        frame_pool.Close(),
+                        session.Close(),
+                    ];
+                    for result in results {
+                        teardown = teardown.and(result);
+                    }
+                }
+                if target_closed {
+                    // Closed targets cannot be released cleanly, so their teardown errors are
+                    // expected
+                    teardown = Ok(());
+                    break;
+                }
+                if resized {
+                    // The window is still open, so the target is not closed. The thread exits with
+                    // the error, which wakes up waiting threads
+                    target_resized.store(true, Ordering::SeqCst);
+                    break;
+                }
+                let Some((process_id, class_name)) = sticky_window.as_ref().filter(|_| rebind)
+                else {
+                    break;
+                };
+                // The closed window cannot be released cleanly, so its teardown errors are expected
+                teardown = Ok(());
+                let replaced = window.map(|window| window.as_handle());
+                if let Some(replacement) =
+                    find_replacement_window(*process_id, class_name, replaced)
+                {
+                    let replacement_target = CaptureTarget::Window(replacement);
+                    sources = replacement_target.capture_sources()?;
+                    window = Some(replacement);
+                    *target.lock() = Some(replacement_target);
+                    frame_tracker.target_rebound();
+                    continue;
+                }
+                // No replacement appeared, so the capture ends like a capture of a closed window
+                let _frame_guard = frame.lock();
+                closed.store(true, Ordering::SeqCst);
+                frame_arrived.notify_all();
+                break;
+            }
+            // Shutdown dispatcher queue
+            let async_shutdown = controller.ShutdownQueueAsync()?;
+            async_shutdown.SetCompleted(&AsyncActionCompletedHandler::new(
+                move |_, _| -> Result<(), windows::core::Error> {
+                    unsafe { PostQuitMessage(0) };
+                    Ok(())
+                },
+            ))?;
+
+            // Only balance our own initialization, a failed RoInitialize must not be uninitialized
+            if ro_initialized {
+                unsafe { RoUninitialize() };
+            }
+            if resized {
+                return Err(CaptureError::TargetResized);
+            }
+            Ok(teardown?)
+        })?;
+        // Wait for the setup so that its errors are returned to the caller instead of only
+        // ending the thread
+        if started_receiver.recv().is_err() {
+            self.thread_id.lock().take();
+            return Err(match capture_thread.join() {
+                Ok(Err(error)) => error,
+                _ => CaptureError::CaptureThreadError,
+            });
+        }
+        self.thread = Some(capture_thread);
+        *self.target.lock() = Some(capture_target);
+        self.color_format = color_format;
+        self.running.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Block until the first frame of the capture is available.
+    ///
+    /// Returns true if a frame arrived, or false if the capture thread exited before that.
+    pub fn await_first_frame(&self) -> bool {
+        let mut frame_guard = self.frame.lock();
+        while !self.frame.has_frame() && !self.has_thread_exited() {
+            self.frame_arrived.wait(&mut frame_guard);
+        }
+        self.frame.has_frame()
+    }
+
+    /// Stop the capture thread, wait for it to join and invalidate the last frame.
+    ///
+    /// A running recording and pipe are finished first. Their errors are discarded, use
+    /// `stop_recording` and `stop_pipe` to receive them. Errors of the capture thread are
+    /// discarded as well, use `try_stop` to receive them.
+    pub fn stop(&mut self) {
+        let _ = self.try_stop();
+    }
+
+    /// Stop the capture like `stop` and return the error the capture thread exited with.
+    ///
+    /// The capture is stopped and its resources are released even if an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::CaptureThreadError`: The capture thread panicked.
+    /// Any other error that ended the capture thread, e.g. a failed teardown of the capture
+    /// session of a target that was destroyed.
+    pub fn try_stop(&mut self) -> Result<(), CaptureError> {
+        let _ = self.stop_recording();
+        let _ = self.stop_pipe();
+        self.running.store(false, Ordering::SeqCst);
+        // If the thread_id is set, send a WM_QUIT message to the message pumping thread. The
+        // message pumping thread will receive the WM_QUIT message, stop its loop and close the
+        // dispatcher queue
+        if let Some(thread_id) = self.thread_id.lock().take() {
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+        let result = match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .unwrap_or(Err(CaptureError::CaptureThreadError)),
+            None => Ok(()),
+        };
+        self.target.lock().take();
+        self.frame.clear(); // Clear the frame when the capture is stopped
+        self.history.lock().clear();
+        self.queue.lock().clear();
+        self.frame_tracker.clear_unread();
+        self.frame_arrived.notify_all(); // Wake up threads waiting for a frame
+        result
+    }
+
+    /// Wake up all threads that wait for a frame and let them return as if the capture stopped.
+    ///
+    /// The capture thread keeps running until `stop` is called. Bindings use this to release
+    /// waiting threads that keep them from stopping the capture.
+    pub fn interrupt_waits(&self) {
+        // Notify under the signal lock so waiting threads cannot miss the notification
+        let _frame_guard = self.frame.lock();
+        self.running.store(false, Ordering::SeqCst);
+        self.frame_arrived.notify_all();
+    }
+
+    /// Return true if the capture thread is running.
+    ///
+    /// The thread exits on its own once a target is closed or the capture fails. The core is
+    /// inactive afterwards, but the thread is only joined by `try_stop`, `stop` or the next
+    /// `start`, so its error can still be retrieved.
+    pub fn is_active(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Check whether the message loop of the capture thread is alive and responsive.
+    ///
+    /// Posts a message to the capture thread and waits up to `timeout` for the message loop to
+    /// acknowledge it. Returns false if no capture thread is running or it did not respond in
+    /// time, e.g. because it is deadlocked or waits for the replacement of a sticky window.
+    pub fn ping(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let Some(thread_id) = *self.thread_id.lock() else {
+            return false;
+        };
+        if !self.is_active() {
+            return false;
+        }
+        let (last_acknowledged, acknowledged) = &*self.ping_acknowledged;
+        // Post under the lock so the acknowledgment cannot be missed
+        let mut last_acknowledged = last_acknowledged.lock();
+        let id = self.last_ping.fetch_add(1, Ordering::SeqCst) + 1;
+        let posted = unsafe { PostThreadMessageW(thread_id, WM_PING, WPARAM(id), LPARAM(0)) };
+        if posted.is_err() {
+            return false;
+        }
+        while *last_acknowledged < id {
+            if acknowledged
+                .wait_until(&mut last_acknowledged, deadline)
+                .timed_out()
+            {
+                return *last_acknowledged >= id;
+            }
+        }
+        true
+    }
+
+    /// Return the target of the running capture.
+    pub fn target(&self) -> Option<CaptureTarget> {
+        self.target.lock().clone()
+    }
+
+    /// Return the pixel format of the running capture, or of the last one if it was stopped.
+    pub fn color_format(&self) -> ColorFormat {
+        self.color_format
+    }
+
+    /// Return the feature level of the device of the running capture, or of the last one if it was
+    /// stopped.
+    ///
+    /// Returns `None` until the capture thread has created its device, or if the device has a
+    /// level without a `FeatureLevel` variant.
+    pub fn feature_level(&self) -> Option<FeatureLevel> {
+        *self.feature_level.lock()
+    }
+
+    /// Return the latest frame, or `None` if no frame has arrived yet.
+    ///
+    /// The frame is acquired without blocking the capture thread. Frames only hold references to
+    /// their GPU textures, so this does not copy any pixels.
+    pub fn latest_frame(&self) -> Option<Arc<Frame>> {
+        self.frame.load()
+    }
+
+    /// Return up to `count` of the most recent frames, oldest first.
+    ///
+    /// Only frames kept according to `CaptureOptions::history_size` are available.
+    pub fn recent_frames(&self, count: usize) -> Vec<Frame> {
+        let history = self.history.lock();
+        let skip = history.len().saturating_sub(count);
+        history.iter().skip(skip).cloned().collect()
+    }
+
+    /// Wait for the oldest queued frame and remove it from the queue.
+    ///
+    /// Only frames queued according to `CaptureOptions::queue_depth` are received. Returns `None`
+    /// if the queue is still empty at `deadline`, or once the queue is empty and the capture has
+    /// stopped, its thread exited or its target was closed. Waits indefinitely if `deadline` is
+    /// `None`.
+    pub fn recv_queued(&self, deadline: Option<Instant>) -> Option<Frame> {
+        let mut frame_guard = self.frame.lock();
+        loop {
+            if let Some(frame) = self.queue.lock().pop_front() {
+                return Some(frame);
+            }
+            if !self.running.load(Ordering::SeqCst) || self.has_thread_exited() || self.is_closed()
+            {
+                return None;
+            }
+            match deadline {
+                Some(deadline) => {
+                    if self
+                        .frame_arrived
+                        .wait_until(&mut frame_guard, deadline)
+                        .timed_out()
+                    {
+                        return self.queue.lock().pop_front();
+                    }
+                }
+                None => self.frame_arrived.wait(&mut frame_guard),
+            }
+        }
+    }
+
+    /// Return the number of frames in the queue.
+    pub fn queued_frames(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    /// Return the queue depth of the running capture, or of the last one if it was stopped.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Add a sink that receives every frame stored by the following captures.
+    ///
+    /// The latest frame, history, queue and recording keep working, so sinks can tee the frames.
+    /// Sinks added while a capture is running take effect when the next capture is started.
+    pub fn add_sink(&mut self, sink: Arc<dyn FrameSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Remove all sinks added with `add_sink`.
+    pub fn clear_sinks(&mut self) {
+        self.sinks.clear();
+    }
+
+    /// Return the resize policy of the running capture, or of the last one if it was stopped.
+    pub fn resize_policy(&self) -> ResizePolicy {
+        self.resize_policy
+    }
+
+    /// Return whether the frames of the running capture, or of the last one if it was stopped,
+    /// are converted to straight alpha.
+    pub fn unpremultiply_alpha(&self) -> bool {
+        self.unpremultiply_alpha
+    }
+
+    /// Start encoding all following frames into the H.264 MP4 file at `path`.
+    ///
+    /// The recording has the size of the latest frame. Frames of a different size, e.g. after the
+    /// target was resized, are skipped. Frame timestamps are kept, so frames skipped because of
+    /// `max_fps` lengthen the previous frame instead of speeding up the video.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::NoFrameAvailable`: No frame has arrived yet.
+    /// `RecorderError::AlreadyRecording`: A recording is already running.
+    /// `RecorderError::UnsupportedColorFormat`: The capture does not use the `Rgba8` format.
+    pub fn start_recording(&self, path: &str, fps: u32) -> Result<(), CaptureError> {
+        let frame = self.latest_frame().ok_or(CaptureError::NoFrameAvailable)?;
+        let mut recorder = self.recorder.lock();
+        if recorder.is_some() {
+            return Err(RecorderError::AlreadyRecording.into());
+        }
+        *recorder = Some(Recorder::new(path, fps, &frame)?);
+        Ok(())
+    }
+
+    /// Finish the running recording and close its file.
+    ///
+    /// # Errors
+    ///
+    /// `RecorderError::NotRecording`: No recording is running.
+    /// `CaptureError::RecorderError`: Encoding a frame or finishing the file failed.
+    pub fn stop_recording(&self) -> Result<(), CaptureError> {
+        // Take the recorder out of the lock so that finishing the file does not block the capture
+        let recorder = self.recorder.lock().take();
+        recorder.ok_or(RecorderError::NotRecording)?.finish()?;
+        Ok(())
+    }
+
+    /// Return true if a recording is running.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().is_some()
+    }
+
+    /// Start streaming all following frames as raw RGBA bytes to `path` at `fps` frames per
+    /// second. A `path` of `-` streams to stdout.
+    ///
+    /// The stream has the size of the latest frame. Frames of a different size are skipped.
+    /// Frames are written on the capture thread, so a slow reader delays the capture.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::NoFrameAvailable`: No frame has arrived yet.
+    /// `PipeError::AlreadyPiping`: A pipe is already running.
+    /// `PipeError::UnsupportedColorFormat`: The capture does not use the `Rgba8` format.
+    /// `PipeError::IoError`: The file or pipe could not be opened.
+    pub fn start_pipe(&self, path: &str, fps: u32) -> Result<(), CaptureError> {
+        let frame = self.latest_frame().ok_or(CaptureError::NoFrameAvailable)?;
+        let mut pipe = self.pipe.lock();
+        if pipe.is_some() {
+            return Err(PipeError::AlreadyPiping.into());
+        }
+        *pipe = Some(PipeWriter::new(path, fps, &frame)?);
+        Ok(())
+    }
+
+    /// Stop the running pipe and flush its output.
+    ///
+    /// # Errors
+    ///
+    /// `PipeError::NotPiping`: No pipe is running.
+    /// `CaptureError::PipeError`: Writing a frame or flushing the output failed.
+    pub fn stop_pipe(&self) -> Result<(), CaptureError> {
+        let pipe = self.pipe.lock().take();
+        pipe.ok_or(PipeError::NotPiping)?.finish()?;
+        Ok(())
+    }
+
+    /// Return true if a pipe is running.
+    pub fn is_piping(&self) -> bool {
+        self.pipe.lock().is_some()
+    }
+
+    /// Return true if the compositor applies the minimum update interval of the running capture.
+    ///
+    /// If false, frames are skipped in the frame handler instead. The value is set before the
+    /// first frame arrives.
+    pub fn is_throttled_at_source(&self) -> bool {
+        self.throttled_at_source.load(Ordering::SeqCst)
+    }
+
+    /// Return true if a target of the running capture has been closed.
+    ///
+    /// Closed targets stop delivering frames, but the capture keeps running until it is stopped.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Return true if the last started capture ended because its window target was resized under
+    /// `ResizePolicy::Error`.
+    pub fn is_resized(&self) -> bool {
+        self.target_resized.load(Ordering::SeqCst)
+    }
+
+    /// Return true if the thread of the last started capture has exited, e.g. because it failed.
+    ///
+    /// Unlike `is_active`, the flag is set under the signal lock of the frame slot before waiting
+    /// threads are notified, so waiters can check it without missing the exit.
+    pub fn has_thread_exited(&self) -> bool {
+        self.thread_exited.load(Ordering::SeqCst)
+    }
+
+    /// Return the frame statistics since the last call of `start`.
+    pub fn stats(&self) -> CaptureStats {
+        self.frame_tracker.stats()
+    }
+
+    /// Return a watch that waits for the frames of this core without borrowing it.
+    pub fn watch(&self) -> FrameWatch {
+        FrameWatch {
+            frame: self.frame.clone(),
+            frame_arrived: self.frame_arrived.clone(),
+            running: self.running.clone(),
+            thread_exited: self.thread_exited.clone(),
+            closed: self.closed.clone(),
+        }
+    }
+}
+
+/// Shared view of the latest frame of a capture core for consumers on other threads.
+///
+/// Each consumer tracks the frames it has seen by their timestamps, so consumers do not take new
+/// frames away from each other or from the core. The watch stays valid across restarts of the
+/// core.
+#[derive(Clone)]
+pub struct FrameWatch {
+    frame: Arc<FrameSlot>,
+    frame_arrived: Arc<Condvar>,
+    running: Arc<AtomicBool>,
+    thread_exited: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+}
+
+impl FrameWatch {
+    /// Return true if the capture has not been stopped. Closed targets do not stop the capture.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Return true if the capture is running, its thread has not exited and its target has not
+    /// been closed.
+    pub fn is_receiving(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+            && !self.thread_exited.load(Ordering::SeqCst)
+            && !self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the capture stops, its target is closed or the deadline passes. Returns true if
+    /// the target was closed.
+    pub fn wait_for_close(&self, deadline: Instant) -> bool {
+        let mut frame_guard = self.frame.lock();
+        while self.is_receiving() {
+            if self
+                .frame_arrived
+                .wait_until(&mut frame_guard, deadline)
+                .timed_out()
+            {
+                break;
+            }
+        }
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the latest frame has a different timestamp than `seen`, the capture stops or the
+    /// deadline passes. Returns true if such a frame is available.
+    pub fn wait_for_new(&self, seen: Option<i64>, deadline: Option<Instant>) -> bool {
+        let is_new = || {
+            self.frame
+                .load()
+                .is_some_and(|frame| Some(frame.time) != seen)
+        };
+        let mut frame_guard = self.frame.lock();
+        loop {
+            if is_new() {
+                return true;
+            }
+            if !self.is_receiving() {
+                return false;
+            }
+            match deadline {
+                Some(deadline) => {
+                    if self
+                        .frame_arrived
+                        .wait_until(&mut frame_guard, deadline)
+                        .timed_out()
+                    {
+                        return is_new();
+                    }
+                }
+                None => self.frame_arrived.wait(&mut frame_guard),
+            }
+        }
+    }
+}
+
+// The size of the frame pool buffers for content of `size`, enlarged to `max_size` if given
+fn frame_pool_size(size: SizeInt32, max_size: Option<(u32, u32)>) -> SizeInt32 {
+    let Some((max_width, max_height)) = max_size else {
+        return size;
+    };
+    let clamp = |len: u32| i32::try_from(len).unwrap_or(i32::MAX);
+    SizeInt32 {
+        Width: size.Width.max(clamp(max_width)),
+        Height: size.Height.max(clamp(max_height)),
+    }
+}
+
+/// Check if the system supports graphics capture.
+pub fn is_supported() -> bool {
+    GraphicsCaptureSession::IsSupported().unwrap_or(false)
+}
+
+// Thread message that the closed handler of a sticky window posts to the capture thread
+const WM_REBIND: u32 = WM_APP + 1;
+// Thread message that the frame handler posts if the target was resized under `ResizePolicy::Error`
+const WM_TARGET_RESIZED: u32 = WM_APP + 2;
+// Thread message that the closed handler of a target posts to end the capture thread
+const WM_TARGET_CLOSED: u32 = WM_APP + 3;
+// Thread message that `ping` posts to check that the message loop is responsive
+const WM_PING: u32 = WM_APP + 4;
+// Maximum time to wait for the replacement of a closed sticky window
+const REBIND_TIMEOUT: Duration = Duration::from_secs(10);
+const REBIND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wait for a visible top-level window of the process and window class of a closed window.
+///
+/// Returns `None` if no replacement appeared within `REBIND_TIMEOUT` or the capture is stopped.
+fn find_replacement_window(
+    process_id: u32,
+    class_name: &str,
+    replaced: Option<HWND>,
+) -> Option<Window> {
+    let deadline = Instant::now() + REBIND_TIMEOUT;
+    let mut msg = MSG::default();
+    while Instant::now() < deadline {
+        // WM_QUIT stays in the queue for the message loop that ends the capture
+        if unsafe { PeekMessageW(&mut msg, None, WM_QUIT, WM_QUIT, PM_NOREMOVE) }.as_bool() {
+            return None;
+        }
+        let replacement = enumerate_top_level_windows(false)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|window| {
+                Some(window.as_handle()) != replaced
+                    && window.process_id() == process_id
+                    && !window.is_minimized()
+                    && window.class_name().is_ok_and(|name| name == class_name)
+            });
+        if replacement.is_some() {
+            return replacement;
+        }
+        thread::sleep(REBIND_POLL_INTERVAL);
+    }
+    None
+}
+
+// GraphicsCaptureSession interface that adds the minimum update interval. The interface is newer
+// than the bindings, so it is declared here
+#[repr(transparent)]
+#[derive(Clone)]
+struct IGraphicsCaptureSession5(IUnknown);
+
+unsafe impl Interface for IGraphicsCaptureSession5 {
+    type Vtable = IGraphicsCaptureSession5Vtbl;
+    const IID: GUID = GUID::from_u128(0x67c0ea62_1f85_5061_925a_239be0ac09cb);
+}
+
+#[repr(C)]
+struct IGraphicsCaptureSession5Vtbl {
+    base: IInspectable_Vtbl,
+    min_update_interval: unsafe extern "system" fn(*mut c_void, *mut TimeSpan) -> HRESULT,
+    set_min_update_interval: unsafe extern "system" fn(*mut c_void, TimeSpan) -> HRESULT,
+}
+
+/// Set the minimum time between two frames the compositor delivers to the session.
+///
+/// # Errors
+///
+/// `CaptureError::MinUpdateIntervalUnsupported`: The Windows build does not support the interval.
+fn set_min_update_interval(
+    session: &GraphicsCaptureSession,
+    interval: Duration,
+) -> Result<(), CaptureError> {
+    let session = match session.cast::<IGraphicsCaptureSession5>() {
+        Ok(session) => session,
+        Err(error) if error.code() == E_NOINTERFACE => {
+            return Err(CaptureError::MinUpdateIntervalUnsupported)
+        }
+        Err(error) => return Err(error.into()),
+    };
+    // TimeSpans are in 100ns units
+    let interval = TimeSpan {
+        Duration: i64::try_from(interval.as_nanos() / 100).unwrap_or(i64::MAX),
+    };
+    unsafe { (session.vtable().set_min_update_interval)(session.as_raw(), interval).ok()? };
+    Ok(())
+}
+
+// GraphicsCaptureSession interface that adds the secondary windows of window targets. The interface
+// is newer than the bindings, so it is declared here
+#[repr(transparent)]
+#[derive(Clone)]
+struct IGraphicsCaptureSession6(IUnknown);
+
+unsafe impl Interface for IGraphicsCaptureSession6 {
+    type Vtable = IGraphicsCaptureSession6Vtbl;
+    const IID: GUID = GUID::from_u128(0xd7419236_be20_5e9f_bcd6_c4e98fd6afdc);
+}
+
+#[repr(C)]
+struct IGraphicsCaptureSession6Vtbl {
+    base: IInspectable_Vtbl,
+    include_secondary_windows: unsafe extern "system" fn(*mut c_void, *mut bool) -> HRESULT,
+    set_include_secondary_windows: unsafe extern "system" fn(*mut c_void, bool) -> HRESULT,
+}
+
+/// Let the session also capture the secondary windows of its window target.
+///
+/// # Errors
+///
+/// `CaptureError::SecondaryWindowsUnsupported`: The Windows build does not support secondary
+/// windows.
+fn set_include_secondary_windows(session: &GraphicsCaptureSession) -> Result<(), CaptureError> {
+    let session = match session.cast::<IGraphicsCaptureSession6>() {
+        Ok(session) => session,
+        Err(error) if error.code() == E_NOINTERFACE => {
+            return Err(CaptureError::SecondaryWindowsUnsupported)
+        }
+        Err(error) => return Err(error.into()),
+    };
+    unsafe { (session.vtable().set_include_secondary_windows)(session.as_raw(), true).ok()? };
+    Ok(())
+}
+
+#[repr(transparent)]
+#[derive(Clone)]
+struct IDirect3D11CaptureFrame2(IUnknown);
+
+unsafe impl Interface for IDirect3D11CaptureFrame2 {
+    type Vtable = IDirect3D11CaptureFrame2Vtbl;
+    const IID: GUID = GUID::from_u128(0x37869cfa_2b48_5ebf_9afb_dffd805defdb);
+}
+
+#[repr(C)]
+struct IDirect3D11CaptureFrame2Vtbl {
+    base: IInspectable_Vtbl,
+    dirty_regions: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+    dirty_region_mode: unsafe extern "system" fn(*mut c_void, *mut i32) -> HRESULT,
+}
+
+/// Return the regions of a captured frame that changed since the previous frame of its pool.
+///
+/// Returns `None` if the Windows build does not report changed regions.
+fn frame_dirty_regions(
+    frame: &Direct3D11CaptureFrame,
+) -> windows::core::Result<Option<Vec<Region>>> {
+    let frame = match frame.cast::<IDirect3D11CaptureFrame2>() {
+        Ok(frame) => frame,
+        Err(error) if error.code() == E_NOINTERFACE => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    let mut regions = ptr::null_mut();
+    unsafe { (frame.vtable().dirty_regions)(frame.as_raw(), &mut regions).ok()? };
+    let regions = unsafe { IVectorView::<RectInt32>::from_raw(regions) };
+    // Regions can extend beyond the frame on the top and left edges, which are clipped
+    (0..regions.Size()?)
+        .map(|index| {
+            let rect = regions.GetAt(index)?;
+            let (x, y) = (rect.X.max(0), rect.Y.max(0));
+            Ok(Region {
+                x: x.unsigned_abs(),
+                y: y.unsigned_abs(),
+                width: (rect.X + rect.Width - x).max(0).unsigned_abs(),
+                height: (rect.Y + rect.Height - y).max(0).unsigned_abs(),
+            })
+        })
+        .collect::<windows::core::Result<_>>()
+        .map(Some)
+}
+
+// Marks the capture thread as exited and wakes up waiting threads when it is dropped at the end of
+// the thread. The flag is set under the signal lock so waiting threads cannot miss the notification
+struct ExitGuard {
+    frame: Arc<FrameSlot>,
+    frame_arrived: Arc<Condvar>,
+    exited: Arc<AtomicBool>,
+}
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        let _frame_guard = self.frame.lock();
+        self.exited.store(true, Ordering::SeqCst);
+        self.frame_arrived.notify_all();
+    }
+}
+
+// The default sink that makes frames the latest frame and passes them on to the recorder, the
+// pipe, the history and the queue
+struct LatestFrameSink {
+    frame: Arc<FrameSlot>,
+    frame_arrived: Arc<Condvar>,
+    frame_tracker: Arc<FrameTracker>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    pipe: Arc<Mutex<Option<PipeWriter>>>,
+    history: Arc<Mutex<VecDeque<Frame>>>,
+    history_size: usize,
+    queue: Arc<Mutex<VecDeque<Frame>>>,
+    queue_depth: usize,
+}
+
+impl FrameSink for LatestFrameSink {
+    fn on_frame(&self, new_frame: &Frame) {
+        // Encode the frame before taking the signal lock so that waiting threads are not blocked
+        // by the encoder
+        if let Some(recorder) = self.recorder.lock().as_mut() {
+            recorder.write(new_frame);
+        }
+        if let Some(pipe) = self.pipe.lock().as_mut() {
+            pipe.write(new_frame);
+        }
+        // The frame pool reuses its buffers, so the history keeps copies. Frames that fail to
+        // copy are left out of the history
+        if self.history_size > 0 {
+            if let Ok(copy) = new_frame.detached() {
+                let mut history = self.history.lock();
+                if history.len() == self.history_size {
+                    history.pop_front();
+                }
+                history.push_back(copy);
+            }
+        }
+        let queued = if self.queue_depth > 0 {
+            new_frame.detached().ok()
+        } else {
+            None
+        };
+        // Waiting threads check their conditions under the signal lock, so the frame is published
+        // under it as well. Readers that do not wait acquire the frame without the lock
+        let _signal_guard = self.frame.lock();
+        if let Some(copy) = queued {
+            let mut queue = self.queue.lock();
+            if queue.len() == self.queue_depth {
+                queue.pop_front();
+                self.frame_tracker.queue_dropped();
+            }
+            queue.push_back(copy);
+        }
+        // Mark the frame as unread before it is published, so readers of the replaced frame
+        // cannot mark it as read
+        self.frame_tracker.frame_stored(new_frame.id);
+        self.frame.publish(new_frame.clone());
+        self.frame_arrived.notify_all();
+    }
+}
+
+// Pass a stored frame to all sinks of a capture, starting with the latest frame sink
+fn dispatch(sinks: &[Arc<dyn FrameSink>], frame: &Frame) {
+    for sink in sinks {
+        sink.on_frame(frame);
+    }
+}
+
+// Frame handler state that is shared between the capture sources of a capture
+struct FrameState {
+    tiles: Vec<Option<FrameTile>>,
+    // Frame time of the last stored frame
+    last_frame_time: Option<i64>,
+    frame_id: u64,
+    // Changed regions since the last stored frame. None if the changes are unknown
+    dirty_regions: Option<Vec<Region>>,
+    // Frames each source skipped since the last stored frame
+    missed_frames: Vec<u64>,
+    // Missed frames are only counted if the refresh rate of the target is known
+    counts_missed_frames: bool,
+    // Copy of the latest frame skipped by the minimum frame interval, and the thread timer that
+    // stores it once the interval elapsed
+    skipped_frame: Option<Frame>,
+    skipped_frame_timer: Option<usize>,
+}
+
+impl FrameState {
+    // Assign the next id to `frame` and hand it the changes since the last stored frame
+    fn store(&mut self, frame: &mut Frame) {
+        self.last_frame_time = Some(frame.time);
+        self.frame_id += 1;
+        frame.id = self.frame_id;
+        frame.dirty_regions = self.dirty_regions.replace(Vec::new());
+        // Composite frames are as late as their most delayed source
+        frame.missed_frames = self
+            .counts_missed_frames
+            .then(|| self.missed_frames.iter().max().copied().unwrap_or(0));
+        self.missed_frames.fill(0);
+    }
+
+    // Drop the skipped frame once a newer frame is stored or the sessions are closed
+    fn discard_skipped_frame(&mut self) {
+        self.skipped_frame = None;
+        if let Some(timer) = self.skipped_frame_timer.take() {
+            let _ = unsafe { KillTimer(None, timer) };
+        }
+    }
+}
+
+// Drop trait implementation to stop the capture thread when the CaptureCore struct is dropped.
+// This is also executed when a Capture goes out of scope in Python, making sure that the capture
+// thread is stopped
+impl Drop for CaptureCore {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,99 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::capture::{Capture, CaptureConfig, CaptureError};
+
+/// CaptureGroup(capture_targets: list[CaptureTarget]) -> CaptureGroup
+/// Group of captures that are started, stopped and read together.
+///
+/// CaptureGroup owns one :class:`.Capture` per target. All captures share the same settings and
+/// their frames are returned in a single call, keyed by the target they belong to.
+///
+/// Args:
+///     capture_targets: The :class:`.Monitor`, :class:`.Window` or :class:`.VirtualDesktop`
+///         targets to capture.
+#[pyclass]
+pub struct CaptureGroup {
+    captures: Vec<(PyObject, Capture)>,
+}
+
+#[pymethods]
+impl CaptureGroup {
+    #[new]
+    pub fn new(capture_targets: Vec<&PyAny>) -> PyResult<Self> {
+        let captures = capture_targets
+            .into_iter()
+            .map(|target| Ok((target.into(), Capture::new(Some(target.extract()?)))))
+            .collect::<PyResult<_>>()?;
+        Ok(Self { captures })
+    }
+
+    /// start(await_first_frame: bool = True, max_fps: int | None = None)
+    ///
+    /// Start the captures of all targets.
+    ///
+    /// If any capture fails to start, all captures of the group are stopped again.
+    ///
+    /// Args:
+    ///     await_first_frame: Waits for the first frame of each capture to arrive if True.
+    ///     max_fps: The maximum number of frames per second that are stored for each capture.
+    pub fn start(
+        &mut self,
+        py: Python,
+        await_first_frame: Option<bool>,
+        max_fps: Option<u32>,
+    ) -> PyResult<()> {
+        let mut errors = Vec::new();
+        for (target, capture) in self.captures.iter_mut() {
+            let config = CaptureConfig {
+                await_first_frame: await_first_frame.unwrap_or(true),
+                max_fps,
+                ..CaptureConfig::default()
+            };
+            if let Err(error) = capture.start_with(config) {
+                errors.push(format!("{}: {}", target.as_ref(py).repr()?, error));
+            }
+        }
+        if !errors.is_empty() {
+            self.stop();
+            return Err(CaptureError::GroupError(errors.join("; ")).into());
+        }
+        Ok(())
+    }
+
+    /// Stop the captures of all targets.
+    pub fn stop(&mut self) {
+        for (_, capture) in self.captures.iter_mut() {
+            capture.stop();
+        }
+    }
+
+    /// :``bool``: True if the captures of all targets are running, False otherwise.
+    #[getter]
+    pub fn active(&self) -> bool {
+        self.captures.iter().all(|(_, capture)| capture.active())
+    }
+
+    /// frames() -> dict[CaptureTarget, np.ndarray]
+    /// Convert the latest frame of each capture to an array and return them.
+    ///
+    /// :returns: A dictionary mapping each target to its latest frame.
+    pub fn frames<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let frames = PyDict::new(py);
+        let mut errors = Vec::new();
+        for (target, capture) in self.captures.iter() {
+            match capture.py_frame(py, None) {
+                Ok(frame) => frames.set_item(target, frame)?,
+                Err(error) => errors.push(format!("{}: {}", target.as_ref(py).repr()?, error)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(CaptureError::GroupError(errors.join("; ")).into());
+        }
+        Ok(frames)
+    }
+
+    fn __len__(&self) -> usize {
+        self.captures.len()
+    }
+}
@@ -1,18 +1,53 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use arc_swap::ArcSwapOption;
+use parking_lot::{Mutex, MutexGuard};
+
 use pyo3::prelude::*;
+use pyo3::types::PyCFunction;
 
 use windows::Graphics::Capture::GraphicsCaptureItem;
+use windows::Graphics::DirectX::DirectXPixelFormat;
+use windows::Win32::Graphics::Dxgi::Common::{
+    DXGI_FORMAT, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM,
+};
+use windows::Win32::System::Performance::{QueryPerformanceCounter, QueryPerformanceFrequency};
+use windows::Win32::System::Threading::{
+    THREAD_PRIORITY, THREAD_PRIORITY_ABOVE_NORMAL, THREAD_PRIORITY_BELOW_NORMAL,
+    THREAD_PRIORITY_HIGHEST, THREAD_PRIORITY_LOWEST, THREAD_PRIORITY_NORMAL,
+    THREAD_PRIORITY_TIME_CRITICAL,
+};
+use windows::Win32::System::WinRT::{RO_INIT_MULTITHREADED, RO_INIT_SINGLETHREADED, RO_INIT_TYPE};
 
-use crate::monitor::Monitor;
+use crate::direct_x::FeatureLevel;
+use crate::errors;
+use crate::frame::{Frame, MappedFrame};
+use crate::monitor::{enumerate_monitors, Monitor, VirtualDesktop};
 use crate::window::Window;
 
-// We define a capture target as either a monitor or a window. Pyo3 does not allow functions
-// generics, so we have to use an enum to represent the two types of capture sources that we can
-// pass to Capture::start. We also define the TryInto trait for CaptureTarget to convert it into a
-// GraphicsCaptureItem, which is what we ultimately need to start capturing frames.
-#[derive(FromPyObject)]
+// We define a capture target as either a monitor, a window or the virtual desktop. Pyo3 does not
+// allow functions generics, so we have to use an enum to represent the types of capture sources that
+// we can pass to Capture::start. Each target is converted into one or more CaptureSources, which
+// hold the GraphicsCaptureItems we ultimately need to start capturing frames.
+#[derive(FromPyObject, Clone, Debug)]
 pub enum CaptureTarget {
     Monitor(Monitor),
     Window(Window),
+    VirtualDesktop(VirtualDesktop),
+    Item(CaptureItem),
+}
+
+impl IntoPy<PyObject> for CaptureTarget {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            CaptureTarget::Monitor(monitor) => monitor.into_py(py),
+            CaptureTarget::Window(window) => window.into_py(py),
+            CaptureTarget::VirtualDesktop(desktop) => desktop.into_py(py),
+            CaptureTarget::Item(item) => item.into_py(py),
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -21,31 +56,826 @@ pub enum CaptureTargetError {
     MonitorConversionError,
     #[error("Failed to Window to GraphicsCaptureItem")]
     WindowConversionError,
+    #[error("Failed to get the size of the capture item")]
+    SizeUnavailable,
+    #[error("Failed to get the name of the capture item")]
+    NameUnavailable,
+}
+
+impl From<CaptureTargetError> for PyErr {
+    fn from(error: CaptureTargetError) -> PyErr {
+        errors::PixelForgeError::new_err(error.to_string())
+    }
+}
+
+/// CaptureItem
+/// A capture target selected by the user in the system picker, see :func:`pick_capture_target`.
+///
+/// Capture items can be passed to :meth:`.Capture.start` like monitors and windows.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct CaptureItem {
+    item: GraphicsCaptureItem,
+}
+
+impl CaptureItem {
+    pub fn new(item: GraphicsCaptureItem) -> Self {
+        Self { item }
+    }
+}
+
+#[pymethods]
+impl CaptureItem {
+    /// :``str``: The name of the item as shown in the picker, e.g. the window title.
+    #[getter]
+    pub fn display_name(&self) -> Result<String, CaptureTargetError> {
+        let name = self
+            .item
+            .DisplayName()
+            .map_err(|_| CaptureTargetError::NameUnavailable)?;
+        Ok(name.to_string_lossy())
+    }
+
+    fn __repr__(&self) -> String {
+        let name = self.display_name().unwrap_or_default();
+        format!("CaptureItem(display_name={name:?})")
+    }
+}
+
+// A single capture item together with its pixel offset inside the frame. Monitors and windows are
+// captured as a single source at the origin, the virtual desktop as one source per monitor
+pub struct CaptureSource {
+    pub item: GraphicsCaptureItem,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl CaptureSource {
+    fn new(item: GraphicsCaptureItem) -> Self {
+        Self { item, x: 0, y: 0 }
+    }
 }
 
-// Make CaptureTarget convertible to GraphicsCaptureItem for all enum variants
-impl TryInto<GraphicsCaptureItem> for CaptureTarget {
-    type Error = CaptureTargetError;
+impl CaptureTarget {
+    /// Return the kind of the target, e.g. for logging.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CaptureTarget::Monitor(_) => "monitor",
+            CaptureTarget::Window(_) => "window",
+            CaptureTarget::VirtualDesktop(_) => "virtual_desktop",
+            CaptureTarget::Item(_) => "item",
+        }
+    }
 
-    fn try_into(self) -> Result<GraphicsCaptureItem, Self::Error> {
+    /// Convert the capture target into the capture sources that make up its frames.
+    pub fn capture_sources(&self) -> Result<Vec<CaptureSource>, CaptureTargetError> {
+        match self {
+            CaptureTarget::Monitor(monitor) => {
+                Ok(vec![CaptureSource::new((*monitor).try_into().map_err(
+                    |_| CaptureTargetError::MonitorConversionError,
+                )?)])
+            }
+            CaptureTarget::Window(window) => Ok(vec![CaptureSource::new(
+                (*window)
+                    .try_into()
+                    .map_err(|_| CaptureTargetError::WindowConversionError)?,
+            )]),
+            CaptureTarget::VirtualDesktop(desktop) => {
+                let monitors =
+                    enumerate_monitors().map_err(|_| CaptureTargetError::MonitorConversionError)?;
+                let mut sources = Vec::with_capacity(monitors.len());
+                for monitor in monitors {
+                    let rect = monitor
+                        .rect()
+                        .map_err(|_| CaptureTargetError::MonitorConversionError)?;
+                    let item = monitor
+                        .try_into()
+                        .map_err(|_| CaptureTargetError::MonitorConversionError)?;
+                    // Shift monitors with negative coordinates into the frame
+                    sources.push(CaptureSource {
+                        item,
+                        x: (rect.left - desktop.x()).unsigned_abs(),
+                        y: (rect.top - desktop.y()).unsigned_abs(),
+                    });
+                }
+                Ok(sources)
+            }
+            CaptureTarget::Item(item) => Ok(vec![CaptureSource::new(item.item.clone())]),
+        }
+    }
+
+    /// The pixel size of the target's frames as (width, height).
+    pub fn size(&self) -> Result<(u32, u32), CaptureTargetError> {
+        if let Some(size) = self.composite_size() {
+            return Ok(size);
+        }
+        let size = match self.capture_sources()?.first() {
+            Some(source) => source
+                .item
+                .Size()
+                .map_err(|_| CaptureTargetError::SizeUnavailable)?,
+            None => return Ok((0, 0)),
+        };
+        Ok((size.Width.unsigned_abs(), size.Height.unsigned_abs()))
+    }
+
+    /// The refresh rate of the monitor(s) displaying the target, if it can be determined.
+    ///
+    /// Windows use the refresh rate of the monitor they are on, the virtual desktop the highest
+    /// refresh rate of all monitors.
+    pub fn refresh_rate(&self) -> Option<u32> {
+        match self {
+            CaptureTarget::Monitor(monitor) => monitor.refresh_rate().ok(),
+            CaptureTarget::Window(window) => window.monitor()?.refresh_rate().ok(),
+            CaptureTarget::VirtualDesktop(_) => enumerate_monitors()
+                .ok()?
+                .iter()
+                .filter_map(|monitor| monitor.refresh_rate().ok())
+                .max(),
+            // Picked items do not reveal the window or monitor they belong to
+            CaptureTarget::Item(_) => None,
+        }
+    }
+
+    /// A short human readable description of the target, e.g. for thread names.
+    pub fn label(&self) -> String {
         match self {
             CaptureTarget::Monitor(monitor) => monitor
-                .try_into()
-                .map_err(|_| CaptureTargetError::MonitorConversionError),
-            CaptureTarget::Window(window) => window
-                .try_into()
-                .map_err(|_| CaptureTargetError::WindowConversionError),
+                .index()
+                .map_or_else(|_| "monitor".to_string(), |index| format!("monitor{index}")),
+            CaptureTarget::Window(window) => window.name().unwrap_or_else(|_| "window".to_string()),
+            CaptureTarget::VirtualDesktop(_) => "virtual-desktop".to_string(),
+            CaptureTarget::Item(item) => item.display_name().unwrap_or_else(|_| "item".to_string()),
+        }
+    }
+
+    /// The fixed frame size of targets that are composed of several sources, None otherwise.
+    pub fn composite_size(&self) -> Option<(u32, u32)> {
+        match self {
+            CaptureTarget::VirtualDesktop(desktop) => Some((desktop.width(), desktop.height())),
+            _ => None,
         }
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+// Set the result of an asyncio future from a foreign thread. Futures are not thread-safe, so the
+// result has to be scheduled on the event loop with call_soon_threadsafe. Futures that are already
+// done (e.g. cancelled) are left untouched
+pub(crate) fn resolve_future(
+    py: Python,
+    event_loop: &PyObject,
+    future: PyObject,
+    result: PyResult<PyObject>,
+) {
+    let result = result.map_err(|err| err.into_value(py));
+    let Ok(callback) = PyCFunction::new_closure(py, None, None, move |_, _| -> PyResult<()> {
+        Python::with_gil(|py| {
+            if future.call_method0(py, "done")?.is_true(py)? {
+                return Ok(());
+            }
+            match &result {
+                Ok(array) => future.call_method1(py, "set_result", (array,))?,
+                Err(err) => future.call_method1(py, "set_exception", (err,))?,
+            };
+            Ok(())
+        })
+    }) else {
+        return;
+    };
+    // The event loop might already be closed, in which case there is nobody left to notify
+    let _ = event_loop.call_method1(py, "call_soon_threadsafe", (callback,));
+}
+
+// Run `task` in a background thread and return an asyncio future that resolves with its result.
+// Has to be called from within a running asyncio event loop
+pub(crate) fn spawn_future<T, F>(py: Python, task: F) -> PyResult<PyObject>
+where
+    T: IntoPy<PyObject>,
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+{
+    let event_loop: PyObject = py
+        .import("asyncio")?
+        .call_method0("get_running_loop")?
+        .into();
+    let future: PyObject = event_loop.call_method0(py, "create_future")?;
+    let waiter_future = future.clone_ref(py);
+    thread::Builder::new()
+        .name("pixel_forge-future".to_string())
+        .spawn(move || {
+            let result = task();
+            Python::with_gil(|py| {
+                let result = result.map(|value| value.into_py(py));
+                resolve_future(py, &event_loop, waiter_future, result);
+            });
+        })?;
+    Ok(future)
+}
+
+/// The current system relative time in 100ns units.
+///
+/// This is the clock of `Direct3D11CaptureFrame::SystemRelativeTime`, which is based on the
+/// performance counter.
+pub fn system_relative_time() -> windows::core::Result<i64> {
+    let mut counter = 0;
+    let mut frequency = 0;
+    unsafe {
+        QueryPerformanceCounter(&mut counter)?;
+        QueryPerformanceFrequency(&mut frequency)?;
+    }
+    // Widen the intermediate product to avoid overflows on long running systems
+    Ok((i128::from(counter) * 10_000_000 / i128::from(frequency)) as i64)
+}
+
+/// Pixel formats in which frames can be captured.
+///
+/// ``Rgba8`` frames are returned as ``uint8`` arrays in the sRGB color space. HDR and wide-gamut
+/// content is tone mapped to sRGB by the compositor. ``Rgba16Float`` preserves the extended range
+/// of HDR content and frames are returned as ``float16`` arrays in the linear scRGB color space,
+/// which has to be converted before it can be displayed as sRGB.
+#[pyclass]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
 pub enum ColorFormat {
-    Rgba8 = 28,
+    #[default]
+    Rgba8,
+    Rgba16Float,
+}
+
+// The frame pool and the staging textures describe their pixel format with different enums. We map
+// each color format explicitly to both so that they always agree on the memory layout
+impl ColorFormat {
+    /// All color formats, in the order of the variants.
+    pub const ALL: [ColorFormat; 2] = [ColorFormat::Rgba8, ColorFormat::Rgba16Float];
+
+    /// The pixel format of the capture frame pool.
+    pub const fn to_directx_pixel_format(self) -> DirectXPixelFormat {
+        match self {
+            ColorFormat::Rgba8 => DirectXPixelFormat::R8G8B8A8UIntNormalized,
+            ColorFormat::Rgba16Float => DirectXPixelFormat::R16G16B16A16Float,
+        }
+    }
+
+    /// The format of the textures that frames are copied into.
+    pub const fn to_dxgi_format(self) -> DXGI_FORMAT {
+        match self {
+            ColorFormat::Rgba8 => DXGI_FORMAT_R8G8B8A8_UNORM,
+            ColorFormat::Rgba16Float => DXGI_FORMAT_R16G16B16A16_FLOAT,
+        }
+    }
+
+    /// The number of bytes of a single channel.
+    pub const fn channel_size(self) -> u32 {
+        match self {
+            ColorFormat::Rgba8 => 1,
+            ColorFormat::Rgba16Float => 2,
+        }
+    }
+
+    /// The number of bytes of a single RGBA pixel.
+    pub const fn pixel_size(self) -> u32 {
+        self.channels() * self.channel_size()
+    }
+
+    /// The number of channels of a pixel.
+    pub const fn channels(self) -> u32 {
+        4
+    }
+
+    /// The NumPy dtype of the frame arrays.
+    pub const fn dtype(self) -> &'static str {
+        match self {
+            ColorFormat::Rgba8 => "uint8",
+            ColorFormat::Rgba16Float => "float16",
+        }
+    }
+
+    /// The color space of the frame arrays. The compositor converts the captured content into it.
+    pub const fn color_space(self) -> &'static str {
+        match self {
+            ColorFormat::Rgba8 => "sRGB",
+            ColorFormat::Rgba16Float => "scRGB",
+        }
+    }
+
+    /// The order of the channels along the last axis of the frame arrays.
+    pub const fn channel_order(self) -> &'static str {
+        match self {
+            ColorFormat::Rgba8 | ColorFormat::Rgba16Float => "RGBA",
+        }
+    }
+}
+
+/// COM apartment types in which the capture thread initializes the Windows Runtime.
+///
+/// ``Multithreaded`` works for most applications. Use ``Singlethreaded`` if the capture objects
+/// have to live in a single-threaded apartment, e.g. to interoperate with COM objects that are not
+/// free-threaded.
+#[pyclass]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum ApartmentType {
+    #[default]
+    Multithreaded,
+    Singlethreaded,
+}
+
+impl From<ApartmentType> for RO_INIT_TYPE {
+    fn from(apartment_type: ApartmentType) -> Self {
+        match apartment_type {
+            ApartmentType::Multithreaded => RO_INIT_MULTITHREADED,
+            ApartmentType::Singlethreaded => RO_INIT_SINGLETHREADED,
+        }
+    }
+}
+
+/// Scheduling priorities of the capture thread.
+///
+/// Raising the priority above ``Normal`` reduces stutters of high frame rate captures when the
+/// system is under load. ``TimeCritical`` can starve other threads of the process and should be
+/// used with care.
+#[pyclass]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum ThreadPriority {
+    Lowest,
+    BelowNormal,
+    #[default]
+    Normal,
+    AboveNormal,
+    Highest,
+    TimeCritical,
+}
+
+impl From<ThreadPriority> for THREAD_PRIORITY {
+    fn from(priority: ThreadPriority) -> Self {
+        match priority {
+            ThreadPriority::Lowest => THREAD_PRIORITY_LOWEST,
+            ThreadPriority::BelowNormal => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::AboveNormal => THREAD_PRIORITY_ABOVE_NORMAL,
+            ThreadPriority::Highest => THREAD_PRIORITY_HIGHEST,
+            ThreadPriority::TimeCritical => THREAD_PRIORITY_TIME_CRITICAL,
+        }
+    }
+}
+
+/// Policies for window targets that are resized while they are captured.
+///
+/// ``Recreate`` resizes the frames along with the window. The frame that reveals the new size is
+/// dropped while the frame buffers are recreated. ``Fixed`` keeps the frames at the size the
+/// capture started with, so recordings keep constant dimensions. Content of a larger window is
+/// clipped at the right and bottom edges, and a smaller window leaves the rest of the frame
+/// unspecified. ``Error`` stops the capture instead, like a closed target, and
+/// :meth:`.Capture.try_stop` raises the error.
+#[pyclass]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum ResizePolicy {
+    #[default]
+    Recreate,
+    Fixed,
+    Error,
+}
+
+/// CaptureStats
+/// Frame statistics of a capture since the last call of :meth:`.Capture.start`.
+///
+/// A frame counts as dropped if it was replaced by a newer frame before it was read.
+#[pyclass(get_all)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub struct CaptureStats {
+    /// :``int``: The number of frames stored by the capture thread.
+    pub produced: u64,
+    /// :``int``: The number of distinct frames that have been read.
+    pub consumed: u64,
+    /// :``int``: The number of frames that were overwritten before they were read.
+    pub dropped: u64,
+}
+
+#[pymethods]
+impl CaptureStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "CaptureStats(produced={}, consumed={}, dropped={})",
+            self.produced, self.consumed, self.dropped
+        )
+    }
 }
 
-impl Default for ColorFormat {
+/// Diagnostics
+/// Information about the capture support of the system, returned by :func:`.diagnostics`.
+#[pyclass(get_all)]
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Diagnostics {
+    /// :``bool``: True if the system supports graphics capture.
+    pub graphics_capture_supported: bool,
+    /// :``FeatureLevel | None``: The highest feature level of a device on the default adapter, or
+    /// None if no device could be created.
+    pub feature_level: Option<FeatureLevel>,
+    /// :``str | None``: The name of the default graphics adapter, or None if no device could be
+    /// created. The software rasterizer is reported if no hardware device is available.
+    pub adapter_name: Option<String>,
+    /// :``int | None``: The build number of Windows, or None if it is unknown.
+    pub os_build: Option<u32>,
+    /// :``bool``: True if capture sessions can hide the cursor.
+    pub cursor_toggle_supported: bool,
+    /// :``bool``: True if capture sessions can hide the yellow capture border.
+    pub border_toggle_supported: bool,
+}
+
+#[pymethods]
+impl Diagnostics {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostics(graphics_capture_supported={}, feature_level={:?}, adapter_name={:?}, \
+             os_build={:?}, cursor_toggle_supported={}, border_toggle_supported={})",
+            self.graphics_capture_supported,
+            self.feature_level,
+            self.adapter_name,
+            self.os_build,
+            self.cursor_toggle_supported,
+            self.border_toggle_supported
+        )
+    }
+}
+
+/// WindowsBuild
+/// The version of Windows and the capture features it provides, returned by
+/// :func:`.windows_build`.
+#[pyclass(get_all)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct WindowsBuild {
+    /// :``int``: The major version of Windows, 10 for Windows 10 and 11.
+    pub major: u32,
+    /// :``int``: The minor version of Windows.
+    pub minor: u32,
+    /// :``int``: The build number of Windows, e.g. 22631. Windows 11 starts at build 22000.
+    pub build: u32,
+    /// :``bool``: True if the system supports graphics capture.
+    pub graphics_capture_supported: bool,
+    /// :``bool``: True if capture sessions can hide the cursor.
+    pub cursor_toggle_supported: bool,
+    /// :``bool``: True if capture sessions can hide the yellow capture border.
+    pub border_toggle_supported: bool,
+    /// :``bool``: True if capture sessions support a minimum update interval.
+    pub min_update_interval_supported: bool,
+    /// :``bool``: True if capture sessions can include the secondary windows of window targets.
+    pub secondary_windows_supported: bool,
+}
+
+#[pymethods]
+impl WindowsBuild {
+    fn __repr__(&self) -> String {
+        format!(
+            "WindowsBuild(major={}, minor={}, build={}, graphics_capture_supported={}, \
+             cursor_toggle_supported={}, border_toggle_supported={}, \
+             min_update_interval_supported={}, secondary_windows_supported={})",
+            self.major,
+            self.minor,
+            self.build,
+            self.graphics_capture_supported,
+            self.cursor_toggle_supported,
+            self.border_toggle_supported,
+            self.min_update_interval_supported,
+            self.secondary_windows_supported
+        )
+    }
+}
+
+/// Double buffer that hands the latest frame from the capture thread to its readers.
+///
+/// The capture thread prepares the next frame as the back buffer and publishes it with an atomic
+/// pointer swap. Readers acquire the front buffer without a lock, so they never block the capture
+/// thread or each other while they copy a frame, and the replaced frame is released once its last
+/// reader is done. Threads that wait for frames hold the signal lock while they check their
+/// condition and wait on the condition variable of the capture, which is notified under the same
+/// lock after each swap.
+pub struct FrameSlot<T = Frame> {
+    front: ArcSwapOption<T>,
+    signal: Mutex<()>,
+}
+
+impl<T> Default for FrameSlot<T> {
     fn default() -> Self {
-        Self::Rgba8
+        Self {
+            front: ArcSwapOption::empty(),
+            signal: Mutex::new(()),
+        }
+    }
+}
+
+impl<T> FrameSlot<T> {
+    /// Acquire the front buffer, or `None` if no frame has been published.
+    pub fn load(&self) -> Option<Arc<T>> {
+        self.front.load_full()
+    }
+
+    /// Return true if a frame has been published.
+    pub fn has_frame(&self) -> bool {
+        self.front.load().is_some()
+    }
+
+    /// Publish `frame` as the new front buffer.
+    pub fn publish(&self, frame: T) {
+        self.front.store(Some(Arc::new(frame)));
+    }
+
+    /// Remove the front buffer, e.g. because the capture was stopped.
+    pub fn clear(&self) {
+        self.front.store(None);
+    }
+
+    /// Lock the signal that waiting threads and notifications synchronize on.
+    pub fn lock(&self) -> MutexGuard<'_, ()> {
+        self.signal.lock()
+    }
+}
+
+// Shared frame bookkeeping between the capture thread and the readers of a capture. The counters
+// are atomics so that they can be updated without holding the signal lock. The unread frame is
+// tracked by its id plus one, zero meaning that no frame is unread, so that readers of a replaced
+// frame do not mark its successor as read
+#[derive(Debug, Default)]
+pub struct FrameTracker {
+    unread: AtomicU64,
+    produced: AtomicU64,
+    consumed: AtomicU64,
+    dropped: AtomicU64,
+    resizes: AtomicU64,
+    monitor_changes: AtomicU64,
+    rebinds: AtomicU64,
+    queue_drops: AtomicU64,
+    // Content hash of the last read frame and whether it differed from the frame read before
+    content_hash: AtomicU64,
+    content_changed: AtomicBool,
+    // True if the last read frame was entirely black
+    blank: AtomicBool,
+    // Compute the checksum of each read frame. Kept across resets like a setting
+    checksums: AtomicBool,
+    // ID and checksum of the last read frame while checksums are enabled
+    last_checksum: Mutex<Option<(u64, u32)>>,
+    checksum_mismatches: AtomicU64,
+}
+
+impl FrameTracker {
+    /// Record that the frame with `id` has been stored. Overwriting an unread frame counts as a
+    /// drop.
+    pub fn frame_stored(&self, id: u64) {
+        self.produced.fetch_add(1, Ordering::SeqCst);
+        if self.unread.swap(id.wrapping_add(1), Ordering::SeqCst) != 0 {
+            self.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Record that the frame with `id` has been read and whether its content changed.
+    ///
+    /// The unread frame stays unread if it has been replaced while the frame was read.
+    pub fn frame_read(&self, id: u64, mapped_frame: &MappedFrame) {
+        let content_hash = mapped_frame.content_hash();
+        let previous_hash = self.content_hash.swap(content_hash, Ordering::SeqCst);
+        self.content_changed
+            .store(previous_hash != content_hash, Ordering::SeqCst);
+        self.blank.store(mapped_frame.is_blank(), Ordering::SeqCst);
+        if self.checksums.load(Ordering::SeqCst) {
+            let checksum = mapped_frame.checksum();
+            let mut last_checksum = self.last_checksum.lock();
+            // The same frame has to produce the same pixels every time it is copied to the CPU
+            if matches!(*last_checksum, Some((last_id, last)) if last_id == id && last != checksum)
+            {
+                self.checksum_mismatches.fetch_add(1, Ordering::SeqCst);
+            }
+            *last_checksum = Some((id, checksum));
+        }
+        if self
+            .unread
+            .compare_exchange(id.wrapping_add(1), 0, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            self.consumed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Record that the capture source changed its size.
+    pub fn frame_resized(&self) {
+        self.resizes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The number of size changes since the last reset.
+    pub fn resizes(&self) -> u64 {
+        self.resizes.load(Ordering::SeqCst)
+    }
+
+    /// Record that the captured window moved to another monitor.
+    pub fn monitor_changed(&self) {
+        self.monitor_changes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The number of monitor changes since the last reset.
+    pub fn monitor_changes(&self) -> u64 {
+        self.monitor_changes.load(Ordering::SeqCst)
+    }
+
+    /// Record that a closed sticky window target was replaced by a new window.
+    pub fn target_rebound(&self) {
+        self.rebinds.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The number of rebound window targets since the last reset.
+    pub fn rebinds(&self) -> u64 {
+        self.rebinds.load(Ordering::SeqCst)
+    }
+
+    /// Record that the oldest frame of a full frame queue was dropped for a new frame.
+    pub fn queue_dropped(&self) {
+        self.queue_drops.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// The number of frames dropped from the frame queue since the last reset.
+    pub fn queue_drops(&self) -> u64 {
+        self.queue_drops.load(Ordering::SeqCst)
+    }
+
+    /// True if the content of the last read frame differs from the frame read before it.
+    pub fn content_changed(&self) -> bool {
+        self.content_changed.load(Ordering::SeqCst)
+    }
+
+    /// True if the last read frame was entirely black.
+    pub fn blank(&self) -> bool {
+        self.blank.load(Ordering::SeqCst)
+    }
+
+    /// Enable or disable the checksums of read frames.
+    pub fn set_checksums(&self, enabled: bool) {
+        self.checksums.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.last_checksum.lock().take();
+        }
+    }
+
+    /// True if the checksums of read frames are computed.
+    pub fn checksums(&self) -> bool {
+        self.checksums.load(Ordering::SeqCst)
+    }
+
+    /// The checksum of the last read frame, or `None` if checksums are disabled or no frame has
+    /// been read since they were enabled.
+    pub fn last_checksum(&self) -> Option<u32> {
+        self.last_checksum.lock().map(|(_, checksum)| checksum)
+    }
+
+    /// The number of reads of a frame whose checksum differed from the previous read of the same
+    /// frame since the last reset.
+    pub fn checksum_mismatches(&self) -> u64 {
+        self.checksum_mismatches.load(Ordering::SeqCst)
+    }
+
+    /// True if the current frame has not been read yet.
+    pub fn has_unread(&self) -> bool {
+        self.unread.load(Ordering::SeqCst) != 0
+    }
+
+    /// Discard the unread state, e.g. because the current frame has been invalidated.
+    pub fn clear_unread(&self) {
+        self.unread.store(0, Ordering::SeqCst);
+    }
+
+    /// Reset the unread state and all counters.
+    pub fn reset(&self) {
+        self.clear_unread();
+        self.produced.store(0, Ordering::SeqCst);
+        self.consumed.store(0, Ordering::SeqCst);
+        self.dropped.store(0, Ordering::SeqCst);
+        self.resizes.store(0, Ordering::SeqCst);
+        self.monitor_changes.store(0, Ordering::SeqCst);
+        self.rebinds.store(0, Ordering::SeqCst);
+        self.queue_drops.store(0, Ordering::SeqCst);
+        self.content_hash.store(0, Ordering::SeqCst);
+        self.blank.store(false, Ordering::SeqCst);
+        self.content_changed.store(false, Ordering::SeqCst);
+        self.last_checksum.lock().take();
+        self.checksum_mismatches.store(0, Ordering::SeqCst);
+    }
+
+    pub fn stats(&self) -> CaptureStats {
+        CaptureStats {
+            produced: self.produced.load(Ordering::SeqCst),
+            consumed: self.consumed.load(Ordering::SeqCst),
+            dropped: self.dropped.load(Ordering::SeqCst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    #[test]
+    fn color_formats_agree() {
+        for color_format in [ColorFormat::Rgba8, ColorFormat::Rgba16Float] {
+            assert_eq!(
+                color_format.to_directx_pixel_format().0,
+                color_format.to_dxgi_format().0,
+                "{color_format:?} maps to different pixel formats"
+            );
+        }
+    }
+
+    #[test]
+    fn frame_slot_publish_load_clear() {
+        let slot = FrameSlot::<u64>::default();
+        assert!(!slot.has_frame());
+        assert_eq!(slot.load(), None);
+        slot.publish(1);
+        assert!(slot.has_frame());
+        let first = slot.load().expect("a frame was published");
+        assert_eq!(*first, 1);
+        // Readers keep the frame they acquired after it has been replaced
+        slot.publish(2);
+        assert_eq!(*first, 1);
+        assert_eq!(slot.load().as_deref(), Some(&2));
+        slot.clear();
+        assert!(!slot.has_frame());
+        assert_eq!(slot.load(), None);
+        assert_eq!(*first, 1);
+    }
+
+    // Frames only hold references to their textures and a few small vectors, so cloning a frame
+    // allocates like this payload. Cloning the latest frame under a mutex was the handoff before
+    // the frame slot
+    type Payload = Vec<u64>;
+
+    trait Handoff: Send + Sync {
+        fn publish(&self, frame: Payload);
+        fn read(&self) -> usize;
+    }
+
+    impl Handoff for FrameSlot<Payload> {
+        fn publish(&self, frame: Payload) {
+            FrameSlot::publish(self, frame);
+        }
+
+        fn read(&self) -> usize {
+            self.load().map_or(0, |frame| frame.len())
+        }
+    }
+
+    impl Handoff for Mutex<Option<Payload>> {
+        fn publish(&self, frame: Payload) {
+            *self.lock() = Some(frame);
+        }
+
+        fn read(&self) -> usize {
+            self.lock().clone().map_or(0, |frame| frame.len())
+        }
+    }
+
+    // Publish frames as fast as possible while `readers` threads read them for `duration`. Returns
+    // the number of published and read frames per second
+    fn measure_handoff(handoff: &dyn Handoff, readers: usize, duration: Duration) -> (f64, f64) {
+        let stop = AtomicBool::new(false);
+        let reads = AtomicUsize::new(0);
+        let mut published = 0;
+        thread::scope(|scope| {
+            for _ in 0..readers {
+                scope.spawn(|| {
+                    let mut count = 0;
+                    while !stop.load(Ordering::Relaxed) {
+                        black_box(handoff.read());
+                        count += 1;
+                    }
+                    reads.fetch_add(count, Ordering::Relaxed);
+                });
+            }
+            let start = Instant::now();
+            while start.elapsed() < duration {
+                handoff.publish(vec![0; 8]);
+                published += 1;
+            }
+            stop.store(true, Ordering::Relaxed);
+        });
+        let seconds = duration.as_secs_f64();
+        (
+            published as f64 / seconds,
+            reads.into_inner() as f64 / seconds,
+        )
+    }
+
+    // Baseline of benchmarks/frame_handoff.py without capturing and converting frames. Run with
+    // `cargo test --release frame_slot_handoff -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn frame_slot_handoff_benchmark() {
+        let duration = Duration::from_secs(1);
+        println!("readers  handoff      published/s     reads/s");
+        for readers in [1, 2, 4, 8] {
+            let slot = FrameSlot::<Payload>::default();
+            let mutex = Mutex::new(None::<Payload>);
+            let handoffs: [(&str, &dyn Handoff); 2] = [("slot", &slot), ("mutex", &mutex)];
+            for (name, handoff) in handoffs {
+                let (published, reads) = measure_handoff(handoff, readers, duration);
+                println!("{readers:>7}  {name:<7} {published:>16.0} {reads:>11.0}");
+            }
+        }
     }
 }
@@ -1,33 +1,98 @@
 // This code has been adapted from https://github.com/NiiightmareXD/windows-capture
 
-use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 
-use windows::core::Interface;
+use windows::core::{IUnknown, Interface};
 use windows::Graphics::DirectX::Direct3D11::IDirect3DDevice;
 use windows::Win32::Graphics::Direct3D::{
-    D3D_DRIVER_TYPE_HARDWARE, D3D_FEATURE_LEVEL, D3D_FEATURE_LEVEL_10_0, D3D_FEATURE_LEVEL_10_1,
-    D3D_FEATURE_LEVEL_11_0, D3D_FEATURE_LEVEL_11_1, D3D_FEATURE_LEVEL_9_1, D3D_FEATURE_LEVEL_9_2,
-    D3D_FEATURE_LEVEL_9_3,
+    D3D_DRIVER_TYPE, D3D_DRIVER_TYPE_HARDWARE, D3D_DRIVER_TYPE_UNKNOWN, D3D_DRIVER_TYPE_WARP,
+    D3D_FEATURE_LEVEL, D3D_FEATURE_LEVEL_10_0, D3D_FEATURE_LEVEL_10_1, D3D_FEATURE_LEVEL_11_0,
+    D3D_FEATURE_LEVEL_11_1, D3D_FEATURE_LEVEL_9_1, D3D_FEATURE_LEVEL_9_2, D3D_FEATURE_LEVEL_9_3,
 };
 use windows::Win32::Graphics::Direct3D11::{
     D3D11CreateDevice, ID3D11Device, ID3D11DeviceContext, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+    D3D11_CREATE_DEVICE_FLAG, D3D11_FORMAT_SUPPORT_RENDER_TARGET, D3D11_FORMAT_SUPPORT_TEXTURE2D,
     D3D11_SDK_VERSION,
 };
-use windows::Win32::Graphics::Dxgi::IDXGIDevice;
+use windows::Win32::Graphics::Dxgi::{
+    CreateDXGIFactory1, IDXGIAdapter, IDXGIDevice, IDXGIFactory1, DXGI_ADAPTER_DESC,
+};
 use windows::Win32::System::WinRT::Direct3D11::CreateDirect3D11DeviceFromDXGIDevice;
 
+use crate::capture_utils::ColorFormat;
+use crate::errors;
+
 #[derive(thiserror::Error, Eq, PartialEq, Clone, Debug)]
 pub enum DirectXError {
     #[error("Failed to create DirectX device with the recommended feature levels")]
     FeatureLevelNotSatisfied,
+    #[error("Graphics adapter with index {0} does not exist")]
+    AdapterNotFound(u32),
+    #[error("The DirectX device pointer is null")]
+    NullDevice,
+    #[error("The COM interface does not belong to a Direct3D 11 device")]
+    NotD3D11Device,
+    #[error("The DirectX device was not created with BGRA support")]
+    MissingBgraSupport,
+    #[error("The device does not support the {format:?} color format, only {supported:?}")]
+    UnsupportedColorFormat {
+        format: ColorFormat,
+        supported: Vec<ColorFormat>,
+    },
     #[error("Windows API Error: {0}")]
     WindowsError(#[from] windows::core::Error),
 }
 
 impl From<DirectXError> for PyErr {
     fn from(error: DirectXError) -> PyErr {
-        PyRuntimeError::new_err(error.to_string())
+        errors::DirectXError::new_err(error.to_string())
+    }
+}
+
+/// Direct3D feature levels that can be requested as minimum for the capture device.
+///
+/// Levels are ordered by capability, so a level compares greater than all levels it supersedes.
+#[pyclass]
+#[derive(Eq, PartialEq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub enum FeatureLevel {
+    Level9_1,
+    Level9_2,
+    Level9_3,
+    Level10_0,
+    Level10_1,
+    Level11_0,
+    #[default]
+    Level11_1,
+}
+
+impl From<FeatureLevel> for D3D_FEATURE_LEVEL {
+    fn from(level: FeatureLevel) -> Self {
+        match level {
+            FeatureLevel::Level9_1 => D3D_FEATURE_LEVEL_9_1,
+            FeatureLevel::Level9_2 => D3D_FEATURE_LEVEL_9_2,
+            FeatureLevel::Level9_3 => D3D_FEATURE_LEVEL_9_3,
+            FeatureLevel::Level10_0 => D3D_FEATURE_LEVEL_10_0,
+            FeatureLevel::Level10_1 => D3D_FEATURE_LEVEL_10_1,
+            FeatureLevel::Level11_0 => D3D_FEATURE_LEVEL_11_0,
+            FeatureLevel::Level11_1 => D3D_FEATURE_LEVEL_11_1,
+        }
+    }
+}
+
+impl FeatureLevel {
+    /// Convert a Direct3D feature level. Returns `None` for levels without a matching variant,
+    /// e.g. Direct3D 12 levels of devices supplied by the user.
+    pub fn from_d3d(level: D3D_FEATURE_LEVEL) -> Option<Self> {
+        match level {
+            D3D_FEATURE_LEVEL_9_1 => Some(FeatureLevel::Level9_1),
+            D3D_FEATURE_LEVEL_9_2 => Some(FeatureLevel::Level9_2),
+            D3D_FEATURE_LEVEL_9_3 => Some(FeatureLevel::Level9_3),
+            D3D_FEATURE_LEVEL_10_0 => Some(FeatureLevel::Level10_0),
+            D3D_FEATURE_LEVEL_10_1 => Some(FeatureLevel::Level10_1),
+            D3D_FEATURE_LEVEL_11_0 => Some(FeatureLevel::Level11_0),
+            D3D_FEATURE_LEVEL_11_1 => Some(FeatureLevel::Level11_1),
+            _ => None,
+        }
     }
 }
 
@@ -52,8 +117,127 @@ impl<T> SendDirectX<T> {
 #[allow(clippy::non_send_fields_in_send_ty)]
 unsafe impl<T> Send for SendDirectX<T> {}
 
+/// The flags DirectX devices are created with unless the user sets their own. The capture API
+/// requires BGRA support
+pub const DEFAULT_DEVICE_FLAGS: D3D11_CREATE_DEVICE_FLAG = D3D11_CREATE_DEVICE_BGRA_SUPPORT;
+
 /// Create `ID3D11Device` and `ID3D11DeviceContext`
-pub fn create_d3d_device() -> Result<(ID3D11Device, ID3D11DeviceContext), DirectXError> {
+///
+/// # Arguments
+///
+/// * `min_feature_level` - The lowest feature level that is accepted for the device
+/// * `software_fallback` - Retry with the WARP software rasterizer if no hardware device with the
+///   requested feature level can be created
+/// * `adapter_index` - The index of the graphics adapter to create the device on. If `None`, the
+///   default adapter is used
+/// * `flags` - The `D3D11_CREATE_DEVICE_FLAG` values the device is created with. Must include
+///   `D3D11_CREATE_DEVICE_BGRA_SUPPORT`, see `DEFAULT_DEVICE_FLAGS`
+///
+/// # Errors
+///
+/// `DirectXError::FeatureLevelNotSatisfied`: Software fallback is enabled and neither the hardware
+/// nor the WARP device could be created.
+/// `DirectXError::AdapterNotFound`: The requested adapter does not exist.
+/// `DirectXError::MissingBgraSupport`: The flags do not include BGRA support.
+pub fn create_d3d_device(
+    min_feature_level: FeatureLevel,
+    software_fallback: bool,
+    adapter_index: Option<u32>,
+    flags: D3D11_CREATE_DEVICE_FLAG,
+) -> Result<(ID3D11Device, ID3D11DeviceContext), DirectXError> {
+    if flags.0 & D3D11_CREATE_DEVICE_BGRA_SUPPORT.0 == 0 {
+        return Err(DirectXError::MissingBgraSupport);
+    }
+    let hardware_device = match adapter_index {
+        // Devices on an explicit adapter require the unknown driver type
+        Some(index) => create_d3d_device_for_driver(
+            Some(&find_adapter(index)?),
+            D3D_DRIVER_TYPE_UNKNOWN,
+            min_feature_level,
+            flags,
+        ),
+        None => {
+            create_d3d_device_for_driver(None, D3D_DRIVER_TYPE_HARDWARE, min_feature_level, flags)
+        }
+    };
+    match hardware_device {
+        Err(_) if software_fallback => {
+            create_d3d_device_for_driver(None, D3D_DRIVER_TYPE_WARP, min_feature_level, flags)
+                .map_err(|_| DirectXError::FeatureLevelNotSatisfied)
+        }
+        result => result,
+    }
+}
+
+/// Check that `device` can hold captured frames in the `color_format`
+///
+/// Frames are stored in 2D textures that are also rendered into when frames are composed or
+/// downscaled, so the format has to support both.
+///
+/// # Errors
+///
+/// `DirectXError::UnsupportedColorFormat`: The device does not support the format. The error lists
+/// the color formats the device supports instead.
+pub fn check_color_format(
+    device: &ID3D11Device,
+    color_format: ColorFormat,
+) -> Result<(), DirectXError> {
+    if supports_color_format(device, color_format) {
+        return Ok(());
+    }
+    Err(DirectXError::UnsupportedColorFormat {
+        format: color_format,
+        supported: supported_color_formats(device),
+    })
+}
+
+/// Return the color formats that `device` can hold captured frames in, in the order of the
+/// variants
+pub fn supported_color_formats(device: &ID3D11Device) -> Vec<ColorFormat> {
+    ColorFormat::ALL
+        .into_iter()
+        .filter(|format| supports_color_format(device, *format))
+        .collect()
+}
+
+fn supports_color_format(device: &ID3D11Device, color_format: ColorFormat) -> bool {
+    let required = (D3D11_FORMAT_SUPPORT_TEXTURE2D.0 | D3D11_FORMAT_SUPPORT_RENDER_TARGET.0) as u32;
+    // Devices fail the query for formats they do not support at all
+    unsafe { device.CheckFormatSupport(color_format.to_dxgi_format()) }
+        .is_ok_and(|support| support & required == required)
+}
+
+/// Return the description of the graphics adapter that `device` was created on
+pub fn adapter_name(device: &ID3D11Device) -> Result<String, DirectXError> {
+    let adapter = unsafe { device.cast::<IDXGIDevice>()?.GetAdapter()? };
+    let mut desc = DXGI_ADAPTER_DESC::default();
+    unsafe { adapter.GetDesc(&mut desc)? };
+    let description = desc.Description;
+    // The description is a null-terminated UTF-16 string in a fixed size buffer
+    let len = description
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(description.len());
+    Ok(String::from_utf16_lossy(&description[..len]))
+}
+
+/// Find the graphics adapter with the given index in the order of `IDXGIFactory1::EnumAdapters`
+///
+/// # Errors
+///
+/// `DirectXError::AdapterNotFound`: No adapter with this index exists.
+pub fn find_adapter(index: u32) -> Result<IDXGIAdapter, DirectXError> {
+    let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1()? };
+    unsafe { factory.EnumAdapters(index) }.map_err(|_| DirectXError::AdapterNotFound(index))
+}
+
+/// Create `ID3D11Device` and `ID3D11DeviceContext` for a specific adapter and driver type
+fn create_d3d_device_for_driver(
+    adapter: Option<&IDXGIAdapter>,
+    driver_type: D3D_DRIVER_TYPE,
+    min_feature_level: FeatureLevel,
+    flags: D3D11_CREATE_DEVICE_FLAG,
+) -> Result<(ID3D11Device, ID3D11DeviceContext), DirectXError> {
     // Array of Direct3D feature levels.
     // The feature levels are listed in descending order of capability.
     // The highest feature level supported by the system is at index 0.
@@ -73,10 +257,10 @@ pub fn create_d3d_device() -> Result<(ID3D11Device, ID3D11DeviceContext), Direct
     let mut d3d_device_context = None;
     unsafe {
         D3D11CreateDevice(
+            adapter,
+            driver_type,
             None,
-            D3D_DRIVER_TYPE_HARDWARE,
-            None,
-            D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+            flags,
             Some(&feature_flags),
             D3D11_SDK_VERSION,
             Some(&mut d3d_device),
@@ -85,13 +269,44 @@ pub fn create_d3d_device() -> Result<(ID3D11Device, ID3D11DeviceContext), Direct
         )?;
     };
 
-    if feature_level != D3D_FEATURE_LEVEL_11_1 {
+    // Feature level values increase with capability, so they can be compared directly
+    if feature_level.0 < D3D_FEATURE_LEVEL::from(min_feature_level).0 {
         return Err(DirectXError::FeatureLevelNotSatisfied);
     }
 
     Ok((d3d_device.unwrap(), d3d_device_context.unwrap()))
 }
 
+/// Take a reference to an `ID3D11Device` created by the caller
+///
+/// # Arguments
+///
+/// * `device` - The raw pointer to any COM interface of the device, e.g. `ID3D11Device` or the
+///   `IDXGIDevice` of a swapchain. The device is queried for `ID3D11Device`, which increments its
+///   reference count, so the caller may release its own reference afterwards
+///
+/// # Errors
+///
+/// `DirectXError::NullDevice`: The pointer is null.
+/// `DirectXError::NotD3D11Device`: The interface does not belong to a Direct3D 11 device.
+/// `DirectXError::MissingBgraSupport`: The device cannot be used for capturing because it was not
+/// created with `D3D11_CREATE_DEVICE_BGRA_SUPPORT`.
+///
+/// # Safety
+///
+/// A non-null `device` has to point to a valid COM interface.
+pub unsafe fn d3d_device_from_raw(device: usize) -> Result<ID3D11Device, DirectXError> {
+    let raw = device as *mut std::ffi::c_void;
+    let d3d_device: ID3D11Device = IUnknown::from_raw_borrowed(&raw)
+        .ok_or(DirectXError::NullDevice)?
+        .cast()
+        .map_err(|_| DirectXError::NotD3D11Device)?;
+    if d3d_device.GetCreationFlags() & D3D11_CREATE_DEVICE_BGRA_SUPPORT.0 == 0 {
+        return Err(DirectXError::MissingBgraSupport);
+    }
+    Ok(d3d_device)
+}
+
 /// Create `IDirect3DDevice` From `ID3D11Device`
 pub fn create_direct3d_device(d3d_device: &ID3D11Device) -> Result<IDirect3DDevice, DirectXError> {
     let dxgi_device: IDXGIDevice = d3d_device.cast()?;
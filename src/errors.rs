@@ -0,0 +1,84 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+// All exceptions derive from RuntimeError, which every error was raised as before the dedicated
+// exception classes existed, so existing `except RuntimeError` handlers keep working
+
+create_exception!(
+    pixel_forge,
+    PixelForgeError,
+    PyRuntimeError,
+    "Base class of all errors raised by pixel_forge."
+);
+create_exception!(
+    pixel_forge,
+    WindowNotFoundError,
+    PixelForgeError,
+    "The requested window does not exist."
+);
+create_exception!(
+    pixel_forge,
+    MonitorNotFoundError,
+    PixelForgeError,
+    "The requested monitor does not exist."
+);
+create_exception!(
+    pixel_forge,
+    DirectXError,
+    PixelForgeError,
+    "Creating or using the DirectX device failed."
+);
+create_exception!(
+    pixel_forge,
+    CaptureThreadError,
+    PixelForgeError,
+    "The capture thread failed to start or exited with an error."
+);
+create_exception!(
+    pixel_forge,
+    CaptureNotRunningError,
+    PixelForgeError,
+    "The capture is not running, e.g. because it was never started or has been stopped."
+);
+create_exception!(
+    pixel_forge,
+    FrameUnavailableError,
+    PixelForgeError,
+    "No frame is available yet or no frame arrived before the timeout."
+);
+create_exception!(
+    pixel_forge,
+    RecorderError,
+    PixelForgeError,
+    "Starting, feeding or finishing a recording failed."
+);
+create_exception!(
+    pixel_forge,
+    PipeError,
+    PixelForgeError,
+    "Starting, writing or stopping a raw frame pipe failed."
+);
+
+/// Register the exception classes in the Python module.
+pub fn register(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("PixelForgeError", py.get_type::<PixelForgeError>())?;
+    m.add("WindowNotFoundError", py.get_type::<WindowNotFoundError>())?;
+    m.add(
+        "MonitorNotFoundError",
+        py.get_type::<MonitorNotFoundError>(),
+    )?;
+    m.add("DirectXError", py.get_type::<DirectXError>())?;
+    m.add("CaptureThreadError", py.get_type::<CaptureThreadError>())?;
+    m.add(
+        "CaptureNotRunningError",
+        py.get_type::<CaptureNotRunningError>(),
+    )?;
+    m.add(
+        "FrameUnavailableError",
+        py.get_type::<FrameUnavailableError>(),
+    )?;
+    m.add("RecorderError", py.get_type::<RecorderError>())?;
+    m.add("PipeError", py.get_type::<PipeError>())?;
+    Ok(())
+}
@@ -1,20 +1,36 @@
 // This code has been adapted from https://github.com/NiiightmareXD/windows-capture
 
-use std::slice;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::os::raw::{c_char, c_int, c_void};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{ops, ptr, slice, thread};
 
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::PyBufferError;
+use pyo3::ffi;
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
 
+use numpy::ndarray::{self, s, ArrayView3, Axis};
+use numpy::{Element, IntoPyArray, PyArray2, PyArray3, ToPyArray};
+
+use windows::core::Interface;
+use windows::Win32::Foundation::E_OUTOFMEMORY;
 use windows::Win32::Graphics::Direct3D11::{
-    ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D, D3D11_CPU_ACCESS_READ,
-    D3D11_CPU_ACCESS_WRITE, D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ_WRITE, D3D11_TEXTURE2D_DESC,
+    ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D, D3D11_BIND_RENDER_TARGET,
+    D3D11_BIND_SHADER_RESOURCE, D3D11_BOX, D3D11_CPU_ACCESS_READ, D3D11_CPU_ACCESS_WRITE,
+    D3D11_MAPPED_SUBRESOURCE, D3D11_MAP_READ_WRITE, D3D11_RESOURCE_MISC_GENERATE_MIPS,
+    D3D11_RESOURCE_MISC_SHARED, D3D11_SUBRESOURCE_DATA, D3D11_TEXTURE2D_DESC, D3D11_USAGE_DEFAULT,
     D3D11_USAGE_STAGING,
 };
-use windows::Win32::Graphics::Dxgi::Common::{DXGI_FORMAT, DXGI_SAMPLE_DESC};
+use windows::Win32::Graphics::Dxgi::Common::DXGI_SAMPLE_DESC;
+use windows::Win32::Graphics::Dxgi::{IDXGIResource, DXGI_ERROR_WAS_STILL_DRAWING};
 
 use windows_result::Error as WindowsError;
 
 use crate::capture_utils::ColorFormat;
+use crate::errors;
 
 #[derive(thiserror::Error, Debug)]
 pub enum FrameError {
@@ -22,50 +38,168 @@ pub enum FrameError {
     FrameConversionFailed,
     #[error("Windows error during frame conversion")]
     FrameConversionWindowsError(#[from] WindowsError),
+    #[error("Output array has shape {actual:?}, but the frame has shape {expected:?}")]
+    OutputShapeMismatch {
+        expected: [usize; 3],
+        actual: Vec<usize>,
+    },
+    #[error("Frames in the {0:?} color format cannot be copied into uint8 arrays")]
+    OutputFormatMismatch(ColorFormat),
+    #[error("Frames in the {0:?} color format cannot be converted to PIL images")]
+    PilFormatUnsupported(ColorFormat),
+    #[error("Frames in the {0:?} color format cannot be converted to RGB565")]
+    Rgb565FormatUnsupported(ColorFormat),
+    #[error("The alpha channel of frames in the {0:?} color format cannot be extracted")]
+    AlphaFormatUnsupported(ColorFormat),
+    #[error("Frames in the {0:?} color format cannot be averaged")]
+    AverageFormatUnsupported(ColorFormat),
+    #[error("Frames in the {0:?} color format cannot be resized")]
+    ResizeFormatUnsupported(ColorFormat),
+    #[error("Borders of frames in the {0:?} color format cannot be detected")]
+    AutoCropFormatUnsupported(ColorFormat),
+    #[error("Target size {0:?} must be at least 1x1")]
+    EmptyTargetSize((u32, u32)),
+    #[error("Failed to map the frame for CPU access after {attempts} attempt(s): {source}")]
+    MapFailed { attempts: u32, source: WindowsError },
+    #[error("Unknown frame layout '{0}', expected 'hwc' or 'chw'")]
+    UnknownLayout(String),
 }
 
 impl From<FrameError> for PyErr {
     fn from(error: FrameError) -> PyErr {
-        PyRuntimeError::new_err(error.to_string())
+        errors::PixelForgeError::new_err(error.to_string())
     }
 }
 
+// A captured texture and its pixel offset inside the frame
+#[derive(Clone, Debug)]
+pub struct FrameTile {
+    pub texture: ID3D11Texture2D,
+    pub x: u32,
+    pub y: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct Frame {
-    // Texture properties
-    frame_texture: ID3D11Texture2D,
+    // Texture properties. Most frames consist of a single tile at the origin, but frames of the
+    // virtual desktop are composed of one tile per monitor
+    tiles: Vec<FrameTile>,
+    pub id: u64,
     pub height: u32,
     pub width: u32,
+    // Size of the captured content inside the texture. Textures can be larger than their content,
+    // e.g. while the frame pool has not caught up with a resized window yet
+    pub content_height: u32,
+    pub content_width: u32,
+    pub color_format: ColorFormat,
+    // System relative time in 100ns units at which the frame was rendered
+    pub time: i64,
+    // Regions of the full size frame that changed since the previous stored frame. None if the
+    // changes are unknown, so the whole frame has to be treated as changed
+    pub dirty_regions: Option<Vec<Region>>,
+    // Convert the premultiplied colors of the captured content to straight alpha when the frame
+    // is copied to the CPU. Only supported for `Rgba8` frames
+    pub unpremultiply_alpha: bool,
+    // Number of frames the compositor skipped since the previous stored frame, estimated from the
+    // gaps between the frame times. None if the delivery interval of the target is unknown
+    pub missed_frames: Option<u64>,
+    // Number of times the frame is halved on the GPU before it is copied to the CPU
+    downscale_level: u32,
     // Conversion devices
     d3d_device: ID3D11Device,
     context: ID3D11DeviceContext,
 }
 
 impl Frame {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        frame_texture: ID3D11Texture2D,
+        tiles: Vec<FrameTile>,
+        id: u64,
         height: u32,
         width: u32,
+        (content_width, content_height): (u32, u32),
+        color_format: ColorFormat,
+        time: i64,
+        downscale_level: u32,
         d3d_device: ID3D11Device,
         context: ID3D11DeviceContext,
     ) -> Self {
         Self {
-            frame_texture,
+            tiles,
+            id,
             height,
             width,
+            // Content outside of the texture was not captured
+            content_height: content_height.min(height),
+            content_width: content_width.min(width),
+            color_format,
+            time,
+            dirty_regions: None,
+            unpremultiply_alpha: false,
+            missed_frames: None,
+            // Frames cannot be halved beyond a single pixel
+            downscale_level: downscale_level.min(width.max(height).max(1).ilog2()),
             d3d_device,
             context,
         }
     }
 
-    pub fn materialize(&self) -> Result<&[u8], FrameError> {
+    /// Return the (width, height) of the frame after downscaling.
+    pub fn output_size(&self) -> (u32, u32) {
+        (self.downscaled(self.width), self.downscaled(self.height))
+    }
+
+    /// Scale a region of the full size frame to the downscaled output.
+    pub fn output_region(&self, region: Region) -> Region {
+        Region {
+            x: region.x >> self.downscale_level,
+            y: region.y >> self.downscale_level,
+            width: self.downscaled(region.width),
+            height: self.downscaled(region.height),
+        }
+    }
+
+    /// Scale a region of the full size frame to the smallest region of the downscaled output that
+    /// covers it.
+    pub fn output_bounds(&self, region: Region) -> Region {
+        let (width, height) = self.output_size();
+        // Round the start down and the end up so partially covered output pixels are included
+        let scale = |start: u32, len: u32, max: u32| {
+            let end = (start + len).div_ceil(1 << self.downscale_level).min(max);
+            let start = (start >> self.downscale_level).min(end);
+            (start, end - start)
+        };
+        let (x, width) = scale(region.x, region.width, width);
+        let (y, height) = scale(region.y, region.height, height);
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Return the (width, height) of the captured content after downscaling.
+    pub fn output_content_size(&self) -> (u32, u32) {
+        (
+            self.downscaled(self.content_width),
+            self.downscaled(self.content_height),
+        )
+    }
+
+    fn downscaled(&self, len: u32) -> u32 {
+        (len >> self.downscale_level).max(1)
+    }
+
+    pub fn materialize(&self) -> Result<MappedFrame, FrameError> {
+        let (width, height) = self.output_size();
         // Create a texture that CPU can read
         let texture_desc = D3D11_TEXTURE2D_DESC {
-            Width: self.width,
-            Height: self.height,
+            Width: width,
+            Height: height,
             MipLevels: 1,
             ArraySize: 1,
-            Format: DXGI_FORMAT(ColorFormat::default() as i32),
+            Format: self.color_format.to_dxgi_format(),
             SampleDesc: DXGI_SAMPLE_DESC {
                 Count: 1,
                 Quality: 0,
@@ -76,37 +210,1678 @@ impl Frame {
             MiscFlags: 0,
         };
 
-        let mut texture = None;
+        let texture = if self.downscale_level == 0 {
+            self.compose(&texture_desc)?
+        } else {
+            // Let the GPU generate the mip chain of the frame and only copy the mip level with the
+            // output size to the CPU readable texture
+            let mip_desc = D3D11_TEXTURE2D_DESC {
+                Width: self.width,
+                Height: self.height,
+                MipLevels: self.downscale_level + 1,
+                Usage: D3D11_USAGE_DEFAULT,
+                BindFlags: D3D11_BIND_RENDER_TARGET.0 as u32 | D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                CPUAccessFlags: 0,
+                MiscFlags: D3D11_RESOURCE_MISC_GENERATE_MIPS.0 as u32,
+                ..texture_desc
+            };
+            let mip_texture = self.compose(&mip_desc)?;
+            let mut view = None;
+            unsafe {
+                self.d3d_device
+                    .CreateShaderResourceView(&mip_texture, None, Some(&mut view))?;
+                self.context.GenerateMips(view.as_ref().unwrap());
+            };
+
+            let mut texture = None;
+            unsafe {
+                self.d3d_device
+                    .CreateTexture2D(&texture_desc, None, Some(&mut texture))?;
+            };
+            let texture = texture.unwrap();
+            unsafe {
+                self.context.CopySubresourceRegion(
+                    &texture,
+                    0,
+                    0,
+                    0,
+                    0,
+                    &mip_texture,
+                    self.downscale_level,
+                    None,
+                )
+            };
+            texture
+        };
+
+        // Map the texture to enable CPU access. At high frame rates the GPU occasionally has not
+        // finished the copy yet, so transient failures are retried before giving up
+        let mut mapped_resource = D3D11_MAPPED_SUBRESOURCE::default();
+        retry(
+            MAP_ATTEMPTS,
+            MAP_RETRY_INTERVAL,
+            || unsafe {
+                self.context.Map(
+                    &texture,
+                    0,
+                    D3D11_MAP_READ_WRITE,
+                    0,
+                    Some(&mut mapped_resource),
+                )
+            },
+            |error: &WindowsError| {
+                error.code() == DXGI_ERROR_WAS_STILL_DRAWING || error.code() == E_OUTOFMEMORY
+            },
+        )
+        .map_err(|(source, attempts)| FrameError::MapFailed { attempts, source })?;
+
+        let mut mapped_frame = MappedFrame {
+            texture,
+            context: self.context.clone(),
+            data: mapped_resource.pData.cast(),
+            row_pitch: mapped_resource.RowPitch,
+            height,
+            width,
+            color_format: self.color_format,
+        };
+        if self.unpremultiply_alpha && self.color_format == ColorFormat::Rgba8 {
+            mapped_frame.unpremultiply_alpha();
+        }
+        Ok(mapped_frame)
+    }
+
+    /// Copy the frame into a texture of its own.
+    ///
+    /// Captured frames reference the buffers of the frame pool, which are overwritten by later
+    /// frames. Copies stay valid as long as they are kept.
+    pub fn detached(&self) -> Result<Frame, FrameError> {
+        let texture = self.copy_to_texture(self.width, self.height)?;
+        let mut frame = self.clone();
+        frame.tiles = vec![FrameTile {
+            texture,
+            x: 0,
+            y: 0,
+        }];
+        Ok(frame)
+    }
+
+    /// Copy the frame into a new texture that other devices can open and return it together with
+    /// its legacy DXGI shared handle.
+    ///
+    /// The handle is not an NT handle and must not be closed. It stays valid as long as the
+    /// returned texture or a resource opened from the handle is alive.
+    pub(crate) fn shared_texture(&self) -> Result<(ID3D11Texture2D, isize), FrameError> {
+        let texture_desc = D3D11_TEXTURE2D_DESC {
+            Width: self.width,
+            Height: self.height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: self.color_format.to_dxgi_format(),
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_RENDER_TARGET.0 as u32 | D3D11_BIND_SHADER_RESOURCE.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: D3D11_RESOURCE_MISC_SHARED.0 as u32,
+        };
+        let texture = self.compose(&texture_desc)?;
+        // Submit the copy so that it is visible to other devices once they open the texture
+        unsafe { self.context.Flush() };
+        let handle = unsafe { texture.cast::<IDXGIResource>()?.GetSharedHandle()? };
+        Ok((texture, handle.0))
+    }
+
+    /// Return the device that owns the textures of the frame.
+    pub(crate) fn device(&self) -> &ID3D11Device {
+        &self.d3d_device
+    }
+
+    /// Copy the frame into a new GPU texture of `width` x `height` pixels.
+    ///
+    /// The size must not exceed the frame size. Smaller sizes crop the bottom and right edges.
+    pub(crate) fn copy_to_texture(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> Result<ID3D11Texture2D, FrameError> {
+        let texture_desc = D3D11_TEXTURE2D_DESC {
+            Width: self.width,
+            Height: self.height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: self.color_format.to_dxgi_format(),
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_RENDER_TARGET.0 as u32 | D3D11_BIND_SHADER_RESOURCE.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let texture = self.compose(&texture_desc)?;
+        if width == self.width && height == self.height {
+            return Ok(texture);
+        }
+
+        let mut cropped = None;
+        let cropped_desc = D3D11_TEXTURE2D_DESC {
+            Width: width,
+            Height: height,
+            ..texture_desc
+        };
         unsafe {
             self.d3d_device
-                .CreateTexture2D(&texture_desc, None, Some(&mut texture))?;
+                .CreateTexture2D(&cropped_desc, None, Some(&mut cropped))?;
+        };
+        let cropped = cropped.unwrap();
+        let source_box = D3D11_BOX {
+            left: 0,
+            top: 0,
+            front: 0,
+            right: width,
+            bottom: height,
+            back: 1,
         };
-        let texture = texture.unwrap();
-
-        // Copy the real texture to copy texture
         unsafe {
-            self.context.CopyResource(&texture, &self.frame_texture);
+            self.context
+                .CopySubresourceRegion(&cropped, 0, 0, 0, 0, &texture, 0, Some(&source_box))
         };
+        Ok(cropped)
+    }
 
-        // Map the texture to enable CPU access
-        let mut mapped_resource = D3D11_MAPPED_SUBRESOURCE::default();
-        unsafe {
-            self.context.Map(
-                &texture,
-                0,
-                D3D11_MAP_READ_WRITE,
-                0,
-                Some(&mut mapped_resource),
-            )?;
+    // Create a texture with the given description and copy the tiles into its first subresource
+    fn compose(&self, texture_desc: &D3D11_TEXTURE2D_DESC) -> Result<ID3D11Texture2D, FrameError> {
+        let mut texture = None;
+        match self.tiles.as_slice() {
+            [tile] if tile.x == 0 && tile.y == 0 => {
+                unsafe {
+                    self.d3d_device
+                        .CreateTexture2D(texture_desc, None, Some(&mut texture))?;
+                };
+                let texture = texture.as_ref().unwrap();
+                let mut tile_desc = D3D11_TEXTURE2D_DESC::default();
+                unsafe { tile.texture.GetDesc(&mut tile_desc) };
+                // Copy the real texture to copy texture. Frames of pre-sized frame pools only
+                // cover the content at the top left of their texture
+                if texture_desc.MipLevels == 1
+                    && (tile_desc.Width, tile_desc.Height) == (self.width, self.height)
+                {
+                    unsafe { self.context.CopyResource(texture, &tile.texture) };
+                } else {
+                    let source_box = D3D11_BOX {
+                        left: 0,
+                        top: 0,
+                        front: 0,
+                        right: self.width,
+                        bottom: self.height,
+                        back: 1,
+                    };
+                    unsafe {
+                        self.context.CopySubresourceRegion(
+                            texture,
+                            0,
+                            0,
+                            0,
+                            0,
+                            &tile.texture,
+                            0,
+                            Some(&source_box),
+                        )
+                    };
+                }
+            }
+            tiles => {
+                // Zero-initialize the texture so that areas without tiles are black. Each mip level
+                // needs its own initial data, but smaller levels can share the buffer of the first
+                let row_pitch = self.width * self.color_format.pixel_size();
+                let zeros = vec![0u8; (row_pitch * self.height) as usize];
+                let initial_data = vec![
+                    D3D11_SUBRESOURCE_DATA {
+                        pSysMem: zeros.as_ptr().cast(),
+                        SysMemPitch: row_pitch,
+                        SysMemSlicePitch: 0,
+                    };
+                    texture_desc.MipLevels as usize
+                ];
+                unsafe {
+                    self.d3d_device.CreateTexture2D(
+                        texture_desc,
+                        Some(initial_data.as_ptr()),
+                        Some(&mut texture),
+                    )?;
+                };
+                let texture = texture.as_ref().unwrap();
+                // Copy each tile to its position in the copy texture
+                for tile in tiles {
+                    unsafe {
+                        self.context.CopySubresourceRegion(
+                            texture,
+                            0,
+                            tile.x,
+                            tile.y,
+                            0,
+                            &tile.texture,
+                            0,
+                            None,
+                        )
+                    };
+                }
+            }
+        }
+        Ok(texture.unwrap())
+    }
+}
+
+/// Return the number of times a frame has to be halved to be downscaled by `scale`.
+///
+/// Frames can only be halved on the GPU, so the effective scale is the largest power of two that
+/// does not fall below `scale`.
+pub fn downscale_level(scale: f32) -> u32 {
+    // The float to int cast saturates, so tiny scales do not overflow
+    (1.0 / scale).log2().floor() as u32
+}
+
+/// Return the number of frames that were skipped between two deliveries `gap` apart.
+///
+/// Deliveries are expected every `interval`. Frame times jitter around the delivery interval, so
+/// gaps are rounded to the nearest number of intervals.
+pub fn missed_frames(gap: i64, interval: i64) -> u64 {
+    if interval <= 0 {
+        return 0;
+    }
+    let intervals = (gap.max(0) + interval / 2) / interval;
+    (intervals - 1).max(0).unsigned_abs()
+}
+
+/// Rectangle of a frame in pixels.
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    /// Return the overlap of two regions, or `None` if they do not overlap.
+    #[must_use]
+    pub fn intersection(&self, other: Region) -> Option<Region> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let end_x = (self.x + self.width).min(other.x + other.width);
+        let end_y = (self.y + self.height).min(other.y + other.height);
+        (end_x > x && end_y > y).then_some(Region {
+            x,
+            y,
+            width: end_x - x,
+            height: end_y - y,
+        })
+    }
+}
+
+/// Orientation of the frames that are returned to Python.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub struct Flip {
+    pub vertical: bool,
+    pub horizontal: bool,
+}
+
+/// Axis order of the arrays that frames are returned as.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+pub enum Layout {
+    /// Interleaved channels with dimensions [h w 4], the order of the frame memory.
+    #[default]
+    Hwc,
+    /// Planar channels with dimensions [4 h w], the order expected by many ML frameworks.
+    Chw,
+}
+
+impl FromStr for Layout {
+    type Err = FrameError;
+
+    fn from_str(layout: &str) -> Result<Self, Self::Err> {
+        match layout {
+            "hwc" => Ok(Layout::Hwc),
+            "chw" => Ok(Layout::Chw),
+            _ => Err(FrameError::UnknownLayout(layout.to_string())),
+        }
+    }
+}
+
+// Reverse the rows and/or columns of a frame with dimensions [h w 4]. Only the strides of the view
+// change, the data is reordered when the view is copied into a NumPy array
+fn flip_view<T>(mut view: ArrayView3<T>, flip: Flip) -> ArrayView3<T> {
+    if flip.vertical {
+        view.invert_axis(Axis(0));
+    }
+    if flip.horizontal {
+        view.invert_axis(Axis(1));
+    }
+    view
+}
+
+// Reorder the axes of a frame with dimensions [h w 4] to `layout`. Like flipping, this only changes
+// the strides, the copy into a NumPy array transposes the data
+fn layout_view<T>(view: ArrayView3<T>, layout: Layout) -> ArrayView3<T> {
+    match layout {
+        Layout::Hwc => view,
+        Layout::Chw => view.permuted_axes([2, 0, 1]),
+    }
+}
+
+// Copy a region of padded frame data into a NumPy array with the dimensions of `layout`
+fn crop_to_pyarray<'py, T: Element>(
+    py: Python<'py>,
+    data: &[T],
+    height: usize,
+    region: [ops::Range<usize>; 2],
+    flip: Flip,
+    layout: Layout,
+) -> &'py PyArray3<T> {
+    layout_view(flip_view(crop_view(data, height, region), flip), layout).to_pyarray(py)
+}
+
+// View a region of padded frame data with dimensions [h w 4]
+fn crop_view<T>(data: &[T], height: usize, region: [ops::Range<usize>; 2]) -> ArrayView3<'_, T> {
+    let img_array = ndarray::ArrayView1::from(data);
+    // For some reason, only the height of the frame is correct and the texture includes a white
+    // border. We calculate the width according to the number of available elements and later
+    // crop the frame back to the intended size
+    let dims: [usize; 3] = [height, data.len() / height / 4, 4];
+    let img_array = img_array
+        .into_shape(dims)
+        .expect("Failed to reshape frame into the correct dimensions");
+    // Crop image into the correct dimensions and discard any borders
+    let [rows, columns] = region;
+    img_array.slice_move(s![rows, columns, ..])
+}
+
+// Move tightly packed frame data into a NumPy array with dimensions [h w 4]
+fn packed_to_pyarray<T: Element>(
+    py: Python<'_>,
+    data: Vec<T>,
+    height: usize,
+    width: usize,
+    flip: Flip,
+) -> &PyArray3<T> {
+    let img_array = ndarray::Array3::from_shape_vec([height, width, 4], data)
+        .expect("Packed frame data does not match the frame dimensions");
+    if flip == Flip::default() {
+        return img_array.into_pyarray(py);
+    }
+    // Flipped frames are copied in their new order to keep the array C-contiguous
+    flip_view(img_array.view(), flip).to_pyarray(py)
+}
+
+// Place content of `source` size (width, height) into a `target` size frame. Returns the region of
+// the target that the content covers. Without `preserve_aspect`, the content is stretched over the
+// whole target. Otherwise it is scaled to fit and centered, leaving bars on two opposite sides
+fn fit_region(source: (u32, u32), target: (u32, u32), preserve_aspect: bool) -> Region {
+    let (width, height) = if !preserve_aspect {
+        target
+    } else {
+        let (source_width, source_height) = (u64::from(source.0), u64::from(source.1));
+        let (target_width, target_height) = (u64::from(target.0), u64::from(target.1));
+        // Compare the aspect ratios without division. The scaled side is rounded to the nearest
+        // pixel and cannot exceed the target, since it is at most the target side
+        if source_width * target_height <= source_height * target_width {
+            let width = (source_width * target_height + source_height / 2) / source_height;
+            (width.clamp(1, target_width) as u32, target.1)
+        } else {
+            let height = (source_height * target_width + source_width / 2) / source_width;
+            (target.0, height.clamp(1, target_height) as u32)
+        }
+    };
+    Region {
+        x: (target.0 - width) / 2,
+        y: (target.1 - height) / 2,
+        width,
+        height,
+    }
+}
+
+// Resize a frame with dimensions [h w 4] to `size` with nearest neighbor sampling. The frame is
+// scaled into `content`, and pixels outside of it are set to `pad_color`
+fn resize_nearest(
+    frame: ArrayView3<u8>,
+    size: (u32, u32),
+    content: Region,
+    pad_color: [u8; 4],
+) -> ndarray::Array3<u8> {
+    let (source_height, source_width) = (frame.shape()[0], frame.shape()[1]);
+    let (width, height) = (size.0 as usize, size.1 as usize);
+    let (x0, y0) = (content.x as usize, content.y as usize);
+    let (content_width, content_height) = (content.width as usize, content.height as usize);
+    // Sample the source pixel below the center of each target pixel
+    let source_x: Vec<usize> = (0..content_width)
+        .map(|x| (2 * x + 1) * source_width / (2 * content_width))
+        .collect();
+    let mut resized = ndarray::Array3::from_shape_fn([height, width, 4], |(_, _, c)| pad_color[c]);
+    for y in 0..content_height {
+        let source_row =
+            frame.index_axis(Axis(0), (2 * y + 1) * source_height / (2 * content_height));
+        let mut row = resized.index_axis_mut(Axis(0), y0 + y);
+        for (x, &sx) in source_x.iter().enumerate() {
+            row.index_axis_mut(Axis(0), x0 + x)
+                .assign(&source_row.index_axis(Axis(0), sx));
+        }
+    }
+    resized
+}
+
+/// Map a point `offset` pixels from the top left corner of an area of `source` size (width,
+/// height) to the pixel of a `target` size image of the same area, e.g. from logical to physical
+/// pixels. Returns `None` if the point lies outside of the area.
+pub fn map_point(offset: (i32, i32), source: (u32, u32), target: (u32, u32)) -> Option<(u32, u32)> {
+    let map = |offset: i32, source: u32, target: u32| {
+        let offset = u32::try_from(offset)
+            .ok()
+            .filter(|offset| *offset < source)?;
+        // Source pixels cover whole target pixels, so rounding down picks the covering pixel
+        u32::try_from(u64::from(offset) * u64::from(target) / u64::from(source)).ok()
+    };
+    Some((
+        map(offset.0, source.0, target.0)?,
+        map(offset.1, source.1, target.1)?,
+    ))
+}
+
+// Number of times mapping a frame is attempted and the pause between two attempts
+const MAP_ATTEMPTS: u32 = 5;
+const MAP_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+// Call `operation` up to `attempts` times while it fails with errors that `transient` accepts,
+// sleeping `interval` in between. Returns the last error and the number of attempts on failure
+fn retry<T, E>(
+    attempts: u32,
+    interval: Duration,
+    mut operation: impl FnMut() -> Result<T, E>,
+    transient: impl Fn(&E) -> bool,
+) -> Result<T, (E, u32)> {
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < attempts && transient(&error) => {
+                attempt += 1;
+                thread::sleep(interval);
+            }
+            Err(error) => return Err((error, attempt)),
+        }
+    }
+}
+
+// Pack an 8 bit RGB color into 16 bits with 5 bits of red and blue and 6 bits of green
+fn rgb565(red: u8, green: u8, blue: u8) -> u16 {
+    (u16::from(red >> 3) << 11) | (u16::from(green >> 2) << 5) | u16::from(blue >> 3)
+}
+
+// Reinterpret the bytes of 16 bit float channels as their raw bit patterns. NumPy views them as
+// float16 afterwards, so we do not need a half precision float type on the Rust side
+fn half_floats(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+        .collect()
+}
+
+// Evenly spaced positions along a frame dimension for content sampling
+fn sample_positions(len: u32) -> impl Iterator<Item = usize> {
+    const SAMPLES: u32 = 64;
+    let step = (len / SAMPLES).max(1) as usize;
+    (0..len as usize).step_by(step)
+}
+
+// Copy the first `row_len` bytes of each row into a contiguous buffer, dropping the padding at the
+// end of rows that are `row_pitch` bytes apart
+fn pack_rows(data: &[u8], row_pitch: usize, row_len: usize, height: usize) -> Vec<u8> {
+    let mut packed = Vec::with_capacity(row_len * height);
+    for row in data.chunks(row_pitch).take(height) {
+        packed.extend_from_slice(&row[..row_len]);
+    }
+    packed
+}
+
+// Divide the colors of the first `width` RGBA pixels of each row by their alpha, rounded and
+// clamped to 255. Fully transparent pixels have no color and become transparent black
+fn unpremultiply_rows(data: &mut [u8], row_pitch: usize, width: usize, height: usize) {
+    for row in data.chunks_mut(row_pitch).take(height) {
+        for pixel in row[..width * 4].chunks_exact_mut(4) {
+            let alpha = u32::from(pixel[3]);
+            if alpha == 255 {
+                continue;
+            }
+            for channel in &mut pixel[..3] {
+                *channel = match alpha {
+                    0 => 0,
+                    _ => ((u32::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8,
+                };
+            }
+        }
+    }
+}
+
+// Bounds of the RGBA pixels inside the dark border of a `width` x `height` image, or `None` if the
+// whole image is dark. Pixels are dark if none of their colors exceeds `tolerance`
+fn content_bounds(
+    data: &[u8],
+    row_pitch: usize,
+    width: usize,
+    height: usize,
+    tolerance: u8,
+) -> Option<Region> {
+    let is_dark = |x: usize, y: usize| {
+        data[y * row_pitch + x * 4..][..3]
+            .iter()
+            .all(|&color| color <= tolerance)
+    };
+    let is_dark_row = |y: usize| (0..width).all(|x| is_dark(x, y));
+    let top = (0..height).find(|&y| !is_dark_row(y))?;
+    let bottom = (top..height).rev().find(|&y| !is_dark_row(y))?;
+    // Columns are only scanned between the top and bottom border
+    let is_dark_column = |x: usize| (top..=bottom).all(|y| is_dark(x, y));
+    let left = (0..width).find(|&x| !is_dark_column(x))?;
+    let right = (left..width).rev().find(|&x| !is_dark_column(x))?;
+    Some(Region {
+        x: left as u32,
+        y: top as u32,
+        width: (right - left + 1) as u32,
+        height: (bottom - top + 1) as u32,
+    })
+}
+
+// FNV-1a hash of the first `row_len` bytes of each row, so the row padding does not affect the hash
+fn hash_rows(data: &[u8], row_pitch: usize, row_len: usize, height: usize) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    data.chunks(row_pitch)
+        .take(height)
+        .flat_map(|row| &row[..row_len])
+        .fold(FNV_OFFSET_BASIS, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+// Lookup table of the reflected CRC-32 polynomial used by zlib, PNG and Ethernet
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+// CRC-32 of the first `row_len` bytes of each row, so the row padding does not affect the checksum
+fn crc32_rows(data: &[u8], row_pitch: usize, row_len: usize, height: usize) -> u32 {
+    !data
+        .chunks(row_pitch)
+        .take(height)
+        .flat_map(|row| &row[..row_len])
+        .fold(!0, |crc, &byte| {
+            CRC32_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+        })
+}
+
+// Copy every `step`-th pixel of every `step`-th row into a contiguous buffer. Rows are
+// `row_pitch` bytes apart and hold `width` pixels of `pixel_size` bytes
+fn sample_rows(
+    data: &[u8],
+    row_pitch: usize,
+    pixel_size: usize,
+    [width, height]: [usize; 2],
+    step: usize,
+) -> Vec<u8> {
+    data.chunks(row_pitch)
+        .take(height)
+        .step_by(step)
+        .flat_map(|row| {
+            row[..width * pixel_size]
+                .chunks_exact(pixel_size)
+                .step_by(step)
+        })
+        .flatten()
+        .copied()
+        .collect()
+}
+
+// Fraction of the pixels of `pixel_size` bytes that differ between two buffers of equal length
+fn changed_fraction(a: &[u8], b: &[u8], pixel_size: usize) -> f64 {
+    let pixels = a.len() / pixel_size;
+    if pixels == 0 {
+        return 0.0;
+    }
+    let changed = a
+        .chunks_exact(pixel_size)
+        .zip(b.chunks_exact(pixel_size))
+        .filter(|(a, b)| a != b)
+        .count();
+    changed as f64 / pixels as f64
+}
+
+/// Pixels of a frame sampled on a regular grid, kept to compare later frames against.
+pub struct FrameSample {
+    width: u32,
+    height: u32,
+    color_format: ColorFormat,
+    data: Vec<u8>,
+}
+
+impl FrameSample {
+    /// Return the fraction of sampled pixels that differ from `other`.
+    ///
+    /// Samples with different dimensions or color formats count as entirely changed.
+    pub fn changed_fraction(&self, other: &FrameSample) -> f64 {
+        if (self.width, self.height, self.color_format)
+            != (other.width, other.height, other.color_format)
+        {
+            return 1.0;
+        }
+        let pixel_size = self.color_format.pixel_size() as usize;
+        changed_fraction(&self.data, &other.data, pixel_size)
+    }
+}
+
+/// Running per-channel sum of `Rgba8` frames of equal size.
+///
+/// Frames are added one at a time, so averaging does not need to keep all frames in memory.
+pub struct FrameAccumulator {
+    sum: ndarray::Array3<u32>,
+    count: u32,
+}
+
+impl FrameAccumulator {
+    pub fn new(height: usize, width: usize) -> FrameAccumulator {
+        FrameAccumulator {
+            sum: ndarray::Array3::zeros([height, width, 4]),
+            count: 0,
+        }
+    }
+
+    /// Add a frame with dimensions [h w 4] to the sum. Frames of a different size are ignored.
+    ///
+    /// Returns true if the frame was added.
+    pub fn add(&mut self, frame: ArrayView3<u8>) -> bool {
+        if frame.shape() != self.sum.shape() {
+            return false;
+        }
+        ndarray::Zip::from(&mut self.sum)
+            .and(&frame)
+            .for_each(|sum, &value| *sum += u32::from(value));
+        self.count += 1;
+        true
+    }
+
+    /// Return the rounded mean of the added frames with dimensions [h w 4].
+    pub fn average(&self) -> ndarray::Array3<u8> {
+        let count = self.count.max(1);
+        // The mean of u8 values fits into u8, so the cast cannot truncate
+        self.sum.mapv(|sum| ((sum + count / 2) / count) as u8)
+    }
+
+    /// Return the rounded mean as a NumPy array, flipped according to `flip`.
+    pub fn average_to_pyarray<'py>(&self, py: Python<'py>, flip: Flip) -> &'py PyArray3<u8> {
+        let [height, width, _] = self.sum.dim().into();
+        packed_to_pyarray(py, self.average().into_raw_vec(), height, width, flip)
+    }
+}
+
+/// A CPU-readable copy of a frame that stays mapped until it is dropped.
+///
+/// The mapped memory belongs to a staging texture that is created for each materialization, so it
+/// is not affected by new frames arriving. Rows are `row_pitch` bytes apart, which can be larger
+/// than `width * 4` because of padding.
+pub struct MappedFrame {
+    texture: ID3D11Texture2D,
+    context: ID3D11DeviceContext,
+    data: *const u8,
+    pub row_pitch: u32,
+    pub height: u32,
+    pub width: u32,
+    pub color_format: ColorFormat,
+}
+
+impl MappedFrame {
+    /// Return the mapped frame data including the row padding.
+    pub fn data(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.data, (self.height * self.row_pitch) as usize) }
+    }
+
+    /// Copy the frame into a NumPy array with dimensions [h w 4], flipped according to `flip`.
+    ///
+    /// The array has dtype uint8 for `Rgba8` frames and float16 for `Rgba16Float` frames.
+    pub fn to_pyarray<'py>(&self, py: Python<'py>, flip: Flip) -> PyResult<&'py PyAny> {
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
         };
+        self.region_to_pyarray(py, region, flip, Layout::Hwc)
+    }
+
+    /// Copy a region of the frame into a NumPy array with the dimensions of `layout`, flipped
+    /// according to `flip`. The region must lie inside the frame.
+    pub fn region_to_pyarray<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+        layout: Layout,
+    ) -> PyResult<&'py PyAny> {
+        let height: usize = self.height.try_into()?;
+        let (x, y): (usize, usize) = (region.x.try_into()?, region.y.try_into()?);
+        let rows = y..y + usize::try_from(region.height)?;
+        let columns = x..x + usize::try_from(region.width)?;
+        match self.color_format {
+            ColorFormat::Rgba8 => Ok(crop_to_pyarray(
+                py,
+                self.data(),
+                height,
+                [rows, columns],
+                flip,
+                layout,
+            )),
+            ColorFormat::Rgba16Float => {
+                let data = half_floats(self.data());
+                crop_to_pyarray(py, &data, height, [rows, columns], flip, layout)
+                    .call_method1("view", ("float16",))
+            }
+        }
+    }
+
+    /// View a region of the frame bytes with dimensions [h w 4], flipped according to `flip`. The
+    /// region must lie inside the frame. Only `Rgba8` frames have one byte per channel.
+    pub fn region_view(&self, region: Region, flip: Flip) -> PyResult<ArrayView3<'_, u8>> {
+        let height: usize = self.height.try_into()?;
+        let (x, y): (usize, usize) = (region.x.try_into()?, region.y.try_into()?);
+        let rows = y..y + usize::try_from(region.height)?;
+        let columns = x..x + usize::try_from(region.width)?;
+        let view = flip_view(crop_view(self.data(), height, [rows, columns]), flip);
+        Ok(view)
+    }
+
+    /// Copy a region of the frame into the existing `uint8` NumPy array `out`, flipped according
+    /// to `flip`. The region must lie inside the frame.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::OutputFormatMismatch`: The frame is not in the `Rgba8` format.
+    /// `FrameError::OutputShapeMismatch`: The shape of `out` is not [h w 4] of the region.
+    pub fn copy_region_into(&self, region: Region, flip: Flip, out: &PyArray3<u8>) -> PyResult<()> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::OutputFormatMismatch(self.color_format).into());
+        }
+        let view = self.region_view(region, flip)?;
+        let expected = [view.shape()[0], view.shape()[1], 4];
+        if out.shape() != expected {
+            let actual = out.shape().to_vec();
+            return Err(FrameError::OutputShapeMismatch { expected, actual }.into());
+        }
+        out.try_readwrite()?.as_array_mut().assign(&view);
+        Ok(())
+    }
+
+    /// Copy a region of the frame into a `PIL.Image`, flipped according to `flip`. The region must
+    /// lie inside the frame.
+    ///
+    /// The image has mode `RGBA`, or `RGB` if `alpha` is false.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::PilFormatUnsupported`: The frame is not in the `Rgba8` format. Pillow has no
+    /// mode for 16 bit float channels.
+    pub fn region_to_pil<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+        alpha: bool,
+    ) -> PyResult<&'py PyAny> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::PilFormatUnsupported(self.color_format).into());
+        }
+        let view = self.region_view(region, flip)?;
+        // Views iterate in logical order, so the pixels are packed in the flipped row order
+        let data: Vec<u8> = view.iter().copied().collect();
+        let bytes = PyBytes::new(py, &data);
+        // The RGBX raw mode drops the alpha channel while decoding the 4 byte pixels
+        let (mode, raw_mode) = if alpha {
+            ("RGBA", "RGBA")
+        } else {
+            ("RGB", "RGBX")
+        };
+        let size = (region.width, region.height);
+        py.import("PIL.Image")?
+            .call_method1("frombuffer", (mode, size, bytes, "raw", raw_mode, 0, 1))
+    }
+
+    /// Resize a region of the frame to `size` (width, height) and copy it into a NumPy array with
+    /// dimensions [h w 4], flipped according to `flip`. The region must lie inside the frame.
+    ///
+    /// If `preserve_aspect` is set, the region is scaled to fit and the remaining border is padded
+    /// with `pad_color` instead of stretching the content.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::ResizeFormatUnsupported`: The frame is not in the `Rgba8` format.
+    /// `FrameError::EmptyTargetSize`: The target size is zero in any dimension.
+    pub fn region_to_sized_pyarray<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+        size: (u32, u32),
+        preserve_aspect: bool,
+        pad_color: [u8; 4],
+    ) -> PyResult<&'py PyArray3<u8>> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::ResizeFormatUnsupported(self.color_format).into());
+        }
+        if size.0 == 0 || size.1 == 0 {
+            return Err(FrameError::EmptyTargetSize(size).into());
+        }
+        let view = self.region_view(region, flip)?;
+        let content = fit_region((region.width, region.height), size, preserve_aspect);
+        Ok(resize_nearest(view, size, content, pad_color).into_pyarray(py))
+    }
+
+    /// Convert a region of the frame into a `uint16` NumPy array with dimensions [h w] of RGB565
+    /// pixels, flipped according to `flip`. The region must lie inside the frame.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::Rgb565FormatUnsupported`: The frame is not in the `Rgba8` format.
+    pub fn region_to_rgb565<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+    ) -> PyResult<&'py PyArray2<u16>> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::Rgb565FormatUnsupported(self.color_format).into());
+        }
+        let view = self.region_view(region, flip)?;
+        let packed = view.map_axis(Axis(2), |pixel| rgb565(pixel[0], pixel[1], pixel[2]));
+        Ok(packed.into_pyarray(py))
+    }
+
+    /// Extract the alpha channel of a region of the frame into a `uint8` NumPy array with
+    /// dimensions [h w], flipped according to `flip`. The region must lie inside the frame.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::AlphaFormatUnsupported`: The frame is not in the `Rgba8` format.
+    pub fn region_to_alpha<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+    ) -> PyResult<&'py PyArray2<u8>> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::AlphaFormatUnsupported(self.color_format).into());
+        }
+        let view = self.region_view(region, flip)?;
+        Ok(view.map_axis(Axis(2), |pixel| pixel[3]).into_pyarray(py))
+    }
+
+    /// Add a region of the frame to an accumulator. The region must lie inside the frame.
+    ///
+    /// Returns false if the region size does not match the accumulator, in which case the frame
+    /// is left out.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::AverageFormatUnsupported`: The frame is not in the `Rgba8` format.
+    pub fn accumulate_region(
+        &self,
+        accumulator: &mut FrameAccumulator,
+        region: Region,
+    ) -> PyResult<bool> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::AverageFormatUnsupported(self.color_format).into());
+        }
+        Ok(accumulator.add(self.region_view(region, Flip::default())?))
+    }
+
+    /// Copy the frame row by row into a tightly packed buffer without the row padding.
+    pub fn packed_data(&self) -> Vec<u8> {
+        self.packed_region(Region {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        })
+    }
+
+    /// Copy a region of the frame row by row into a tightly packed buffer without the row
+    /// padding. The region must lie inside the frame.
+    pub fn packed_region(&self, region: Region) -> Vec<u8> {
+        let pixel_size = self.color_format.pixel_size() as usize;
+        let row_pitch = self.row_pitch as usize;
+        let offset = region.y as usize * row_pitch + region.x as usize * pixel_size;
+        pack_rows(
+            &self.data()[offset..],
+            row_pitch,
+            region.width as usize * pixel_size,
+            region.height as usize,
+        )
+    }
+
+    /// Copy a region of the frame row by row into a tightly packed NumPy array with dimensions
+    /// [h w 4], flipped according to `flip`. The region must lie inside the frame.
+    pub fn region_to_contiguous_pyarray<'py>(
+        &self,
+        py: Python<'py>,
+        region: Region,
+        flip: Flip,
+    ) -> PyResult<&'py PyAny> {
+        let height: usize = region.height.try_into()?;
+        let width: usize = region.width.try_into()?;
+        let data = self.packed_region(region);
+        match self.color_format {
+            ColorFormat::Rgba8 => Ok(packed_to_pyarray(py, data, height, width, flip)),
+            ColorFormat::Rgba16Float => {
+                packed_to_pyarray(py, half_floats(&data), height, width, flip)
+                    .call_method1("view", ("float16",))
+            }
+        }
+    }
+
+    /// Hash a sparse grid of pixels to cheaply detect whether the frame content changed.
+    ///
+    /// Changes between the sampled pixels go unnoticed, so equal hashes only indicate that the
+    /// content is likely unchanged.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let pixel_size = self.color_format.pixel_size() as usize;
+        let data = self.data();
+        for y in sample_positions(self.height) {
+            let row = &data[y * self.row_pitch as usize..];
+            for x in sample_positions(self.width) {
+                row[x * pixel_size..(x + 1) * pixel_size].hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Hash all pixels of the frame with FNV-1a.
+    ///
+    /// Unlike `content_hash`, every pixel contributes to the hash, and the hash is stable across
+    /// processes so it can be used as a cache key.
+    pub fn pixel_hash(&self) -> u64 {
+        let row_len = (self.width * self.color_format.pixel_size()) as usize;
+        hash_rows(
+            self.data(),
+            self.row_pitch as usize,
+            row_len,
+            self.height as usize,
+        )
+    }
+
+    /// Compute the CRC-32 of all pixels of the frame, e.g. to check that the same frame is copied
+    /// to the CPU without corruption. The checksum matches `zlib.crc32` of the packed pixels.
+    pub fn checksum(&self) -> u32 {
+        let row_len = (self.width * self.color_format.pixel_size()) as usize;
+        crc32_rows(
+            self.data(),
+            self.row_pitch as usize,
+            row_len,
+            self.height as usize,
+        )
+    }
+
+    /// Copy every `step`-th pixel of every `step`-th row for comparisons with other frames.
+    pub fn sample(&self, step: u32) -> FrameSample {
+        let step = step.max(1);
+        let data = sample_rows(
+            self.data(),
+            self.row_pitch as usize,
+            self.color_format.pixel_size() as usize,
+            [self.width as usize, self.height as usize],
+            step as usize,
+        );
+        FrameSample {
+            width: self.width.div_ceil(step),
+            height: self.height.div_ceil(step),
+            color_format: self.color_format,
+            data,
+        }
+    }
 
-        // Get the mapped resource data slice
-        let frame_data: &[u8] = unsafe {
+    /// Return true if the color channels of a sparse grid of pixels are all zero.
+    ///
+    /// Protected content is captured as black frames, so a blank frame hints at protected content.
+    pub fn is_blank(&self) -> bool {
+        let pixel_size = self.color_format.pixel_size() as usize;
+        // The alpha channel is the last of the four channels and is ignored
+        let color_size = pixel_size / 4 * 3;
+        let data = self.data();
+        sample_positions(self.height).all(|y| {
+            let row = &data[y * self.row_pitch as usize..];
+            sample_positions(self.width).all(|x| {
+                row[x * pixel_size..x * pixel_size + color_size]
+                    .iter()
+                    .all(|&byte| byte == 0)
+            })
+        })
+    }
+
+    // Convert the premultiplied `Rgba8` pixels to straight alpha in place. The staging texture is
+    // mapped for reading and writing, so the conversion does not need a copy
+    fn unpremultiply_alpha(&mut self) {
+        let data = unsafe {
             slice::from_raw_parts_mut(
-                mapped_resource.pData.cast(),
-                (self.height * mapped_resource.RowPitch) as usize,
+                self.data.cast_mut(),
+                (self.height * self.row_pitch) as usize,
             )
         };
-        Ok(frame_data)
+        unpremultiply_rows(
+            data,
+            self.row_pitch as usize,
+            self.width as usize,
+            self.height as usize,
+        );
+    }
+
+    /// Detect the region inside the uniform dark borders of the frame, e.g. the letterbox bars of
+    /// fullscreen games. Pixels count as border if none of their colors exceeds `tolerance`, so
+    /// compression noise in near-black bars is ignored. Frames without content outside of the
+    /// borders are not cropped. Only the pixels of `region` are scanned, and the returned region
+    /// lies inside of it.
+    ///
+    /// # Errors
+    ///
+    /// `FrameError::AutoCropFormatUnsupported`: The frame is not in the `Rgba8` format.
+    pub fn content_region(&self, region: Region, tolerance: u8) -> Result<Region, FrameError> {
+        if self.color_format != ColorFormat::Rgba8 {
+            return Err(FrameError::AutoCropFormatUnsupported(self.color_format));
+        }
+        let row_pitch = self.row_pitch as usize;
+        let offset = region.y as usize * row_pitch + region.x as usize * 4;
+        let bounds = content_bounds(
+            &self.data()[offset..],
+            row_pitch,
+            region.width as usize,
+            region.height as usize,
+            tolerance,
+        );
+        Ok(bounds.map_or(region, |bounds| Region {
+            x: region.x + bounds.x,
+            y: region.y + bounds.y,
+            ..bounds
+        }))
+    }
+}
+
+// The mapped memory is only unmapped on drop, and the DirectX interfaces are free-threaded
+unsafe impl Send for MappedFrame {}
+
+impl Drop for MappedFrame {
+    fn drop(&mut self) {
+        unsafe { self.context.Unmap(&self.texture, 0) };
+    }
+}
+
+/// Read-only view on the pixels of a frame that supports the Python buffer protocol.
+///
+/// The view exposes the mapped frame memory directly, so ``np.asarray(view)`` returns an array with
+/// dimensions [h w 4] without copying the pixels. The memory stays valid as long as the view or any
+/// array created from it is alive, and is released afterwards. New frames do not change the content
+/// of an existing view. Frameworks that support DLPack import the view without copies as well, e.g.
+/// ``torch.from_dlpack(view)`` or ``np.from_dlpack(view)``.
+///
+/// .. warning::
+///    Each view keeps its own staging texture on the GPU alive. Release views as soon as you are
+///    done with them instead of holding on to views of old frames.
+#[pyclass]
+pub struct FrameView {
+    mapped_frame: MappedFrame,
+    shape: [isize; 3],
+    strides: [isize; 3],
+}
+
+impl FrameView {
+    pub fn new(mapped_frame: MappedFrame) -> Self {
+        let channel_size = mapped_frame.color_format.channel_size() as isize;
+        let shape = [mapped_frame.height as isize, mapped_frame.width as isize, 4];
+        let strides = [
+            mapped_frame.row_pitch as isize,
+            4 * channel_size,
+            channel_size,
+        ];
+        Self {
+            mapped_frame,
+            shape,
+            strides,
+        }
+    }
+}
+
+#[pymethods]
+impl FrameView {
+    /// :``tuple[int, int, int]``: The dimensions of the view as (height, width, 4).
+    #[getter]
+    pub fn shape(&self) -> (u32, u32, u32) {
+        (self.mapped_frame.height, self.mapped_frame.width, 4)
+    }
+
+    unsafe fn __getbuffer__(
+        slf: &PyCell<Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Frame views are read-only"));
+        }
+        let frame_view = slf.borrow();
+        // Rows are padded to the row pitch, so consumers have to support strided buffers
+        if (flags & ffi::PyBUF_STRIDES) != ffi::PyBUF_STRIDES {
+            return Err(PyBufferError::new_err(
+                "Frame views require strided buffer access",
+            ));
+        }
+        let mapped_frame = &frame_view.mapped_frame;
+        let itemsize = frame_view.strides[2];
+        (*view).obj = ffi::_Py_NewRef(slf.as_ptr());
+        (*view).buf = mapped_frame.data as *mut c_void;
+        (*view).len = frame_view.shape.iter().product::<isize>() * itemsize;
+        (*view).readonly = 1;
+        (*view).itemsize = itemsize;
+        (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+            match mapped_frame.color_format {
+                ColorFormat::Rgba8 => c"B".as_ptr() as *mut c_char,
+                ColorFormat::Rgba16Float => c"e".as_ptr() as *mut c_char,
+            }
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 3;
+        (*view).shape = frame_view.shape.as_ptr() as *mut isize;
+        (*view).strides = frame_view.strides.as_ptr() as *mut isize;
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+
+    /// __dlpack__(stream: None = None) -> PyCapsule
+    /// Export the view as a DLPack tensor with dimensions [h w 4].
+    ///
+    /// The tensor references the mapped frame memory and keeps the view alive until the consumer
+    /// releases it.
+    #[pyo3(signature = (stream=None))]
+    pub fn __dlpack__(slf: &PyCell<Self>, stream: Option<PyObject>) -> PyResult<PyObject> {
+        // Frames live in CPU memory, which has no streams to synchronize with
+        if stream.is_some() {
+            return Err(PyBufferError::new_err(
+                "Frame views do not support DLPack streams",
+            ));
+        }
+        let frame_view = slf.borrow();
+        let mapped_frame = &frame_view.mapped_frame;
+        let channel_size = mapped_frame.color_format.channel_size() as i64;
+        let dtype = match mapped_frame.color_format {
+            ColorFormat::Rgba8 => DLDataType {
+                code: DL_UINT,
+                bits: 8,
+                lanes: 1,
+            },
+            ColorFormat::Rgba16Float => DLDataType {
+                code: DL_FLOAT,
+                bits: 16,
+                lanes: 1,
+            },
+        };
+        let mut tensor = Box::new(DlpackTensor {
+            managed: DLManagedTensor {
+                dl_tensor: DLTensor {
+                    data: mapped_frame.data as *mut c_void,
+                    device: DLDevice {
+                        device_type: DL_CPU,
+                        device_id: 0,
+                    },
+                    ndim: 3,
+                    dtype,
+                    shape: ptr::null_mut(),
+                    strides: ptr::null_mut(),
+                    byte_offset: 0,
+                },
+                manager_ctx: ptr::null_mut(),
+                deleter: Some(dlpack_deleter),
+            },
+            // DLPack strides count elements instead of bytes
+            shape: frame_view.shape.map(|len| len as i64),
+            strides: frame_view
+                .strides
+                .map(|stride| stride as i64 / channel_size),
+            owner: slf.into(),
+        });
+        tensor.managed.dl_tensor.shape = tensor.shape.as_mut_ptr();
+        tensor.managed.dl_tensor.strides = tensor.strides.as_mut_ptr();
+        let managed = Box::into_raw(tensor).cast::<c_void>();
+        let capsule = unsafe {
+            ffi::PyCapsule_New(managed, DLTENSOR.as_ptr(), Some(dlpack_capsule_destructor))
+        };
+        if capsule.is_null() {
+            unsafe { dlpack_deleter(managed.cast()) };
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(unsafe { PyObject::from_owned_ptr(slf.py(), capsule) })
+    }
+
+    /// __dlpack_device__() -> tuple[int, int]
+    /// Return the DLPack device of the view, which is always the CPU.
+    pub fn __dlpack_device__(&self) -> (i32, i32) {
+        (DL_CPU, 0)
+    }
+}
+
+// DLPack data structures, see https://dmlc.github.io/dlpack/latest/c_api.html
+const DLTENSOR: &std::ffi::CStr = c"dltensor";
+const DL_CPU: i32 = 1;
+const DL_UINT: u8 = 1;
+const DL_FLOAT: u8 = 2;
+
+#[repr(C)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: c_int,
+    dtype: DLDataType,
+    shape: *mut i64,
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+// A DLPack tensor together with the dimensions it points to and the view that owns its memory. The
+// managed tensor comes first, so pointers to both are interchangeable
+#[repr(C)]
+struct DlpackTensor {
+    managed: DLManagedTensor,
+    shape: [i64; 3],
+    strides: [i64; 3],
+    owner: Py<FrameView>,
+}
+
+// Called by the consumer once it no longer needs the tensor, possibly without holding the GIL
+unsafe extern "C" fn dlpack_deleter(managed: *mut DLManagedTensor) {
+    let tensor = Box::from_raw(managed.cast::<DlpackTensor>());
+    Python::with_gil(|_| drop(tensor));
+}
+
+// Consumers rename the capsule to "used_dltensor" once they took over the tensor. Otherwise, the
+// capsule still owns it
+unsafe extern "C" fn dlpack_capsule_destructor(capsule: *mut ffi::PyObject) {
+    if ffi::PyCapsule_IsValid(capsule, DLTENSOR.as_ptr()) == 1 {
+        let managed = ffi::PyCapsule_GetPointer(capsule, DLTENSOR.as_ptr());
+        dlpack_deleter(managed.cast());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpremultiply_rows_divides_by_alpha() {
+        // One row of four RGBA pixels, padded with 4 bytes that must not change
+        let mut data = [
+            10, 20, 30, 255, 64, 32, 0, 128, 200, 1, 0, 100, 7, 7, 7, 0, 0xff, 0xff, 0xff, 0xff,
+        ];
+        unpremultiply_rows(&mut data, 20, 4, 1);
+        assert_eq!(
+            data,
+            [
+                // Opaque pixels keep their colors
+                10, 20, 30, 255, //
+                // Colors are rounded to the nearest value
+                128, 64, 0, 128, //
+                // Colors above the alpha of invalid input are clamped
+                255, 3, 0, 100, //
+                // Transparent pixels become black
+                0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff,
+            ]
+        );
+    }
+
+    #[test]
+    fn content_bounds_skips_dark_borders() {
+        // 4x3 image with a row pitch of 5 pixels. The content is the bright pixel at (1, 1) and
+        // the near-black pixel at (2, 1), framed by a black border with a bright padding pixel
+        let mut data = vec![0; 5 * 4 * 3];
+        for y in 0..3 {
+            data[y * 20 + 16..y * 20 + 20].fill(0xff);
+        }
+        data[20 + 4..20 + 8].copy_from_slice(&[200, 10, 10, 255]);
+        data[20 + 8..20 + 12].copy_from_slice(&[0, 0, 12, 255]);
+        assert_eq!(content_bounds(&data, 20, 4, 3, 0), Some(region(1, 1, 2, 1)));
+        assert_eq!(
+            content_bounds(&data, 20, 4, 3, 16),
+            Some(region(1, 1, 1, 1))
+        );
+        // Alpha is not a color
+        assert_eq!(content_bounds(&data, 20, 4, 3, 200), None);
+        assert_eq!(content_bounds(&[0; 16], 8, 2, 2, 0), None);
+    }
+
+    #[test]
+    fn pack_rows_strips_padding() {
+        // Two rows of two RGBA pixels, each padded with 4 bytes
+        let data = [
+            0, 1, 2, 3, 4, 5, 6, 7, 0xff, 0xff, 0xff, 0xff, //
+            8, 9, 10, 11, 12, 13, 14, 15, 0xff, 0xff, 0xff, 0xff,
+        ];
+        let packed = pack_rows(&data, 12, 8, 2);
+        assert_eq!(packed, (0..16).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn pack_rows_without_padding() {
+        let data: Vec<u8> = (0..16).collect();
+        assert_eq!(pack_rows(&data, 8, 8, 2), data);
+    }
+
+    #[test]
+    fn hash_rows_ignores_padding() {
+        let padded = [0, 1, 2, 3, 0xff, 4, 5, 6, 7, 0xee];
+        let packed: Vec<u8> = (0..8).collect();
+        assert_eq!(hash_rows(&padded, 5, 4, 2), hash_rows(&packed, 4, 4, 2));
+    }
+
+    #[test]
+    fn hash_rows_detects_changes() {
+        let data: Vec<u8> = (0..8).collect();
+        let mut changed = data.clone();
+        changed[7] = 0;
+        assert_ne!(hash_rows(&data, 4, 4, 2), hash_rows(&changed, 4, 4, 2));
+        // FNV-1a of empty input is the offset basis
+        assert_eq!(hash_rows(&[], 4, 4, 0), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn crc32_rows_matches_reference() {
+        // Check value of CRC-32 for the ASCII digits, split into rows with one byte of padding
+        assert_eq!(crc32_rows(b"123456789", 9, 9, 1), 0xcbf4_3926);
+        assert_eq!(crc32_rows(b"123x456x789x", 4, 3, 3), 0xcbf4_3926);
+        assert_eq!(crc32_rows(&[], 4, 4, 0), 0);
+    }
+
+    #[test]
+    fn sample_rows_skips_pixels_and_padding() {
+        // Three rows of three 1 byte pixels, each padded with 1 byte
+        let data = [0, 1, 2, 0xff, 3, 4, 5, 0xff, 6, 7, 8, 0xff];
+        assert_eq!(sample_rows(&data, 4, 1, [3, 3], 2), [0, 2, 6, 8]);
+        assert_eq!(
+            sample_rows(&data, 4, 1, [3, 3], 1),
+            (0..9).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn changed_fraction_counts_pixels() {
+        let data = [0, 1, 2, 3, 4, 5, 6, 7];
+        assert_eq!(changed_fraction(&data, &data, 2), 0.0);
+        // A single changed byte changes the whole pixel
+        let changed = [0, 1, 2, 0, 4, 5, 6, 7];
+        assert_eq!(changed_fraction(&data, &changed, 2), 0.25);
+        assert_eq!(changed_fraction(&[], &[], 2), 0.0);
+    }
+
+    #[test]
+    fn region_intersection() {
+        let a = region(0, 0, 10, 10);
+        assert_eq!(
+            a.intersection(region(5, 2, 10, 4)),
+            Some(region(5, 2, 5, 4))
+        );
+        assert_eq!(a.intersection(region(2, 2, 2, 2)), Some(region(2, 2, 2, 2)));
+        // Touching regions do not overlap
+        assert_eq!(a.intersection(region(10, 0, 5, 5)), None);
+    }
+
+    #[test]
+    fn rgb565_keeps_high_bits() {
+        assert_eq!(rgb565(0, 0, 0), 0);
+        assert_eq!(rgb565(0xff, 0xff, 0xff), 0xffff);
+        assert_eq!(rgb565(0xff, 0, 0), 0xf800);
+        assert_eq!(rgb565(0, 0xff, 0), 0x07e0);
+        assert_eq!(rgb565(0, 0, 0xff), 0x001f);
+        // The low bits of each channel are dropped
+        assert_eq!(rgb565(0x07, 0x03, 0x07), 0);
+    }
+
+    #[test]
+    fn map_point_scales_and_clips() {
+        // Equal sizes map points to themselves
+        assert_eq!(map_point((10, 20), (100, 100), (100, 100)), Some((10, 20)));
+        // Logical pixels of a 150% display cover 1.5 physical pixels
+        assert_eq!(
+            map_point((10, 20), (1280, 720), (1920, 1080)),
+            Some((15, 30))
+        );
+        assert_eq!(
+            map_point((1279, 719), (1280, 720), (1920, 1080)),
+            Some((1918, 1078))
+        );
+        // Halved frames map two pixels to one
+        assert_eq!(map_point((3, 3), (100, 100), (50, 50)), Some((1, 1)));
+        // Points outside of the area are not mapped
+        assert_eq!(map_point((-1, 0), (100, 100), (100, 100)), None);
+        assert_eq!(map_point((0, 100), (100, 100), (100, 100)), None);
+    }
+
+    #[test]
+    fn fit_region_letterboxes() {
+        // A wide frame gets bars at the top and bottom
+        assert_eq!(
+            fit_region((200, 100), (100, 100), true),
+            region(0, 25, 100, 50)
+        );
+        // A tall frame gets bars on the left and right
+        assert_eq!(
+            fit_region((100, 200), (100, 100), true),
+            region(25, 0, 50, 100)
+        );
+        // Matching aspect ratios need no bars
+        assert_eq!(
+            fit_region((160, 90), (320, 180), true),
+            region(0, 0, 320, 180)
+        );
+        // Without preserving the aspect ratio, the content is stretched
+        assert_eq!(
+            fit_region((200, 100), (100, 100), false),
+            region(0, 0, 100, 100)
+        );
+        // Extreme ratios still cover at least one pixel
+        assert_eq!(fit_region((1000, 1), (10, 10), true), region(0, 4, 10, 1));
+    }
+
+    #[test]
+    fn resize_nearest_pads_and_samples() {
+        let frame = numbered_frame();
+        // Upscale the 2x2 frame into the left half of a 4x2 target
+        let resized = resize_nearest(frame.view(), (4, 2), region(0, 0, 2, 2), [9; 4]);
+        assert_eq!(pixel_order(resized.view()), [0, 1, 9, 9, 2, 3, 9, 9]);
+        // Downscale to a single pixel
+        let resized = resize_nearest(frame.view(), (1, 1), region(0, 0, 1, 1), [9; 4]);
+        assert_eq!(pixel_order(resized.view()), [3]);
+    }
+
+    #[test]
+    fn retry_recovers_from_transient_errors() {
+        let mut calls = 0;
+        let result = retry(
+            5,
+            Duration::ZERO,
+            || {
+                calls += 1;
+                if calls < 3 {
+                    Err("still drawing")
+                } else {
+                    Ok(calls)
+                }
+            },
+            |_| true,
+        );
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn retry_gives_up() {
+        // Persistent transient errors are retried until the attempts are used up
+        let result: Result<(), _> = retry(3, Duration::ZERO, || Err("still drawing"), |_| true);
+        assert_eq!(result, Err(("still drawing", 3)));
+        // Other errors fail immediately
+        let mut calls = 0;
+        let result: Result<(), _> = retry(
+            3,
+            Duration::ZERO,
+            || {
+                calls += 1;
+                Err("device removed")
+            },
+            |error| *error == "still drawing",
+        );
+        assert_eq!(result, Err(("device removed", 1)));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn accumulator_rounds_mean() {
+        let mut accumulator = FrameAccumulator::new(2, 2);
+        let frame = numbered_frame();
+        assert!(accumulator.add(frame.view()));
+        assert!(accumulator.add((&frame + 1).view()));
+        // The means 0.5, 1.5, 2.5 and 3.5 are rounded up
+        assert_eq!(pixel_order(accumulator.average().view()), [1, 2, 3, 4]);
+        // Frames of a different size are left out
+        assert!(!accumulator.add(ndarray::Array3::zeros([1, 2, 4]).view()));
+        assert_eq!(pixel_order(accumulator.average().view()), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn downscale_level_rounds_to_larger_scale() {
+        assert_eq!(downscale_level(1.0), 0);
+        assert_eq!(downscale_level(0.5), 1);
+        assert_eq!(downscale_level(0.3), 1);
+        assert_eq!(downscale_level(0.25), 2);
+        assert_eq!(downscale_level(0.125), 3);
+    }
+
+    #[test]
+    fn missed_frames_rounds_to_intervals() {
+        assert_eq!(missed_frames(166_667, 166_667), 0);
+        assert_eq!(missed_frames(200_000, 166_667), 0);
+        assert_eq!(missed_frames(333_333, 166_667), 1);
+        assert_eq!(missed_frames(1_000_000, 166_667), 5);
+        // Out of order and duplicated frame times do not count as skips
+        assert_eq!(missed_frames(-10, 166_667), 0);
+        assert_eq!(missed_frames(0, 166_667), 0);
+        assert_eq!(missed_frames(1_000_000, 0), 0);
+    }
+
+    fn region(x: u32, y: u32, width: u32, height: u32) -> Region {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // A 2x2 frame whose pixels are numbered 0 to 3 in row-major order
+    fn numbered_frame() -> ndarray::Array3<u8> {
+        ndarray::Array3::from_shape_fn([2, 2, 4], |(y, x, _)| (y * 2 + x) as u8)
+    }
+
+    fn pixel_order(view: ArrayView3<u8>) -> Vec<u8> {
+        view.outer_iter()
+            .flat_map(|row| row.outer_iter().map(|pixel| pixel[0]).collect::<Vec<_>>())
+            .collect()
+    }
+
+    #[test]
+    fn flip_view_vertical() {
+        let frame = numbered_frame();
+        let flip = Flip {
+            vertical: true,
+            horizontal: false,
+        };
+        assert_eq!(pixel_order(flip_view(frame.view(), flip)), [2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn flip_view_horizontal() {
+        let frame = numbered_frame();
+        let flip = Flip {
+            vertical: false,
+            horizontal: true,
+        };
+        assert_eq!(pixel_order(flip_view(frame.view(), flip)), [1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn flip_view_both() {
+        let frame = numbered_frame();
+        let flip = Flip {
+            vertical: true,
+            horizontal: true,
+        };
+        assert_eq!(pixel_order(flip_view(frame.view(), flip)), [3, 2, 1, 0]);
+        assert_eq!(
+            pixel_order(flip_view(frame.view(), Flip::default())),
+            [0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn layout_view_planar() {
+        // Channel c of pixel p holds p * 4 + c
+        let frame =
+            ndarray::Array3::from_shape_fn([2, 2, 4], |(y, x, c)| ((y * 2 + x) * 4 + c) as u8);
+        let planar = layout_view(frame.view(), Layout::Chw);
+        assert_eq!(planar.shape(), [4, 2, 2]);
+        for ((c, y, x), &value) in planar.indexed_iter() {
+            assert_eq!(value, frame[[y, x, c]]);
+        }
+        assert_eq!(layout_view(frame.view(), Layout::Hwc), frame.view());
     }
 }
@@ -1,22 +1,84 @@
+// The pyo3 0.20 macros generate impl blocks that newer compilers flag as non-local definitions
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
 
 pub mod capture;
+pub mod capture_core;
+pub mod capture_group;
 mod capture_utils;
 mod direct_x;
+mod errors;
 mod frame;
 pub mod monitor;
+mod pipe;
+mod recorder;
 pub mod window;
 
+// Types of the Rust capture API in `capture_core`
+pub use capture_utils::{
+    ApartmentType, CaptureItem, CaptureStats, CaptureTarget, ColorFormat, ResizePolicy,
+    ThreadPriority,
+};
+pub use direct_x::FeatureLevel;
+pub use frame::{Frame, MappedFrame};
+pub use recorder::RecorderError;
+
 /// Export the pixel_forge Rust library to Python.
 #[pymodule]
-fn pixel_forge(_py: Python, m: &PyModule) -> PyResult<()> {
+fn pixel_forge(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(window::enumerate_windows, m)?)?;
+    m.add_function(wrap_pyfunction!(window::enumerate_windows_on_monitor, m)?)?;
+    m.add_function(wrap_pyfunction!(window::enumerate_windows_async, m)?)?;
+    m.add_function(wrap_pyfunction!(window::enumerate_top_level_windows, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        window::enumerate_processes_with_windows,
+        m
+    )?)?;
     m.add_function(wrap_pyfunction!(window::foreground_window, m)?)?;
+    m.add_function(wrap_pyfunction!(window::wallpaper_window, m)?)?;
     m.add_class::<window::Window>()?;
+    m.add_class::<window::TitleChangeListener>()?;
     m.add_function(wrap_pyfunction!(monitor::primary_monitor, m)?)?;
     m.add_function(wrap_pyfunction!(monitor::enumerate_monitors, m)?)?;
+    m.add_function(wrap_pyfunction!(monitor::enumerate_monitors_async, m)?)?;
+    m.add_function(wrap_pyfunction!(monitor::monitor_count, m)?)?;
     m.add_class::<monitor::Monitor>()?;
+    m.add_class::<monitor::VirtualDesktop>()?;
+    m.add_function(wrap_pyfunction!(monitor::enumerate_display_devices, m)?)?;
+    m.add_class::<monitor::DisplayDevice>()?;
+    m.add_function(wrap_pyfunction!(monitor::set_process_dpi_aware, m)?)?;
+    m.add_function(wrap_pyfunction!(monitor::on_display_change, m)?)?;
+    m.add_class::<monitor::DisplayChangeListener>()?;
     m.add_class::<capture::Capture>()?;
+    m.add_class::<capture::FrameIterator>()?;
+    m.add_class::<capture::CaptureHandle>()?;
+    m.add_class::<capture::CaptureConfig>()?;
+    m.add_class::<capture::FrameData>()?;
+    m.add_function(wrap_pyfunction!(capture::capture_size, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::supported_formats, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::grab, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::grab_monitors, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::screenshot_foreground, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::capture_when_available, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::is_supported, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::diagnostics, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::windows_build, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::supports_cursor_toggle, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::supports_border_toggle, m)?)?;
+    m.add_function(wrap_pyfunction!(capture::pick_capture_target, m)?)?;
+    m.add_class::<capture_utils::CaptureItem>()?;
+    m.add_class::<capture_group::CaptureGroup>()?;
+    m.add_class::<capture_utils::CaptureStats>()?;
+    m.add_class::<capture_utils::Diagnostics>()?;
+    m.add_class::<capture_utils::WindowsBuild>()?;
+    m.add_class::<capture_utils::ColorFormat>()?;
+    m.add_class::<capture_utils::ApartmentType>()?;
+    m.add_class::<capture_utils::ThreadPriority>()?;
+    m.add_class::<capture_utils::ResizePolicy>()?;
+    m.add_class::<frame::FrameView>()?;
+    m.add_class::<direct_x::FeatureLevel>()?;
+    errors::register(py, m)?;
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     Ok(())
 }
@@ -1,21 +1,49 @@
 // This code has been adapted from https://github.com/NiiightmareXD/windows-capture
 
 use std::mem;
-use std::num::ParseIntError;
+use std::num::{ParseIntError, TryFromIntError};
 use std::string::FromUtf16Error;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
 
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
-use windows::core::{HSTRING, PCWSTR};
+use windows::core::{w, HSTRING, PCWSTR};
 use windows::Graphics::Capture::GraphicsCaptureItem;
-use windows::Win32::Foundation::{BOOL, LPARAM, POINT, RECT, TRUE};
+use windows::Win32::Devices::Display::{
+    DisplayConfigGetDeviceInfo, GetDisplayConfigBufferSizes, QueryDisplayConfig,
+    DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME, DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+    DISPLAYCONFIG_DEVICE_INFO_HEADER, DISPLAYCONFIG_MODE_INFO, DISPLAYCONFIG_MODE_INFO_TYPE_TARGET,
+    DISPLAYCONFIG_PATH_INFO, DISPLAYCONFIG_SOURCE_DEVICE_NAME, DISPLAYCONFIG_TARGET_DEVICE_NAME,
+    QDC_ONLY_ACTIVE_PATHS,
+};
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, LRESULT, POINT, RECT, TRUE, WPARAM};
+use windows::Win32::Graphics::Dxgi::{
+    CreateDXGIFactory1, IDXGIFactory1, DXGI_ADAPTER_DESC1, DXGI_OUTPUT_DESC,
+};
 use windows::Win32::Graphics::Gdi::{
     EnumDisplayDevicesW, EnumDisplayMonitors, EnumDisplaySettingsW, GetMonitorInfoW,
-    MonitorFromPoint, DEVMODEW, DISPLAY_DEVICEW, ENUM_CURRENT_SETTINGS, HDC, HMONITOR, MONITORINFO,
-    MONITORINFOEXW, MONITOR_DEFAULTTONULL,
+    MonitorFromPoint, DEVMODEW, DISPLAY_DEVICEW, DISPLAY_DEVICE_ATTACHED_TO_DESKTOP,
+    DISPLAY_DEVICE_MIRRORING_DRIVER, DISPLAY_DEVICE_PRIMARY_DEVICE, ENUM_CURRENT_SETTINGS, HDC,
+    HMONITOR, MONITORINFO, MONITORINFOEXW, MONITOR_DEFAULTTONULL,
 };
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::System::Threading::GetCurrentThreadId;
 use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
+use windows::Win32::UI::HiDpi::{
+    SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+    GetSystemMetrics, GetWindowLongPtrW, PostThreadMessageW, RegisterClassW, SetWindowLongPtrW,
+    TranslateMessage, GWLP_USERDATA, MSG, SM_CMONITORS, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+    SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, WM_DISPLAYCHANGE, WM_QUIT, WNDCLASSW, WS_EX_TOOLWINDOW,
+    WS_OVERLAPPED,
+};
+
+use crate::capture_utils::spawn_future;
+use crate::errors;
 
 #[derive(thiserror::Error, Debug)]
 pub enum MonitorError {
@@ -23,6 +51,8 @@ pub enum MonitorError {
     NotFound,
     #[error("Failed to find monitor name")]
     NameNotFound,
+    #[error("Monitor handle must not be null")]
+    InvalidHandle,
     #[error("Monitor index is lower than one")]
     IndexError,
     #[error("Failed to get monitor info")]
@@ -31,6 +61,16 @@ pub enum MonitorError {
     MonitorSettingsError,
     #[error("Failed to get monitor name")]
     MonitorNameError,
+    #[error("Failed to query the display configuration")]
+    DisplayConfigError,
+    #[error("Failed to find the graphics adapter of the monitor")]
+    AdapterNotFound,
+    #[error("Failed to spawn display listener thread: {0}")]
+    ListenerSpawnError(#[from] std::io::Error),
+    #[error("Display listener thread exited unexpectedly")]
+    ListenerThreadError,
+    #[error("Failed to convert size: {0}")]
+    SizeConversionError(#[from] TryFromIntError),
     #[error("Failed to parse monitor index: {0}")]
     MonitorIndexError(#[from] ParseIntError),
     #[error("Failed to convert windows string: {0}")]
@@ -41,7 +81,10 @@ pub enum MonitorError {
 
 impl From<MonitorError> for PyErr {
     fn from(error: MonitorError) -> PyErr {
-        PyRuntimeError::new_err(error.to_string())
+        match error {
+            MonitorError::NotFound => errors::MonitorNotFoundError::new_err(error.to_string()),
+            _ => errors::PixelForgeError::new_err(error.to_string()),
+        }
     }
 }
 
@@ -68,18 +111,51 @@ impl Monitor {
     /// Args:
     ///    id: The monitor ID. If None, the primary monitor is used.
     #[new]
-    pub fn new(id: Option<usize>) -> Self {
+    pub fn new(id: Option<usize>) -> Result<Self, MonitorError> {
         match id {
-            Some(id) => Monitor::from_index(id).unwrap(),
-            None => primary_monitor().unwrap(),
+            Some(id) => Monitor::from_index(id),
+            None => primary_monitor(),
+        }
+    }
+
+    /// from_hmonitor(handle: int) -> Monitor
+    ///
+    /// Create a :class:`.Monitor` instance from a raw monitor handle (HMONITOR).
+    ///
+    /// This allows wrapping handles obtained from other libraries, e.g. pywin32.
+    ///
+    /// Args:
+    ///    handle: The raw monitor handle as integer.
+    ///
+    /// Raises:
+    ///    InvalidHandle: The handle is zero.
+    #[staticmethod]
+    pub fn from_hmonitor(handle: isize) -> Result<Self, MonitorError> {
+        if handle == 0 {
+            return Err(MonitorError::InvalidHandle);
         }
+        Ok(Self::from_handle(HMONITOR(handle)))
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.monitor_handle.0 as u64
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    /// :``bool``: True if the monitor is still connected, else False.
+    #[getter]
+    pub fn valid(&self) -> bool {
+        enumerate_monitors().is_ok_and(|monitors| monitors.contains(self))
     }
 
     /// :``int``: The pixel width of the monitor.
     #[getter]
     pub fn width(&self) -> Result<u32, MonitorError> {
         let mut device_mode = DEVMODEW {
-            dmSize: u16::try_from(mem::size_of::<DEVMODEW>()).unwrap(),
+            dmSize: u16::try_from(mem::size_of::<DEVMODEW>())?,
             ..DEVMODEW::default()
         };
         let name = HSTRING::from(self.device_name()?);
@@ -101,7 +177,7 @@ impl Monitor {
     #[getter]
     pub fn height(&self) -> Result<u32, MonitorError> {
         let mut device_mode = DEVMODEW {
-            dmSize: u16::try_from(mem::size_of::<DEVMODEW>()).unwrap(),
+            dmSize: u16::try_from(mem::size_of::<DEVMODEW>())?,
             ..DEVMODEW::default()
         };
         let name = HSTRING::from(self.device_name()?);
@@ -130,7 +206,7 @@ impl Monitor {
     #[getter]
     pub fn refresh_rate(&self) -> Result<u32, MonitorError> {
         let mut device_mode = DEVMODEW {
-            dmSize: u16::try_from(mem::size_of::<DEVMODEW>()).unwrap(),
+            dmSize: u16::try_from(mem::size_of::<DEVMODEW>())?,
             ..DEVMODEW::default()
         };
         let name = HSTRING::from(self.device_name()?);
@@ -148,28 +224,56 @@ impl Monitor {
         Ok(device_mode.dmDisplayFrequency)
     }
 
-    /// :``str``: The monitor device name.
+    /// :``int``: The color depth of the monitor in bits per pixel.
     #[getter]
-    pub fn device_name(&self) -> Result<String, MonitorError> {
-        let mut monitor_info = MONITORINFOEXW {
-            monitorInfo: MONITORINFO {
-                cbSize: u32::try_from(mem::size_of::<MONITORINFOEXW>()).unwrap(),
-                rcMonitor: RECT::default(),
-                rcWork: RECT::default(),
-                dwFlags: 0,
-            },
-            szDevice: [0; 32],
+    pub fn bits_per_pixel(&self) -> Result<u32, MonitorError> {
+        let mut device_mode = DEVMODEW {
+            dmSize: u16::try_from(mem::size_of::<DEVMODEW>())?,
+            ..DEVMODEW::default()
         };
+        let name = HSTRING::from(self.device_name()?);
         if unsafe {
-            !GetMonitorInfoW(
-                self.as_raw_hmonitor(),
-                std::ptr::addr_of_mut!(monitor_info).cast(),
+            !EnumDisplaySettingsW(
+                PCWSTR(name.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut device_mode,
             )
             .as_bool()
         } {
-            return Err(MonitorError::MonitorInfoError);
+            return Err(MonitorError::MonitorSettingsError);
         }
 
+        Ok(device_mode.dmBitsPerPel)
+    }
+
+    /// :``int``: The clockwise rotation of the monitor in degrees (0, 90, 180 or 270).
+    #[getter]
+    pub fn orientation(&self) -> Result<u32, MonitorError> {
+        let mut device_mode = DEVMODEW {
+            dmSize: u16::try_from(mem::size_of::<DEVMODEW>())?,
+            ..DEVMODEW::default()
+        };
+        let name = HSTRING::from(self.device_name()?);
+        if unsafe {
+            !EnumDisplaySettingsW(
+                PCWSTR(name.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut device_mode,
+            )
+            .as_bool()
+        } {
+            return Err(MonitorError::MonitorSettingsError);
+        }
+
+        // The display fields of the union are valid for display devices
+        let orientation = unsafe { device_mode.Anonymous1.Anonymous2.dmDisplayOrientation };
+        Ok(orientation.0 * 90)
+    }
+
+    /// :``str``: The monitor device name.
+    #[getter]
+    pub fn device_name(&self) -> Result<String, MonitorError> {
+        let monitor_info = self.monitor_info()?;
         let device_name = String::from_utf16(
             &monitor_info
                 .szDevice
@@ -186,27 +290,9 @@ impl Monitor {
     /// :``str``: The device string of the monitor.
     #[getter]
     pub fn device_string(&self) -> Result<String, MonitorError> {
-        let mut monitor_info = MONITORINFOEXW {
-            monitorInfo: MONITORINFO {
-                cbSize: u32::try_from(mem::size_of::<MONITORINFOEXW>()).unwrap(),
-                rcMonitor: RECT::default(),
-                rcWork: RECT::default(),
-                dwFlags: 0,
-            },
-            szDevice: [0; 32],
-        };
-        if unsafe {
-            !GetMonitorInfoW(
-                self.as_raw_hmonitor(),
-                std::ptr::addr_of_mut!(monitor_info).cast(),
-            )
-            .as_bool()
-        } {
-            return Err(MonitorError::MonitorInfoError);
-        }
-
+        let mut monitor_info = self.monitor_info()?;
         let mut display_device = DISPLAY_DEVICEW {
-            cb: u32::try_from(mem::size_of::<DISPLAY_DEVICEW>()).unwrap(),
+            cb: u32::try_from(mem::size_of::<DISPLAY_DEVICEW>())?,
             DeviceName: [0; 32],
             DeviceString: [0; 128],
             StateFlags: 0,
@@ -238,6 +324,102 @@ impl Monitor {
 
         Ok(device_string)
     }
+
+    /// :``str``: The friendly name of the monitor from its EDID, e.g. "DELL U2720Q".
+    ///
+    /// Unlike :attr:`device_string`, this is the name users know the monitor by. Some monitors,
+    /// e.g. internal laptop panels, report an empty name.
+    #[getter]
+    pub fn friendly_name(&self) -> Result<String, MonitorError> {
+        let Some((path, _)) = self.display_path()? else {
+            return Err(MonitorError::NameNotFound);
+        };
+        let mut target_name = DISPLAYCONFIG_TARGET_DEVICE_NAME {
+            header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+                r#type: DISPLAYCONFIG_DEVICE_INFO_GET_TARGET_NAME,
+                size: u32::try_from(mem::size_of::<DISPLAYCONFIG_TARGET_DEVICE_NAME>())?,
+                adapterId: path.targetInfo.adapterId,
+                id: path.targetInfo.id,
+            },
+            ..Default::default()
+        };
+        if unsafe { DisplayConfigGetDeviceInfo(&mut target_name.header) } != 0 {
+            return Err(MonitorError::DisplayConfigError);
+        }
+        Ok(wide_to_string(&target_name.monitorFriendlyDeviceName)?)
+    }
+
+    /// :``float``: The exact refresh rate of the monitor in Hz, e.g. 59.94.
+    ///
+    /// :attr:`refresh_rate` rounds fractional rates to whole numbers. This rate is taken from the
+    /// timing of the video signal, which makes it suitable for precise frame pacing.
+    #[getter]
+    pub fn refresh_rate_precise(&self) -> Result<f64, MonitorError> {
+        let Some((path, modes)) = self.display_path()? else {
+            return Err(MonitorError::MonitorSettingsError);
+        };
+        // Paths without a target mode have an invalid index that is out of bounds
+        let index = usize::try_from(unsafe { path.targetInfo.Anonymous.modeInfoIdx })?;
+        let mode = modes
+            .get(index)
+            .filter(|mode| mode.infoType == DISPLAYCONFIG_MODE_INFO_TYPE_TARGET)
+            .ok_or(MonitorError::MonitorSettingsError)?;
+        let rate = unsafe { mode.Anonymous.targetMode.targetVideoSignalInfo.vSyncFreq };
+        if rate.Denominator == 0 {
+            return Err(MonitorError::MonitorSettingsError);
+        }
+        Ok(f64::from(rate.Numerator) / f64::from(rate.Denominator))
+    }
+
+    /// :``str``: The description of the graphics adapter (GPU) that drives the monitor.
+    #[getter]
+    pub fn adapter_name(&self) -> Result<String, MonitorError> {
+        let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1()? };
+        // Enumeration ends with an error once the index exceeds the number of adapters or outputs
+        let mut adapter_index = 0;
+        while let Ok(adapter) = unsafe { factory.EnumAdapters1(adapter_index) } {
+            let mut output_index = 0;
+            while let Ok(output) = unsafe { adapter.EnumOutputs(output_index) } {
+                let mut output_desc = DXGI_OUTPUT_DESC::default();
+                unsafe { output.GetDesc(&mut output_desc)? };
+                if output_desc.Monitor == self.monitor_handle {
+                    let mut adapter_desc = DXGI_ADAPTER_DESC1::default();
+                    unsafe { adapter.GetDesc1(&mut adapter_desc)? };
+                    let adapter_name = String::from_utf16(
+                        &adapter_desc
+                            .Description
+                            .as_slice()
+                            .iter()
+                            .take_while(|ch| **ch != 0x0000)
+                            .copied()
+                            .collect::<Vec<u16>>(),
+                    )?;
+                    return Ok(adapter_name);
+                }
+                output_index += 1;
+            }
+            adapter_index += 1;
+        }
+
+        Err(MonitorError::AdapterNotFound)
+    }
+
+    /// contains_point(x: int, y: int) -> bool
+    /// Check whether a point in virtual desktop coordinates lies on the monitor.
+    ///
+    /// Useful to map a click or a window position to the monitor that shows it. The right and
+    /// bottom edges of the monitor belong to its neighbours.
+    ///
+    /// Args:
+    ///     x: The horizontal coordinate of the point.
+    ///     y: The vertical coordinate of the point.
+    ///
+    /// Returns:
+    ///     True if the point lies on the monitor, else False.
+    pub fn contains_point(&self, x: i32, y: i32) -> Result<bool, MonitorError> {
+        let rect = self.rect()?;
+        Ok((rect.left..rect.right).contains(&x) && (rect.top..rect.bottom).contains(&y))
+    }
 }
 
 impl Monitor {
@@ -275,6 +457,62 @@ impl Monitor {
         Self { monitor_handle }
     }
 
+    /// Find the active display path of the monitor, together with the display modes that the mode
+    /// indices of the path refer to. Returns `None` if the monitor has no active path.
+    fn display_path(
+        &self,
+    ) -> Result<Option<(DISPLAYCONFIG_PATH_INFO, Vec<DISPLAYCONFIG_MODE_INFO>)>, MonitorError> {
+        let device_name = self.device_name()?;
+        let (paths, modes) = active_display_config()?;
+        for path in paths {
+            // Match the path through the GDI device name of its source, e.g. \\.\DISPLAY1
+            let mut source_name = DISPLAYCONFIG_SOURCE_DEVICE_NAME {
+                header: DISPLAYCONFIG_DEVICE_INFO_HEADER {
+                    r#type: DISPLAYCONFIG_DEVICE_INFO_GET_SOURCE_NAME,
+                    size: u32::try_from(mem::size_of::<DISPLAYCONFIG_SOURCE_DEVICE_NAME>())?,
+                    adapterId: path.sourceInfo.adapterId,
+                    id: path.sourceInfo.id,
+                },
+                ..Default::default()
+            };
+            if unsafe { DisplayConfigGetDeviceInfo(&mut source_name.header) } != 0 {
+                return Err(MonitorError::DisplayConfigError);
+            }
+            if wide_to_string(&source_name.viewGdiDeviceName)? == device_name {
+                return Ok(Some((path, modes)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return the monitor rectangle in virtual desktop coordinates.
+    pub fn rect(&self) -> Result<RECT, MonitorError> {
+        Ok(self.monitor_info()?.monitorInfo.rcMonitor)
+    }
+
+    /// Query the extended monitor info of the monitor.
+    fn monitor_info(&self) -> Result<MONITORINFOEXW, MonitorError> {
+        let mut monitor_info = MONITORINFOEXW {
+            monitorInfo: MONITORINFO {
+                cbSize: u32::try_from(mem::size_of::<MONITORINFOEXW>())?,
+                rcMonitor: RECT::default(),
+                rcWork: RECT::default(),
+                dwFlags: 0,
+            },
+            szDevice: [0; 32],
+        };
+        if unsafe {
+            !GetMonitorInfoW(
+                self.as_raw_hmonitor(),
+                std::ptr::addr_of_mut!(monitor_info).cast(),
+            )
+            .as_bool()
+        } {
+            return Err(MonitorError::MonitorInfoError);
+        }
+        Ok(monitor_info)
+    }
+
     /// Returns the raw HMONITOR of the monitor.
     #[must_use]
     pub const fn as_raw_hmonitor(&self) -> HMONITOR {
@@ -319,7 +557,7 @@ unsafe extern "system" fn enum_monitors_callback(
 /// Enumerate all monitors connected to the system.
 ///
 /// Returns:
-///   The list of all monitors.
+///   The list of all monitors, ordered by their index.
 #[pyfunction]
 pub fn enumerate_monitors() -> Result<Vec<Monitor>, MonitorError> {
     let mut monitors: Vec<Monitor> = Vec::new();
@@ -333,10 +571,383 @@ pub fn enumerate_monitors() -> Result<Vec<Monitor>, MonitorError> {
         )
         .ok()?;
     };
+    // The callback order does not follow the display index, so we sort the monitors to keep the
+    // list positions consistent with Monitor(id)
+    monitors.sort_by_cached_key(|monitor| monitor.index().unwrap_or(usize::MAX));
 
     Ok(monitors)
 }
 
+/// monitor_count() -> int
+///
+/// Count the monitors connected to the system without enumerating them.
+///
+/// Returns:
+///   The number of display monitors on the desktop.
+#[pyfunction]
+pub fn monitor_count() -> u32 {
+    unsafe { GetSystemMetrics(SM_CMONITORS) }.unsigned_abs()
+}
+
+/// enumerate_monitors_async() -> asyncio.Future[list[Monitor]]
+///
+/// Enumerate all monitors connected to the system without blocking the event loop.
+///
+/// The enumeration runs in a background thread and the returned future resolves with the same
+/// list as :func:`enumerate_monitors`.
+///
+/// .. note::
+///    This function has to be called from within a running asyncio event loop.
+///
+/// Returns:
+///   A future that resolves to the list of all monitors, ordered by their index.
+#[pyfunction]
+pub fn enumerate_monitors_async(py: Python) -> PyResult<PyObject> {
+    spawn_future(py, || Ok(enumerate_monitors()?))
+}
+
+/// A display output of a graphics adapter, returned by :func:`enumerate_display_devices`.
+///
+/// Unlike :class:`.Monitor`, display devices include outputs that are not part of the desktop,
+/// e.g. monitors that are connected but disabled in the display settings.
+#[pyclass(get_all)]
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct DisplayDevice {
+    /// :``str``: The device name, e.g. ``\\.\DISPLAY1``. Matches :attr:`.Monitor.device_name` of
+    /// active devices.
+    pub device_name: String,
+    /// :``str``: The description of the graphics adapter that drives the output.
+    pub adapter_string: String,
+    /// :``str | None``: The description of the connected monitor, or None if no monitor is known.
+    pub monitor_string: Option<String>,
+    /// :``bool``: True if the output is part of the desktop, i.e. it is available as a Monitor.
+    pub active: bool,
+    /// :``bool``: True if the output shows the primary monitor.
+    pub primary: bool,
+}
+
+#[pymethods]
+impl DisplayDevice {
+    fn __repr__(&self) -> String {
+        format!(
+            "DisplayDevice(device_name={:?}, adapter_string={:?}, monitor_string={:?}, active={}, primary={})",
+            self.device_name, self.adapter_string, self.monitor_string, self.active, self.primary
+        )
+    }
+}
+
+// Query the display device at `index`. Without a device name, the outputs of all adapters are
+// enumerated. With the name of an output, the monitors connected to that output are enumerated
+fn display_device(
+    device_name: Option<&HSTRING>,
+    index: u32,
+) -> Result<Option<DISPLAY_DEVICEW>, MonitorError> {
+    let mut display_device = DISPLAY_DEVICEW {
+        cb: u32::try_from(mem::size_of::<DISPLAY_DEVICEW>())?,
+        ..Default::default()
+    };
+    let device_name = device_name.map_or(PCWSTR::null(), |name| PCWSTR::from_raw(name.as_ptr()));
+    // Enumeration fails once the index exceeds the number of devices
+    let found = unsafe { EnumDisplayDevicesW(device_name, index, &mut display_device, 0) };
+    Ok(found.as_bool().then_some(display_device))
+}
+
+// Query the display paths from sources (GDI displays) to targets (monitors) that are in use and
+// the display modes they refer to
+fn active_display_config(
+) -> Result<(Vec<DISPLAYCONFIG_PATH_INFO>, Vec<DISPLAYCONFIG_MODE_INFO>), MonitorError> {
+    let (mut path_count, mut mode_count) = (0, 0);
+    unsafe { GetDisplayConfigBufferSizes(QDC_ONLY_ACTIVE_PATHS, &mut path_count, &mut mode_count) }
+        .ok()?;
+    let mut paths = vec![DISPLAYCONFIG_PATH_INFO::default(); path_count as usize];
+    let mut modes = vec![DISPLAYCONFIG_MODE_INFO::default(); mode_count as usize];
+    unsafe {
+        QueryDisplayConfig(
+            QDC_ONLY_ACTIVE_PATHS,
+            &mut path_count,
+            paths.as_mut_ptr(),
+            &mut mode_count,
+            modes.as_mut_ptr(),
+            None,
+        )
+    }
+    .ok()?;
+    // The configuration may have changed between the two calls and contain fewer paths
+    paths.truncate(path_count as usize);
+    modes.truncate(mode_count as usize);
+    Ok((paths, modes))
+}
+
+fn wide_to_string(wide: &[u16]) -> Result<String, FromUtf16Error> {
+    let len = wide
+        .iter()
+        .position(|ch| *ch == 0x0000)
+        .unwrap_or(wide.len());
+    String::from_utf16(&wide[..len])
+}
+
+/// enumerate_display_devices() -> list[DisplayDevice]
+///
+/// Enumerate all display outputs of the graphics adapters, including inactive ones.
+///
+/// Use this function to present the full display topology, e.g. in configuration dialogs. Only
+/// active devices can be captured, use :func:`enumerate_monitors` to get them as capture targets.
+///
+/// Returns:
+///     The list of all display devices.
+#[pyfunction]
+pub fn enumerate_display_devices() -> Result<Vec<DisplayDevice>, MonitorError> {
+    let mut devices = Vec::new();
+    let mut index = 0;
+    while let Some(output) = display_device(None, index)? {
+        index += 1;
+        // Mirroring drivers are pseudo devices used by remote desktop and screen sharing tools
+        if output.StateFlags & DISPLAY_DEVICE_MIRRORING_DRIVER != 0 {
+            continue;
+        }
+        let device_name = wide_to_string(&output.DeviceName)?;
+        let monitor = display_device(Some(&HSTRING::from(device_name.as_str())), 0)?;
+        devices.push(DisplayDevice {
+            device_name,
+            adapter_string: wide_to_string(&output.DeviceString)?,
+            monitor_string: monitor
+                .map(|monitor| wide_to_string(&monitor.DeviceString))
+                .transpose()?,
+            active: output.StateFlags & DISPLAY_DEVICE_ATTACHED_TO_DESKTOP != 0,
+            primary: output.StateFlags & DISPLAY_DEVICE_PRIMARY_DEVICE != 0,
+        });
+    }
+
+    Ok(devices)
+}
+
+/// set_process_dpi_aware() -> bool
+///
+/// Make the process aware of the DPI scaling of each monitor.
+///
+/// Processes that are not DPI-aware see scaled, virtualized coordinates on displays with a scale
+/// factor other than 100%. Monitor sizes and window rectangles then do not match the pixels of the
+/// captured frames. Call this function once at startup, before any windows are created. The DPI
+/// awareness of a process can only be set once, so the call fails if it was already set, e.g. by
+/// the application manifest or another library.
+///
+/// Returns:
+///     True if the process is now per-monitor DPI-aware, False if the awareness could not be set.
+#[pyfunction]
+pub fn set_process_dpi_aware() -> bool {
+    unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) }.is_ok()
+}
+
+/// DisplayChangeListener
+/// Listener for display configuration changes.
+///
+/// DisplayChangeListener is returned by :func:`on_display_change`. The callback keeps being invoked
+/// until :meth:`stop` is called or the listener is garbage collected.
+#[pyclass]
+pub struct DisplayChangeListener {
+    thread: Option<JoinHandle<()>>,
+    thread_id: Option<u32>,
+}
+
+#[pymethods]
+impl DisplayChangeListener {
+    /// Stop listening for display changes.
+    pub fn stop(&mut self, py: Python) {
+        if let Some(thread_id) = self.thread_id.take() {
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+        // The callback may be waiting for the GIL, so we release it while joining the thread
+        if let Some(thread) = self.thread.take() {
+            let _ = py.allow_threads(|| thread.join());
+        }
+    }
+
+    /// :``bool``: True if the listener is running, False otherwise.
+    #[getter]
+    pub fn active(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+}
+
+impl Drop for DisplayChangeListener {
+    fn drop(&mut self) {
+        // Joining could deadlock with a callback waiting for the GIL, so the thread is detached
+        if let Some(thread_id) = self.thread_id.take() {
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+    }
+}
+
+// Window procedure of the hidden display listener window
+unsafe extern "system" fn display_change_proc(
+    window: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if message == WM_DISPLAYCHANGE {
+        let callback = GetWindowLongPtrW(window, GWLP_USERDATA) as *const PyObject;
+        if let Some(callback) = callback.as_ref() {
+            Python::with_gil(|py| {
+                if let Err(error) = callback.call0(py) {
+                    error.print(py);
+                }
+            });
+        }
+    }
+    DefWindowProcW(window, message, wparam, lparam)
+}
+
+/// Create the hidden top-level window that receives display change broadcasts.
+///
+/// Message-only windows do not receive broadcast messages, so a regular window that is never shown
+/// is used instead.
+fn create_listener_window() -> windows::core::Result<HWND> {
+    let instance = unsafe { GetModuleHandleW(None)? };
+    let class_name = w!("pixel_forge_display_listener");
+    let window_class = WNDCLASSW {
+        lpfnWndProc: Some(display_change_proc),
+        hInstance: instance.into(),
+        lpszClassName: class_name,
+        ..Default::default()
+    };
+    // Registration fails if an earlier listener already registered the class, which is fine
+    unsafe { RegisterClassW(&window_class) };
+
+    let window = unsafe {
+        CreateWindowExW(
+            WS_EX_TOOLWINDOW,
+            class_name,
+            w!(""),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            instance,
+            None,
+        )
+    };
+    if window.0 == 0 {
+        return Err(windows::core::Error::from_win32());
+    }
+    Ok(window)
+}
+
+/// on_display_change(callback: Callable[[], None]) -> DisplayChangeListener
+///
+/// Invoke a callback whenever the display configuration changes.
+///
+/// The callback is called without arguments from a dedicated listener thread when monitors are
+/// added, removed or change their resolution. Previously created :class:`.Monitor` objects may be
+/// stale afterwards, so the callback should re-enumerate the monitors with
+/// :func:`enumerate_monitors`.
+///
+/// Args:
+///     callback: The callable to invoke on display changes.
+///
+/// Returns:
+///     The listener. Call :meth:`DisplayChangeListener.stop` to stop listening.
+#[pyfunction]
+pub fn on_display_change(py: Python, callback: PyObject) -> PyResult<DisplayChangeListener> {
+    if !callback.as_ref(py).is_callable() {
+        return Err(PyTypeError::new_err("callback must be callable"));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let thread = thread::Builder::new()
+        .name("pixel_forge-display-listener".to_string())
+        .spawn(move || {
+            let window = match create_listener_window() {
+                Ok(window) => window,
+                Err(error) => {
+                    let _ = sender.send(Err(error));
+                    return;
+                }
+            };
+            let callback = Box::into_raw(Box::new(callback));
+            unsafe { SetWindowLongPtrW(window, GWLP_USERDATA, callback as isize) };
+            let _ = sender.send(Ok(unsafe { GetCurrentThreadId() }));
+
+            let mut msg = MSG::default();
+            unsafe {
+                while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+                SetWindowLongPtrW(window, GWLP_USERDATA, 0);
+                let _ = DestroyWindow(window);
+                drop(Box::from_raw(callback));
+            }
+        })
+        .map_err(MonitorError::from)?;
+
+    let thread_id = receiver
+        .recv()
+        .map_err(|_| MonitorError::ListenerThreadError)?
+        .map_err(MonitorError::from)?;
+    Ok(DisplayChangeListener {
+        thread: Some(thread),
+        thread_id: Some(thread_id),
+    })
+}
+
+/// VirtualDesktop() -> VirtualDesktop
+/// The virtual desktop spanning all monitors.
+///
+/// VirtualDesktop can be used as capture target for the :class:`.Capture` class. Frames cover the
+/// bounding box of all monitors. Monitors to the left of or above the primary monitor have negative
+/// virtual desktop coordinates. They are shifted into the frame so that pixel (0, 0) of the frame
+/// corresponds to the virtual desktop point (:attr:`x`, :attr:`y`). Areas of the bounding box that
+/// are not covered by any monitor are black.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default)]
+#[pyclass]
+pub struct VirtualDesktop {}
+
+#[pymethods]
+impl VirtualDesktop {
+    #[new]
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn __hash__(&self) -> u64 {
+        0
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    /// :``int``: The left edge of the virtual desktop.
+    #[getter]
+    pub fn x(&self) -> i32 {
+        unsafe { GetSystemMetrics(SM_XVIRTUALSCREEN) }
+    }
+
+    /// :``int``: The top edge of the virtual desktop.
+    #[getter]
+    pub fn y(&self) -> i32 {
+        unsafe { GetSystemMetrics(SM_YVIRTUALSCREEN) }
+    }
+
+    /// :``int``: The pixel width of the virtual desktop.
+    #[getter]
+    pub fn width(&self) -> u32 {
+        unsafe { GetSystemMetrics(SM_CXVIRTUALSCREEN) }.unsigned_abs()
+    }
+
+    /// :``int``: The pixel height of the virtual desktop.
+    #[getter]
+    pub fn height(&self) -> u32 {
+        unsafe { GetSystemMetrics(SM_CYVIRTUALSCREEN) }.unsigned_abs()
+    }
+}
+
 // Implements TryFrom For Monitor To Convert It To GraphicsCaptureItem
 impl TryFrom<Monitor> for GraphicsCaptureItem {
     type Error = MonitorError;
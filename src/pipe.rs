@@ -0,0 +1,156 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+use pyo3::prelude::*;
+
+use crate::capture_utils::ColorFormat;
+use crate::errors;
+use crate::frame::{Frame, FrameError};
+
+// Paths of named pipes on the local machine start with this prefix
+const NAMED_PIPE_PREFIX: &str = r"\\.\pipe\";
+
+#[derive(thiserror::Error, Debug)]
+pub enum PipeError {
+    #[error("A pipe is already running")]
+    AlreadyPiping,
+    #[error("No pipe is running")]
+    NotPiping,
+    #[error("The frame rate of a pipe must be larger than zero")]
+    InvalidFrameRate,
+    #[error("Pipes only support the Rgba8 color format")]
+    UnsupportedColorFormat,
+    #[error("Failed to copy the frame to the CPU: {0}")]
+    FrameError(#[from] FrameError),
+    #[error("Failed to write to the pipe: {0}")]
+    IoError(#[from] io::Error),
+}
+
+impl From<PipeError> for PyErr {
+    fn from(error: PipeError) -> PyErr {
+        errors::PipeError::new_err(error.to_string())
+    }
+}
+
+/// Writer that streams raw frames to a file, a named pipe or stdout at a fixed frame rate.
+///
+/// Each frame is written as `height` rows of `width` RGBA pixels with one byte per channel, top
+/// row first and without padding or headers. This is the `rgba` pixel format of
+/// `ffmpeg -f rawvideo`. Frames are repeated or dropped according to their timestamps, so the
+/// stream has exactly one frame per interval of the frame rate.
+pub struct PipeWriter {
+    output: Box<dyn Write + Send>,
+    fps: u32,
+    width: u32,
+    height: u32,
+    start_time: Option<i64>,
+    // Number of frame slots that have been written since the first frame
+    written: u64,
+    // Pixels of the last written frame, repeated for slots without a new frame
+    last_frame: Vec<u8>,
+    // The first error while writing frames. Frames are written inside the frame handler, so
+    // errors are reported when the pipe is stopped
+    error: Option<PipeError>,
+}
+
+impl PipeWriter {
+    /// Create a writer for frames like `frame` that writes to `path`, or to stdout if `path` is
+    /// `-`.
+    ///
+    /// # Errors
+    ///
+    /// `PipeError::InvalidFrameRate`: `fps` is zero.
+    /// `PipeError::UnsupportedColorFormat`: The frame is not in the `Rgba8` format.
+    /// `PipeError::IoError`: The file or pipe could not be opened.
+    pub fn new(path: &str, fps: u32, frame: &Frame) -> Result<Self, PipeError> {
+        if fps == 0 {
+            return Err(PipeError::InvalidFrameRate);
+        }
+        if frame.color_format != ColorFormat::Rgba8 {
+            return Err(PipeError::UnsupportedColorFormat);
+        }
+        let output: Box<dyn Write + Send> = if path == "-" {
+            Box::new(io::stdout())
+        } else if path.starts_with(NAMED_PIPE_PREFIX) {
+            // Clients can only open existing named pipes
+            Box::new(OpenOptions::new().write(true).open(path)?)
+        } else {
+            Box::new(File::create(path)?)
+        };
+        let (width, height) = frame.output_size();
+        Ok(Self {
+            output,
+            fps,
+            width,
+            height,
+            start_time: None,
+            written: 0,
+            last_frame: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Write a frame. Frames with a different size than the first frame are skipped.
+    ///
+    /// Errors are kept and returned by `finish`, so the capture continues if writing fails.
+    pub fn write(&mut self, frame: &Frame) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.try_write(frame) {
+            self.error = Some(error);
+        }
+    }
+
+    fn try_write(&mut self, frame: &Frame) -> Result<(), PipeError> {
+        if frame.output_size() != (self.width, self.height) {
+            return Ok(());
+        }
+        let start_time = *self.start_time.get_or_insert(frame.time);
+        let slot = frame_slot(frame.time - start_time, self.fps);
+        // The slot of the frame has already been written
+        if slot < self.written {
+            return Ok(());
+        }
+        let data = frame.materialize()?.packed_data();
+        while self.written < slot {
+            self.output.write_all(&self.last_frame)?;
+            self.written += 1;
+        }
+        self.output.write_all(&data)?;
+        self.written += 1;
+        self.last_frame = data;
+        Ok(())
+    }
+
+    /// Flush the output and return the first error that occurred while writing frames.
+    pub fn finish(mut self) -> Result<(), PipeError> {
+        let result = self.output.flush();
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(result?)
+    }
+}
+
+// Index of the frame slot at `elapsed` 100ns units after the first frame of a stream with `fps`
+// frames per second
+fn frame_slot(elapsed: i64, fps: u32) -> u64 {
+    u64::try_from(elapsed).unwrap_or(0) * u64::from(fps) / 10_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_slot_rounds_down() {
+        assert_eq!(frame_slot(0, 30), 0);
+        // One 30 fps slot lasts 333_333 units
+        assert_eq!(frame_slot(333_333, 30), 0);
+        assert_eq!(frame_slot(333_334, 30), 1);
+        assert_eq!(frame_slot(10_000_000, 30), 30);
+        // Frames with earlier timestamps than the first frame fall into the first slot
+        assert_eq!(frame_slot(-1, 30), 0);
+    }
+}
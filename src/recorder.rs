@@ -0,0 +1,232 @@
+use pyo3::prelude::*;
+
+use windows::core::{Interface, GUID, HSTRING};
+use windows::Win32::Foundation::{E_POINTER, FALSE, TRUE};
+use windows::Win32::Graphics::Direct3D11::{ID3D11Multithread, ID3D11Texture2D};
+use windows::Win32::Media::MediaFoundation::{
+    IMF2DBuffer, IMFAttributes, IMFByteStream, IMFDXGIDeviceManager, IMFSinkWriter,
+    MFCreateAttributes, MFCreateDXGIDeviceManager, MFCreateDXGISurfaceBuffer, MFCreateMediaType,
+    MFCreateSample, MFCreateSinkWriterFromURL, MFMediaType_Video, MFShutdown, MFStartup,
+    MFVideoFormat_H264, MFVideoInterlace_Progressive, MFSTARTUP_FULL, MF_MT_AVG_BITRATE,
+    MF_MT_FRAME_RATE, MF_MT_FRAME_SIZE, MF_MT_INTERLACE_MODE, MF_MT_MAJOR_TYPE,
+    MF_MT_PIXEL_ASPECT_RATIO, MF_MT_SUBTYPE, MF_READWRITE_ENABLE_HARDWARE_TRANSFORMS,
+    MF_SINK_WRITER_D3D_MANAGER, MF_VERSION,
+};
+use windows::Win32::System::Com::{CoDecrementMTAUsage, CoIncrementMTAUsage, CO_MTA_USAGE_COOKIE};
+use windows_result::Error as WindowsError;
+
+use crate::capture_utils::ColorFormat;
+use crate::errors;
+use crate::frame::{Frame, FrameError};
+
+// MFVideoFormat_ABGR32 matches DXGI_FORMAT_R8G8B8A8_UNORM, but is missing from the bindings
+const MF_VIDEO_FORMAT_ABGR32: GUID = GUID::from_u128(0x00000020_0000_0010_8000_00aa00389b71);
+
+#[derive(thiserror::Error, Debug)]
+pub enum RecorderError {
+    #[error("A recording is already running")]
+    AlreadyRecording,
+    #[error("No recording is running")]
+    NotRecording,
+    #[error("The frame rate of a recording must be larger than zero")]
+    InvalidFrameRate,
+    #[error("Recordings only support the Rgba8 color format")]
+    UnsupportedColorFormat,
+    #[error("Failed to copy the frame for encoding: {0}")]
+    FrameError(#[from] FrameError),
+    #[error("Windows API error: {0}")]
+    WindowsError(#[from] windows::core::Error),
+}
+
+impl From<RecorderError> for PyErr {
+    fn from(error: RecorderError) -> PyErr {
+        errors::RecorderError::new_err(error.to_string())
+    }
+}
+
+/// Encoder that writes captured frames into an H.264 MP4 file.
+///
+/// Frames stay on the GPU. They are copied into a new texture and handed to the Media Foundation
+/// sink writer, which converts and encodes them with the hardware encoder if one is available.
+pub struct Recorder {
+    writer: IMFSinkWriter,
+    // The sink writer keeps using the device manager, so it has to outlive the writer
+    _device_manager: IMFDXGIDeviceManager,
+    // Keeps the multithreaded apartment alive for the Media Foundation objects. Recordings are
+    // created from arbitrary threads that may not have initialized COM
+    mta_cookie: CO_MTA_USAGE_COOKIE,
+    stream_index: u32,
+    width: u32,
+    height: u32,
+    frame_duration: i64,
+    start_time: Option<i64>,
+    // The first error while writing frames. Encoding runs inside the frame handler, so errors are
+    // reported when the recording is finished
+    error: Option<RecorderError>,
+}
+
+// Media Foundation objects are free-threaded. The recorder is only accessed behind a mutex
+unsafe impl Send for Recorder {}
+
+impl Recorder {
+    /// Create a recorder that writes frames like `frame` to the MP4 file at `path`.
+    ///
+    /// H.264 requires even frame dimensions, so odd frame sizes lose their last row or column.
+    ///
+    /// # Errors
+    ///
+    /// `RecorderError::InvalidFrameRate`: `fps` is zero.
+    /// `RecorderError::UnsupportedColorFormat`: The frame is not in the `Rgba8` format.
+    pub fn new(path: &str, fps: u32, frame: &Frame) -> Result<Self, RecorderError> {
+        if fps == 0 {
+            return Err(RecorderError::InvalidFrameRate);
+        }
+        if frame.color_format != ColorFormat::Rgba8 {
+            return Err(RecorderError::UnsupportedColorFormat);
+        }
+        let width = frame.width & !1;
+        let height = frame.height & !1;
+
+        let mta_cookie = unsafe { CoIncrementMTAUsage()? };
+        if let Err(error) = unsafe { MFStartup(MF_VERSION, MFSTARTUP_FULL) } {
+            let _ = unsafe { CoDecrementMTAUsage(mta_cookie) };
+            return Err(error.into());
+        }
+        match Self::create_writer(path, fps, width, height, frame) {
+            Ok((writer, device_manager, stream_index)) => Ok(Self {
+                writer,
+                _device_manager: device_manager,
+                mta_cookie,
+                stream_index,
+                width,
+                height,
+                frame_duration: 10_000_000 / i64::from(fps),
+                start_time: None,
+                error: None,
+            }),
+            Err(error) => {
+                let _ = unsafe { MFShutdown() };
+                let _ = unsafe { CoDecrementMTAUsage(mta_cookie) };
+                Err(error)
+            }
+        }
+    }
+
+    // Create the sink writer with an H.264 output stream that accepts textures of the frame device
+    fn create_writer(
+        path: &str,
+        fps: u32,
+        width: u32,
+        height: u32,
+        frame: &Frame,
+    ) -> Result<(IMFSinkWriter, IMFDXGIDeviceManager, u32), RecorderError> {
+        // Media Foundation uses the device from its own threads
+        let multithread: ID3D11Multithread = frame.device().cast()?;
+        unsafe { multithread.SetMultithreadProtected(TRUE) };
+
+        let mut reset_token = 0;
+        let mut device_manager = None;
+        unsafe { MFCreateDXGIDeviceManager(&mut reset_token, &mut device_manager)? };
+        // Success without an object is reported as an error, so `new` releases Media Foundation
+        let device_manager = device_manager.ok_or_else(|| WindowsError::from(E_POINTER))?;
+        unsafe { device_manager.ResetDevice(frame.device(), reset_token)? };
+
+        let mut attributes = None;
+        unsafe { MFCreateAttributes(&mut attributes, 2)? };
+        let attributes: IMFAttributes = attributes.ok_or_else(|| WindowsError::from(E_POINTER))?;
+        unsafe {
+            attributes.SetUINT32(&MF_READWRITE_ENABLE_HARDWARE_TRANSFORMS, 1)?;
+            attributes.SetUnknown(&MF_SINK_WRITER_D3D_MANAGER, &device_manager)?;
+        }
+        let writer = unsafe {
+            MFCreateSinkWriterFromURL(&HSTRING::from(path), None::<&IMFByteStream>, &attributes)?
+        };
+
+        let frame_size = (u64::from(width) << 32) | u64::from(height);
+        let frame_rate = (u64::from(fps) << 32) | 1;
+        // Roughly 0.1 bits per pixel, which gives good quality for screen content
+        let bitrate = u64::from(width) * u64::from(height) * u64::from(fps) / 10;
+        let output_type = unsafe { MFCreateMediaType()? };
+        unsafe {
+            output_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+            output_type.SetGUID(&MF_MT_SUBTYPE, &MFVideoFormat_H264)?;
+            output_type.SetUINT32(
+                &MF_MT_AVG_BITRATE,
+                u32::try_from(bitrate).unwrap_or(u32::MAX),
+            )?;
+            output_type.SetUINT32(&MF_MT_INTERLACE_MODE, MFVideoInterlace_Progressive.0 as u32)?;
+            output_type.SetUINT64(&MF_MT_FRAME_SIZE, frame_size)?;
+            output_type.SetUINT64(&MF_MT_FRAME_RATE, frame_rate)?;
+            output_type.SetUINT64(&MF_MT_PIXEL_ASPECT_RATIO, (1 << 32) | 1)?;
+        }
+        let stream_index = unsafe { writer.AddStream(&output_type)? };
+
+        let input_type = unsafe { MFCreateMediaType()? };
+        unsafe {
+            input_type.SetGUID(&MF_MT_MAJOR_TYPE, &MFMediaType_Video)?;
+            input_type.SetGUID(&MF_MT_SUBTYPE, &MF_VIDEO_FORMAT_ABGR32)?;
+            input_type.SetUINT32(&MF_MT_INTERLACE_MODE, MFVideoInterlace_Progressive.0 as u32)?;
+            input_type.SetUINT64(&MF_MT_FRAME_SIZE, frame_size)?;
+            input_type.SetUINT64(&MF_MT_FRAME_RATE, frame_rate)?;
+            input_type.SetUINT64(&MF_MT_PIXEL_ASPECT_RATIO, (1 << 32) | 1)?;
+            writer.SetInputMediaType(stream_index, &input_type, None::<&IMFAttributes>)?;
+            writer.BeginWriting()?;
+        }
+
+        Ok((writer, device_manager, stream_index))
+    }
+
+    /// Encode a frame. Frames with a different size than the recording are skipped.
+    ///
+    /// Errors are kept and returned by `finish`, so the capture continues if encoding fails.
+    pub fn write(&mut self, frame: &Frame) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(error) = self.try_write(frame) {
+            self.error = Some(error);
+        }
+    }
+
+    fn try_write(&mut self, frame: &Frame) -> Result<(), RecorderError> {
+        if frame.width & !1 != self.width || frame.height & !1 != self.height {
+            return Ok(());
+        }
+        // The capture textures are reused by the frame pool, so the encoder gets its own copy
+        let texture = frame.copy_to_texture(self.width, self.height)?;
+        // Frame times and sample times are both in 100ns units
+        let start_time = *self.start_time.get_or_insert(frame.time);
+
+        unsafe {
+            let buffer = MFCreateDXGISurfaceBuffer(&ID3D11Texture2D::IID, &texture, 0, FALSE)?;
+            let length = buffer.cast::<IMF2DBuffer>()?.GetContiguousLength()?;
+            buffer.SetCurrentLength(length)?;
+            let sample = MFCreateSample()?;
+            sample.AddBuffer(&buffer)?;
+            sample.SetSampleTime(frame.time - start_time)?;
+            sample.SetSampleDuration(self.frame_duration)?;
+            self.writer.WriteSample(self.stream_index, &sample)?;
+        }
+        Ok(())
+    }
+
+    /// Finish the file and return the first error that occurred while writing frames.
+    pub fn finish(self) -> Result<(), RecorderError> {
+        let result = unsafe { self.writer.Finalize() };
+        let Self {
+            writer,
+            _device_manager,
+            mta_cookie,
+            error,
+            ..
+        } = self;
+        // Release the Media Foundation objects before shutting it down
+        drop((writer, _device_manager));
+        let _ = unsafe { MFShutdown() };
+        let _ = unsafe { CoDecrementMTAUsage(mta_cookie) };
+        if let Some(error) = error {
+            return Err(error);
+        }
+        Ok(result?)
+    }
+}
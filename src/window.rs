@@ -1,23 +1,42 @@
 // This code has been adapted from https://github.com/NiiightmareXD/windows-capture
 
-use std::ptr;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::num::TryFromIntError;
 use std::string::FromUtf16Error;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::{mem, ptr};
 
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
-use windows::core::HSTRING;
+use windows::core::{w, HSTRING, PWSTR};
 use windows::Graphics::Capture::GraphicsCaptureItem;
-use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT, TRUE};
-use windows::Win32::Graphics::Gdi::{MonitorFromWindow, MONITOR_DEFAULTTONULL};
-use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::Foundation::{
+    CloseHandle, BOOL, HMODULE, HWND, LPARAM, POINT, RECT, TRUE, WPARAM,
+};
+use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+use windows::Win32::Graphics::Gdi::{ClientToScreen, MonitorFromWindow, MONITOR_DEFAULTTONULL};
+use windows::Win32::System::Threading::{
+    GetCurrentProcessId, GetCurrentThreadId, OpenProcess, QueryFullProcessImageNameW,
+    PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
 use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
 use windows::Win32::UI::WindowsAndMessaging::{
-    EnumChildWindows, FindWindowW, GetClientRect, GetDesktopWindow, GetForegroundWindow,
-    GetWindowLongPtrW, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
-    IsWindowVisible, GWL_EXSTYLE, GWL_STYLE, WS_CHILD, WS_EX_TOOLWINDOW,
+    DispatchMessageW, EnumChildWindows, EnumWindows, FindWindowExW, FindWindowW, GetAncestor,
+    GetClassNameW, GetClientRect, GetDesktopWindow, GetForegroundWindow, GetMessageW, GetTopWindow,
+    GetWindow, GetWindowDisplayAffinity, GetWindowLongPtrW, GetWindowRect, GetWindowTextLengthW,
+    GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindowVisible, PostThreadMessageW,
+    SendMessageTimeoutW, SetForegroundWindow, ShowWindow, TranslateMessage, CHILDID_SELF,
+    EVENT_OBJECT_NAMECHANGE, GA_ROOT, GWL_EXSTYLE, GWL_STYLE, GW_HWNDNEXT, MSG, OBJID_WINDOW,
+    SMTO_NORMAL, SW_RESTORE, WDA_NONE, WINEVENT_OUTOFCONTEXT, WM_QUIT, WS_CHILD, WS_EX_TOOLWINDOW,
 };
 
+use crate::capture_utils::spawn_future;
+use crate::errors;
 use crate::monitor::Monitor;
 
 #[derive(thiserror::Error, Debug)]
@@ -28,22 +47,51 @@ pub enum WindowError {
     NotFound(String),
     #[error("Failed to convert windows string '{0}' from UTF-16")]
     FailedToConvertWindowsString(#[from] FromUtf16Error),
+    #[error("Window handle must not be null")]
+    InvalidHandle,
+    #[error("Failed to bring the window to the foreground")]
+    ActivationFailed,
+    #[error("Failed to get the client area of the window")]
+    ClientAreaError,
+    #[error("Failed to install the title change hook")]
+    HookFailed,
+    #[error("Failed to spawn title change listener thread: {0}")]
+    ListenerSpawnError(#[from] std::io::Error),
+    #[error("Title change listener thread exited unexpectedly")]
+    ListenerThreadError,
+    #[error("Failed to resolve the desktop wallpaper window on the current shell")]
+    WallpaperNotFound,
+    #[error("Failed to convert size: {0}")]
+    SizeConversionError(#[from] TryFromIntError),
     #[error("Windows API error: {0}")]
     WindowsError(#[from] windows::core::Error),
 }
 
 impl From<WindowError> for PyErr {
     fn from(error: WindowError) -> PyErr {
-        PyRuntimeError::new_err(error.to_string())
+        match error {
+            WindowError::NoActiveWindow
+            | WindowError::NotFound(_)
+            | WindowError::WallpaperNotFound => {
+                errors::WindowNotFoundError::new_err(error.to_string())
+            }
+            _ => errors::PixelForgeError::new_err(error.to_string()),
+        }
     }
 }
-/// Window(name: str) -> Window
+
+// Time between two lookups while waiting for a window to appear
+const FIND_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Window(name: str, timeout_ms: int | None = None) -> Window
 /// Window abstraction for the Windows operating system.
 ///
 /// Windows can be used as capture target for the :class:`.Capture` class.
 ///
 /// Args:
 ///     name: The name of the window.
+///     timeout_ms: Waits up to this many milliseconds for the window to appear, e.g. for an
+///         application that was just launched. If None, the window has to exist already.
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
 #[pyclass]
 pub struct Window {
@@ -52,11 +100,13 @@ pub struct Window {
 
 #[pymethods]
 impl Window {
-    /// from_name(name: str) -> Window
+    /// from_name(name: str, timeout_ms: int | None = None) -> Window
     /// Create a :class:`.Window` instance from its name.
     ///
     /// Args:
     ///     name: The name of the window.
+    ///     timeout_ms: Retries the lookup until the window appears or the timeout in milliseconds
+    ///         elapses. If None, the lookup fails immediately if the window does not exist.
     ///
     /// Returns:
     ///    The window instance.
@@ -64,21 +114,44 @@ impl Window {
     /// Raises:
     ///    NotFound: The window with the given name was not found.
     #[new]
-    pub fn new(name: &str) -> Result<Window, WindowError> {
-        let hstring_name = HSTRING::from(name);
-        let window_handle = unsafe { FindWindowW(None, &hstring_name) };
+    #[pyo3(signature = (name, timeout_ms=None))]
+    pub fn new(py: Python, name: &str, timeout_ms: Option<u64>) -> Result<Window, WindowError> {
+        Window::wait_for(py, name, false, timeout_ms.unwrap_or(0))
+    }
 
-        if window_handle.0 == 0 {
-            return Err(WindowError::NotFound(String::from(name)));
+    /// from_hwnd(handle: int) -> Window
+    /// Create a :class:`.Window` instance from a raw window handle (HWND).
+    ///
+    /// This allows wrapping handles obtained from other libraries, e.g. pywin32.
+    ///
+    /// Args:
+    ///     handle: The raw window handle as integer.
+    ///
+    /// Returns:
+    ///    The window instance.
+    ///
+    /// Raises:
+    ///    InvalidHandle: The handle is zero.
+    #[staticmethod]
+    pub fn from_hwnd(handle: isize) -> Result<Window, WindowError> {
+        if handle == 0 {
+            return Err(WindowError::InvalidHandle);
         }
+        Ok(Window::from_handle(HWND(handle)))
+    }
 
-        Ok(Window { window_handle })
+    fn __hash__(&self) -> u64 {
+        self.window_handle.0 as u64
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
     }
 
     /// :``bool``: True if the window is still valid (i.e., open), else False.
     #[getter]
     pub fn valid(&self) -> bool {
-        if !unsafe { IsWindowVisible(self.window_handle).as_bool() } {
+        if !self.is_visible() {
             return false;
         }
 
@@ -94,10 +167,11 @@ impl Window {
             let styles = unsafe { GetWindowLongPtrW(self.window_handle, GWL_STYLE) };
             let ex_styles = unsafe { GetWindowLongPtrW(self.window_handle, GWL_EXSTYLE) };
 
-            if (ex_styles & isize::try_from(WS_EX_TOOLWINDOW.0).unwrap()) != 0 {
+            // Window styles are 32 bit flags, so truncating the returned values is lossless
+            if (ex_styles as u32 & WS_EX_TOOLWINDOW.0) != 0 {
                 return false;
             }
-            if (styles & isize::try_from(WS_CHILD.0).unwrap()) != 0 {
+            if (styles as u32 & WS_CHILD.0) != 0 {
                 return false;
             }
         } else {
@@ -107,29 +181,264 @@ impl Window {
         true
     }
 
-    /// :``str``: The name string of the window.
+    /// :``bool``: True if the window is visible, else False.
     #[getter]
-    pub fn name(&self) -> Result<String, WindowError> {
-        let len = unsafe { GetWindowTextLengthW(self.window_handle) };
+    pub fn is_visible(&self) -> bool {
+        unsafe { IsWindowVisible(self.window_handle).as_bool() }
+    }
 
-        let mut name = vec![0u16; usize::try_from(len).unwrap() + 1];
-        if len >= 1 {
-            let copied = unsafe { GetWindowTextW(self.window_handle, &mut name) };
-            if copied == 0 {
-                return Ok(String::new());
-            }
+    /// :``bool``: True if the window is minimized, else False.
+    #[getter]
+    pub fn is_minimized(&self) -> bool {
+        unsafe { IsIconic(self.window_handle).as_bool() }
+    }
+
+    /// :``bool``: True if the window excludes itself from screen captures, else False.
+    ///
+    /// Protected windows capture as black frames. Hardware protected video content, e.g. DRM
+    /// streams, cannot be detected this way. Use :attr:`.Capture.is_protected` to detect it.
+    #[getter]
+    pub fn is_protected(&self) -> bool {
+        let mut affinity = WDA_NONE.0;
+        let result = unsafe { GetWindowDisplayAffinity(self.window_handle, &mut affinity) };
+        result.is_ok() && affinity != WDA_NONE.0
+    }
+
+    /// :``bool``: True if the window is the foreground window, else False.
+    #[getter]
+    pub fn is_foreground(&self) -> bool {
+        unsafe { GetForegroundWindow() == self.window_handle }
+    }
+
+    /// :``tuple[int, int, int, int]``: The client area of the window as (x, y, width, height).
+    ///
+    /// The position is relative to the top left corner of the captured frames of the window, so it
+    /// excludes the title bar and borders. Child windows are captured through their top-level
+    /// window, so their position is relative to the frames of the top-level window. The rectangle
+    /// is only exact if the process is DPI aware, see :func:`set_process_dpi_aware`.
+    #[getter]
+    pub fn client_rect(&self) -> Result<(i32, i32, u32, u32), WindowError> {
+        let frame_rect = self.root().frame_bounds()?;
+        let mut client_rect = RECT::default();
+        unsafe { GetClientRect(self.window_handle, &mut client_rect)? };
+        let mut origin = POINT::default();
+        if !unsafe { ClientToScreen(self.window_handle, &mut origin).as_bool() } {
+            return Err(WindowError::ClientAreaError);
         }
+        Ok((
+            origin.x - frame_rect.left,
+            origin.y - frame_rect.top,
+            client_rect.right.unsigned_abs(),
+            client_rect.bottom.unsigned_abs(),
+        ))
+    }
 
-        let name = String::from_utf16(
-            &name
-                .as_slice()
-                .iter()
-                .take_while(|ch| **ch != 0x0000)
-                .copied()
-                .collect::<Vec<_>>(),
-        )?;
+    /// :``tuple[int, int, int, int]``: The bounds of the window as (x, y, width, height).
+    ///
+    /// The bounds include the title bar and borders of the window. The position is in virtual
+    /// desktop coordinates, so it can be negative for windows on monitors left of or above the
+    /// primary monitor. Unlike :attr:`client_rect`, the rectangle includes the invisible resize
+    /// borders of the window. The rectangle is only exact if the process is DPI aware, see
+    /// :func:`set_process_dpi_aware`.
+    #[getter]
+    pub fn window_rect(&self) -> Result<(i32, i32, u32, u32), WindowError> {
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(self.window_handle, &mut rect)? };
+        Ok((
+            rect.left,
+            rect.top,
+            (rect.right - rect.left).unsigned_abs(),
+            (rect.bottom - rect.top).unsigned_abs(),
+        ))
+    }
+
+    /// monitor() -> Monitor | None
+    /// Get the monitor that has the largest area of intersection with the window.
+    ///
+    /// Returns:
+    ///     The monitor, or None if the window doesn't intersect with any monitor.
+    pub fn monitor(&self) -> Option<Monitor> {
+        let monitor = unsafe { MonitorFromWindow(self.window_handle, MONITOR_DEFAULTTONULL) };
+
+        if monitor.is_invalid() {
+            None
+        } else {
+            Some(Monitor::from_handle(monitor))
+        }
+    }
+
+    /// center() -> tuple[int, int]
+    /// Get the center of the client area of the window in virtual desktop coordinates.
+    ///
+    /// The point can be used to click into the window or to find the monitor that shows it with
+    /// :meth:`.Monitor.contains_point`. It is only exact if the process is DPI aware, see
+    /// :func:`set_process_dpi_aware`.
+    ///
+    /// Returns:
+    ///     The center as (x, y).
+    pub fn center(&self) -> Result<(i32, i32), WindowError> {
+        let mut client_rect = RECT::default();
+        unsafe { GetClientRect(self.window_handle, &mut client_rect)? };
+        let mut origin = POINT::default();
+        if !unsafe { ClientToScreen(self.window_handle, &mut origin).as_bool() } {
+            return Err(WindowError::ClientAreaError);
+        }
+        Ok((
+            origin.x + client_rect.right / 2,
+            origin.y + client_rect.bottom / 2,
+        ))
+    }
 
-        Ok(name)
+    /// child_windows() -> list[Window]
+    /// Enumerate the visible child windows of the window.
+    ///
+    /// Some applications render their content into a child window, e.g. an embedded browser
+    /// control. Child windows can be passed to :meth:`.Capture.start` like any other window. They
+    /// are captured through their top-level window and the frames are cropped to the client area
+    /// of the child window.
+    ///
+    /// Returns:
+    ///     A list of all visible child windows, including nested children.
+    pub fn child_windows(&self) -> Vec<Window> {
+        let mut windows: Vec<Window> = Vec::new();
+
+        // The return value of EnumChildWindows is not used, windows without children return FALSE
+        let _ = unsafe {
+            EnumChildWindows(
+                self.window_handle,
+                Some(enum_child_windows_callback),
+                LPARAM(ptr::addr_of_mut!(windows) as isize),
+            )
+        };
+
+        windows
+    }
+
+    /// activate()
+    /// Bring the window to the foreground and restore it if it is minimized.
+    ///
+    /// Windows restricts which processes may change the foreground window, so activation can fail
+    /// if another application currently holds the foreground lock.
+    ///
+    /// Raises:
+    ///    ActivationFailed: The window could not be brought to the foreground.
+    pub fn activate(&self) -> Result<(), WindowError> {
+        if self.is_minimized() {
+            self.restore();
+        }
+        if !unsafe { SetForegroundWindow(self.window_handle).as_bool() } {
+            return Err(WindowError::ActivationFailed);
+        }
+        Ok(())
+    }
+
+    /// on_title_change(callback: Callable[[str], None]) -> TitleChangeListener
+    /// Invoke a callback whenever the title of the window changes.
+    ///
+    /// The callback is called with the new title from a dedicated listener thread. Use it to keep
+    /// labels in sync or to notice that a target found by its title has to be looked up again.
+    ///
+    /// Args:
+    ///     callback: The callable to invoke with the new title.
+    ///
+    /// Returns:
+    ///     The listener. Call :meth:`TitleChangeListener.stop` to stop listening.
+    pub fn on_title_change(&self, py: Python, callback: PyObject) -> PyResult<TitleChangeListener> {
+        if !callback.as_ref(py).is_callable() {
+            return Err(PyTypeError::new_err("callback must be callable"));
+        }
+        // Only events of the thread that owns the window are of interest
+        let mut process_id = 0;
+        let window_thread_id =
+            unsafe { GetWindowThreadProcessId(self.window_handle, Some(&mut process_id)) };
+        if window_thread_id == 0 {
+            return Err(WindowError::InvalidHandle.into());
+        }
+
+        let window_handle = self.window_handle;
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("pixel_forge-title-listener".to_string())
+            .spawn(move || {
+                TITLE_LISTENER.set(Some((window_handle, callback)));
+                // Out-of-context hooks are called on this thread while it pumps messages
+                let hook = unsafe {
+                    SetWinEventHook(
+                        EVENT_OBJECT_NAMECHANGE,
+                        EVENT_OBJECT_NAMECHANGE,
+                        HMODULE::default(),
+                        Some(title_change_proc),
+                        process_id,
+                        window_thread_id,
+                        WINEVENT_OUTOFCONTEXT,
+                    )
+                };
+                if hook.is_invalid() {
+                    let _ = sender.send(Err(WindowError::HookFailed));
+                    return;
+                }
+                let _ = sender.send(Ok(unsafe { GetCurrentThreadId() }));
+
+                let mut msg = MSG::default();
+                unsafe {
+                    while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                        TranslateMessage(&msg);
+                        DispatchMessageW(&msg);
+                    }
+                    let _ = UnhookWinEvent(hook);
+                }
+                TITLE_LISTENER.take();
+            })
+            .map_err(WindowError::from)?;
+
+        let thread_id = receiver
+            .recv()
+            .map_err(|_| WindowError::ListenerThreadError)??;
+        Ok(TitleChangeListener {
+            thread: Some(thread),
+            thread_id: Some(thread_id),
+        })
+    }
+
+    /// :``str``: The name string of the window. Empty if the window has no name.
+    #[getter]
+    pub fn name(&self) -> Result<String, WindowError> {
+        // A length of zero is returned both for untitled windows and if the query failed
+        let len = unsafe { GetWindowTextLengthW(self.window_handle) };
+        let len = match usize::try_from(len) {
+            Ok(len) if len > 0 => len,
+            _ => return Ok(String::new()),
+        };
+
+        // The buffer needs space for the terminating null character
+        let mut name = vec![0u16; len + 1];
+        let copied = unsafe { GetWindowTextW(self.window_handle, &mut name) };
+        // The title can change between both calls, so we only trust the number of copied chars
+        let copied = usize::try_from(copied).unwrap_or(0).min(len);
+
+        Ok(String::from_utf16(&name[..copied])?)
+    }
+
+    /// :``str``: The name of the window class, e.g. to tell windows of the same process apart.
+    #[getter]
+    pub fn class_name(&self) -> Result<String, WindowError> {
+        // Class names are limited to 256 characters
+        let mut name = [0u16; 256];
+        let copied = unsafe { GetClassNameW(self.window_handle, &mut name) };
+        let copied = match usize::try_from(copied) {
+            Ok(copied) if copied > 0 => copied,
+            _ => return Err(WindowError::InvalidHandle),
+        };
+
+        Ok(String::from_utf16(&name[..copied])?)
+    }
+
+    /// :``int``: The ID of the process that created the window, or 0 if the window was destroyed.
+    #[getter]
+    pub fn process_id(&self) -> u32 {
+        let mut process_id = 0;
+        unsafe { GetWindowThreadProcessId(self.window_handle, Some(&mut process_id)) };
+        process_id
     }
 }
 
@@ -144,22 +453,81 @@ impl Window {
         Window { window_handle }
     }
 
-    /// Get the monitor that has the largest area of intersection with the window.
-    ///
-    /// # Returns
-    ///
-    /// `None` if the window doesn't intersect with any monitor.
-    #[must_use]
-    pub fn monitor(&self) -> Option<Monitor> {
-        let monitor = unsafe { MonitorFromWindow(self.window_handle, MONITOR_DEFAULTTONULL) };
+    /// Wait up to `timeout_ms` for a window with the given title to appear. If `match_class` is
+    /// set, windows whose class name equals `name` are accepted as well.
+    pub(crate) fn wait_for(
+        py: Python,
+        name: &str,
+        match_class: bool,
+        timeout_ms: u64,
+    ) -> Result<Window, WindowError> {
+        let hstring_name = HSTRING::from(name);
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        // Other Python threads keep running while we wait for the window
+        let window_handle = py.allow_threads(|| loop {
+            let mut window_handle = unsafe { FindWindowW(None, &hstring_name) };
+            if window_handle.0 == 0 && match_class {
+                window_handle = unsafe { FindWindowW(&hstring_name, None) };
+            }
+            if window_handle.0 != 0 || Instant::now() >= deadline {
+                break window_handle;
+            }
+            thread::sleep(FIND_RETRY_INTERVAL.min(deadline - Instant::now()));
+        });
 
-        if monitor.is_invalid() {
-            None
+        if window_handle.0 == 0 {
+            return Err(WindowError::NotFound(String::from(name)));
+        }
+
+        Ok(Window { window_handle })
+    }
+
+    /// Return the top-level window that contains the window, or the window itself if it is a
+    /// top-level window.
+    #[must_use]
+    pub fn root(&self) -> Window {
+        let root_handle = unsafe { GetAncestor(self.window_handle, GA_ROOT) };
+        if root_handle.0 == 0 {
+            *self
         } else {
-            Some(Monitor::from_handle(monitor))
+            Window {
+                window_handle: root_handle,
+            }
         }
     }
 
+    /// Return true if the window is a child window of another window.
+    #[must_use]
+    pub fn is_child(&self) -> bool {
+        self.root() != *self
+    }
+
+    /// Return the visible frame of the window in virtual desktop coordinates.
+    ///
+    /// Window captures cover this rectangle, which excludes the invisible resize borders that
+    /// `GetWindowRect` includes.
+    pub fn frame_bounds(&self) -> Result<RECT, WindowError> {
+        let mut frame_rect = RECT::default();
+        unsafe {
+            DwmGetWindowAttribute(
+                self.window_handle,
+                DWMWA_EXTENDED_FRAME_BOUNDS,
+                ptr::addr_of_mut!(frame_rect).cast(),
+                u32::try_from(mem::size_of::<RECT>())?,
+            )?;
+        };
+        Ok(frame_rect)
+    }
+
+    /// Restore the window if it is minimized or maximized.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the window was previously visible, `false` otherwise.
+    pub fn restore(&self) -> bool {
+        unsafe { ShowWindow(self.window_handle, SW_RESTORE).as_bool() }
+    }
+
     /// Return the window handle (HWND) of the window.
     #[must_use]
     pub const fn as_handle(&self) -> HWND {
@@ -179,31 +547,293 @@ unsafe extern "system" fn enum_windows_callback(window_handle: HWND, vec: LPARAM
     TRUE
 }
 
-/// enumerate_windows() -> list[Window]
+// Callback to enumerate the visible child windows of a window.
+unsafe extern "system" fn enum_child_windows_callback(window_handle: HWND, vec: LPARAM) -> BOOL {
+    let windows = &mut *(vec.0 as *mut Vec<Window>);
+
+    let window = Window { window_handle };
+    if window.is_visible() {
+        windows.push(window);
+    }
+
+    TRUE
+}
+
+/// enumerate_windows(skip_untitled: bool = False, z_order: bool = False) -> list[Window]
 ///
 /// Enumerate all windows that are currently available.
 ///
+/// Args:
+///     skip_untitled: Excludes windows with an empty name if True. Untitled windows are rarely
+///         useful capture targets.
+///     z_order: Returns the windows in z-order if True, with the topmost window first, e.g. to
+///         capture the window in front of all others. The order is a snapshot that changes as
+///         soon as another window is activated. If False, the order is unspecified.
+///
 /// Returns:
 ///     A list of all windows.
 ///
 /// Raises:
 ///    WindowError: Enumerating the windows has failed.
 #[pyfunction]
-pub fn enumerate_windows() -> Result<Vec<Window>, WindowError> {
+#[pyo3(signature = (skip_untitled=false, z_order=false))]
+pub fn enumerate_windows(skip_untitled: bool, z_order: bool) -> Result<Vec<Window>, WindowError> {
+    let mut windows: Vec<Window> = Vec::new();
+
+    if z_order {
+        windows = z_ordered_windows();
+    } else {
+        unsafe {
+            EnumChildWindows(
+                GetDesktopWindow(),
+                Some(enum_windows_callback),
+                LPARAM(ptr::addr_of_mut!(windows) as isize),
+            )
+            .ok()?;
+        };
+    }
+    if skip_untitled {
+        windows.retain(|window| window.name().is_ok_and(|name| !name.is_empty()));
+    }
+
+    Ok(windows)
+}
+
+/// enumerate_windows_on_monitor(monitor: Monitor, skip_untitled: bool = False, z_order: bool = False) -> list[Window]
+///
+/// Enumerate the windows that are shown on a monitor.
+///
+/// Windows that span several monitors belong to the monitor with the largest area of
+/// intersection, see :meth:`Window.monitor`, so every window is listed for at most one monitor.
+/// Useful to group the windows of a picker by their display.
+///
+/// Args:
+///     monitor: The :class:`.Monitor` whose windows are returned.
+///     skip_untitled: Excludes windows with an empty name if True.
+///     z_order: Returns the windows in z-order if True, with the topmost window first. See
+///         :func:`enumerate_windows`.
+///
+/// Returns:
+///     A list of the windows on the monitor.
+///
+/// Raises:
+///    WindowError: Enumerating the windows has failed.
+#[pyfunction]
+#[pyo3(signature = (monitor, skip_untitled=false, z_order=false))]
+pub fn enumerate_windows_on_monitor(
+    monitor: Monitor,
+    skip_untitled: bool,
+    z_order: bool,
+) -> Result<Vec<Window>, WindowError> {
+    let mut windows = enumerate_windows(skip_untitled, z_order)?;
+    windows.retain(|window| window.monitor() == Some(monitor));
+    Ok(windows)
+}
+
+// Walk the top-level windows from the topmost to the bottommost window and keep the valid ones.
+// Child windows are never valid capture targets, so they do not have to be visited
+fn z_ordered_windows() -> Vec<Window> {
+    let mut windows = Vec::new();
+    let mut window_handle = unsafe { GetTopWindow(HWND::default()) };
+    while window_handle.0 != 0 {
+        let window = Window { window_handle };
+        if window.valid() {
+            windows.push(window);
+        }
+        window_handle = unsafe { GetWindow(window_handle, GW_HWNDNEXT) };
+    }
+    windows
+}
+
+/// enumerate_windows_async(skip_untitled: bool = False, z_order: bool = False) -> asyncio.Future[list[Window]]
+///
+/// Enumerate all windows that are currently available without blocking the event loop.
+///
+/// The enumeration runs in a background thread and the returned future resolves with the same
+/// list as :func:`enumerate_windows`.
+///
+/// .. note::
+///    This function has to be called from within a running asyncio event loop.
+///
+/// Args:
+///     skip_untitled: Excludes windows with an empty name if True.
+///     z_order: Returns the windows in z-order if True, with the topmost window first.
+///
+/// Returns:
+///     A future that resolves to a list of all windows.
+#[pyfunction]
+#[pyo3(signature = (skip_untitled=false, z_order=false))]
+pub fn enumerate_windows_async(
+    py: Python,
+    skip_untitled: bool,
+    z_order: bool,
+) -> PyResult<PyObject> {
+    spawn_future(py, move || Ok(enumerate_windows(skip_untitled, z_order)?))
+}
+
+/// enumerate_top_level_windows(skip_untitled: bool = False) -> list[Window]
+///
+/// Enumerate all top-level windows that are currently available.
+///
+/// Unlike :func:`enumerate_windows`, which walks all descendants of the desktop window, only
+/// top-level windows are visited. Both functions apply the same validity checks and return the
+/// same windows, because child windows are never valid capture targets. Windows are returned in
+/// z-order, with the topmost window first.
+///
+/// Args:
+///     skip_untitled: Excludes windows with an empty name if True. Untitled windows are rarely
+///         useful capture targets.
+///
+/// Returns:
+///     A list of all top-level windows.
+///
+/// Raises:
+///    WindowError: Enumerating the windows has failed.
+#[pyfunction]
+#[pyo3(signature = (skip_untitled=false))]
+pub fn enumerate_top_level_windows(skip_untitled: bool) -> Result<Vec<Window>, WindowError> {
     let mut windows: Vec<Window> = Vec::new();
 
     unsafe {
-        EnumChildWindows(
-            GetDesktopWindow(),
+        EnumWindows(
             Some(enum_windows_callback),
             LPARAM(ptr::addr_of_mut!(windows) as isize),
-        )
-        .ok()?;
+        )?;
     };
+    if skip_untitled {
+        windows.retain(|window| window.name().is_ok_and(|name| !name.is_empty()));
+    }
 
     Ok(windows)
 }
 
+/// enumerate_processes_with_windows() -> list[tuple[int, str | None, int]]
+///
+/// Enumerate the processes that have capturable windows.
+///
+/// The windows of :func:`enumerate_top_level_windows` are grouped by the process that owns them,
+/// which is the view a capture source picker or task manager needs. Processes are sorted by their
+/// ID.
+///
+/// Returns:
+///     A list of (pid, process_name, window_count) tuples. The process name is the file name of the
+///     executable, e.g. ``"notepad.exe"``, or None if the process denies access, like protected
+///     system processes do.
+///
+/// Raises:
+///    WindowError: Enumerating the windows has failed.
+#[pyfunction]
+pub fn enumerate_processes_with_windows() -> Result<Vec<(u32, Option<String>, usize)>, WindowError>
+{
+    let mut window_counts = BTreeMap::new();
+    for window in enumerate_top_level_windows(false)? {
+        *window_counts.entry(window.process_id()).or_insert(0) += 1;
+    }
+    Ok(window_counts
+        .into_iter()
+        .map(|(process_id, window_count)| (process_id, process_name(process_id), window_count))
+        .collect())
+}
+
+// Return the file name of the executable of a process, or None if the process cannot be queried
+fn process_name(process_id: u32) -> Option<String> {
+    let process =
+        unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }.ok()?;
+    // Long path names exceed MAX_PATH
+    let mut path = vec![0u16; 1024];
+    let mut len = u32::try_from(path.len()).ok()?;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(path.as_mut_ptr()),
+            &mut len,
+        )
+    };
+    let _ = unsafe { CloseHandle(process) };
+    result.ok()?;
+    let path = String::from_utf16(path.get(..usize::try_from(len).ok()?)?).ok()?;
+    path.rsplit('\\').next().map(str::to_owned)
+}
+
+/// TitleChangeListener
+/// Listener for title changes of a window.
+///
+/// TitleChangeListener is returned by :meth:`.Window.on_title_change`. The callback keeps being
+/// invoked until :meth:`stop` is called or the listener is garbage collected.
+#[pyclass]
+pub struct TitleChangeListener {
+    thread: Option<JoinHandle<()>>,
+    thread_id: Option<u32>,
+}
+
+#[pymethods]
+impl TitleChangeListener {
+    /// Stop listening for title changes.
+    pub fn stop(&mut self, py: Python) {
+        if let Some(thread_id) = self.thread_id.take() {
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+        // The callback may be waiting for the GIL, so we release it while joining the thread
+        if let Some(thread) = self.thread.take() {
+            let _ = py.allow_threads(|| thread.join());
+        }
+    }
+
+    /// :``bool``: True if the listener is running, False otherwise.
+    #[getter]
+    pub fn active(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+}
+
+impl Drop for TitleChangeListener {
+    fn drop(&mut self) {
+        // Joining could deadlock with a callback waiting for the GIL, so the thread is detached
+        if let Some(thread_id) = self.thread_id.take() {
+            let _ = unsafe { PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0)) };
+        }
+    }
+}
+
+thread_local! {
+    // Window and callback of the title change listener that runs on the current thread. WinEvent
+    // hooks cannot carry user data, but every listener has a thread of its own
+    static TITLE_LISTENER: RefCell<Option<(HWND, PyObject)>> = const { RefCell::new(None) };
+}
+
+// WinEvent hook procedure of the title change listener
+unsafe extern "system" fn title_change_proc(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    window: HWND,
+    object_id: i32,
+    child_id: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    // Name changes of other windows of the thread or of objects inside the window are ignored
+    if object_id != OBJID_WINDOW.0 || child_id != CHILDID_SELF as i32 {
+        return;
+    }
+    TITLE_LISTENER.with_borrow(|listener| {
+        let Some((target, callback)) = listener else {
+            return;
+        };
+        if *target != window {
+            return;
+        }
+        let title = Window::from_handle(window).name().unwrap_or_default();
+        Python::with_gil(|py| {
+            if let Err(error) = callback.call1(py, (title,)) {
+                error.print(py);
+            }
+        });
+    });
+}
+
 /// foreground_window() -> Window
 ///
 /// Get the currently active window.
@@ -224,12 +854,79 @@ pub fn foreground_window() -> Result<Window, WindowError> {
     Ok(Window { window_handle })
 }
 
+// Undocumented Progman message that spawns the WorkerW window hosting the wallpaper
+const SPAWN_WORKERW: u32 = 0x052C;
+
+// EnumWindows callback that finds the WorkerW window behind the window hosting the desktop icons
+unsafe extern "system" fn find_workerw_callback(window_handle: HWND, workerw: LPARAM) -> BOOL {
+    let workerw = &mut *(workerw.0 as *mut HWND);
+
+    let icons = FindWindowExW(window_handle, None, w!("SHELLDLL_DefView"), None);
+    if icons.0 != 0 {
+        *workerw = FindWindowExW(None, window_handle, w!("WorkerW"), None);
+    }
+
+    TRUE
+}
+
+/// wallpaper_window() -> Window
+///
+/// Get the window that hosts the desktop wallpaper.
+///
+/// Capturing this window yields the desktop background without the desktop icons and without any
+/// application windows on top of it.
+///
+/// Returns:
+///     The wallpaper window.
+///
+/// Raises:
+///     RuntimeError: If the wallpaper window can't be resolved on the current shell.
+#[pyfunction]
+pub fn wallpaper_window() -> Result<Window, WindowError> {
+    let progman = unsafe { FindWindowW(w!("Progman"), None) };
+    if progman.0 == 0 {
+        return Err(WindowError::WallpaperNotFound);
+    }
+    // Progman creates the WorkerW window on request. It is a no-op if the window already exists
+    unsafe {
+        SendMessageTimeoutW(
+            progman,
+            SPAWN_WORKERW,
+            WPARAM(0),
+            LPARAM(0),
+            SMTO_NORMAL,
+            1000,
+            None,
+        )
+    };
+
+    let mut workerw = HWND::default();
+    unsafe {
+        EnumWindows(
+            Some(find_workerw_callback),
+            LPARAM(ptr::addr_of_mut!(workerw) as isize),
+        )?;
+    };
+    // Newer shells parent the WorkerW window to Progman instead of placing it behind the icons
+    if workerw.0 == 0 {
+        workerw = unsafe { FindWindowExW(progman, None, w!("WorkerW"), None) };
+    }
+    if workerw.0 == 0 {
+        return Err(WindowError::WallpaperNotFound);
+    }
+
+    Ok(Window {
+        window_handle: workerw,
+    })
+}
+
 // Window to GraphicsCaptureItem conversion
 impl TryFrom<Window> for GraphicsCaptureItem {
     type Error = WindowError;
 
     fn try_from(value: Window) -> Result<Self, Self::Error> {
-        let window_handle = value.as_handle();
+        // Only top-level windows can be captured, child windows are cropped from their frames
+        let window_handle = value.root().as_handle();
         let interop = windows::core::factory::<Self, IGraphicsCaptureItemInterop>()?;
 
         Ok(unsafe { interop.CreateForWindow(window_handle)? })
@@ -1,6 +1,46 @@
+import asyncio
+import subprocess
+import sys
+import threading
+import time
+import warnings
+import zlib
+
 import numpy as np
 import pytest
-from pixel_forge import Capture, Monitor, primary_monitor
+from pixel_forge import (
+    ApartmentType,
+    Capture,
+    CaptureConfig,
+    CaptureGroup,
+    CaptureNotRunningError,
+    CaptureThreadError,
+    ColorFormat,
+    FeatureLevel,
+    FrameUnavailableError,
+    Monitor,
+    PipeError,
+    PixelForgeError,
+    ResizePolicy,
+    ThreadPriority,
+    VirtualDesktop,
+    Window,
+    WindowNotFoundError,
+    capture_size,
+    capture_when_available,
+    diagnostics,
+    enumerate_monitors,
+    enumerate_windows,
+    grab,
+    grab_monitors,
+    is_supported,
+    primary_monitor,
+    screenshot_foreground,
+    supported_formats,
+    supports_border_toggle,
+    supports_cursor_toggle,
+    windows_build,
+)
 
 
 @pytest.fixture(scope="session")
@@ -8,6 +48,40 @@ def monitor():
     yield primary_monitor()
 
 
+# Window of a separate process that toggles its size, because windows of the own process are never
+# valid capture targets
+RESIZING_WINDOW = """
+import tkinter
+root = tkinter.Tk()
+root.title('pixel_forge resizing window')
+sizes = ['400x300', '300x200']
+def toggle(i=0):
+    root.geometry(sizes[i % 2])
+    root.after(50, toggle, i + 1)
+toggle()
+root.mainloop()
+"""
+
+
+def open_resizing_window() -> tuple[subprocess.Popen, Window | None]:
+    process = subprocess.Popen([sys.executable, "-c", RESIZING_WINDOW])
+    deadline = time.monotonic() + 5
+    window = None
+    while window is None and time.monotonic() < deadline:
+        windows = [w for w in enumerate_windows() if w.name == "pixel_forge resizing window"]
+        window = windows[0] if windows else None
+        time.sleep(0.1)
+    return process, window
+
+
+@pytest.fixture
+def resizing_window():
+    process, window = open_resizing_window()
+    yield window
+    process.kill()
+    process.wait()
+
+
 def test_create_capture():
     c = Capture()
     assert c is not None
@@ -19,6 +93,41 @@ def test_capture_thread(monitor: Monitor):
     c.stop()
 
 
+def test_capture_error_types(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(CaptureNotRunningError):
+        c.frame()
+    with pytest.raises(PixelForgeError):
+        c.start()  # No capture target
+    c.start(monitor, await_first_frame=True)
+    with pytest.raises(PixelForgeError):
+        c.start(monitor)  # Already running
+    c.stop()
+
+
+def test_capture_double_start(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    with pytest.raises(RuntimeError):
+        c.start(monitor)
+    assert c.active
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+    c.start(monitor, await_first_frame=True)  # Starting again after stop is fine
+    c.stop()
+
+
+def test_capture_rapid_restart(monitor: Monitor):
+    # Stopping while frames arrive races the frame handler against the closing frame pool
+    c = Capture()
+    for _ in range(50):
+        c.start(monitor, await_first_frame=False, buffer_count=2)
+        c.stop()
+    c.start(monitor, await_first_frame=True)
+    assert c.frame() is not None
+    c.stop()
+
+
 def test_capture_frame(monitor: Monitor):
     c = Capture()
     c.start(monitor, await_first_frame=True)
@@ -28,6 +137,434 @@ def test_capture_frame(monitor: Monitor):
     assert frame.shape == (monitor.height, monitor.width, 4)
 
 
+def test_capture_frame_layout(monitor: Monitor):
+    c = Capture()
+    # A low frame rate keeps the latest frame unchanged between the two reads
+    c.start(monitor, await_first_frame=True, max_fps=1)
+    hwc = c.frame(layout="hwc")
+    chw = c.frame(layout="chw")
+    with pytest.raises(RuntimeError):
+        c.frame(layout="nchw")
+    c.stop()
+    assert chw.shape == (4, monitor.height, monitor.width)
+    assert chw.flags["C_CONTIGUOUS"], "Planar frames should be transposed during the copy"
+    assert np.array_equal(chw, hwc.transpose(2, 0, 1))
+
+
+def test_capture_flipped_frame(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, flip_vertical=True, flip_horizontal=True)
+    frame = c.frame()
+    contiguous_frame = c.frame_contiguous()
+    c.stop()
+    assert frame.shape == (monitor.height, monitor.width, 4)
+    assert contiguous_frame.flags["C_CONTIGUOUS"]
+
+
+def test_capture_scaled_frame(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, scale=0.3)
+    frame = c.frame()
+    c.stop()
+    assert frame.shape == (monitor.height // 2, monitor.width // 2, 4)
+    with pytest.raises(RuntimeError):
+        c.start(monitor, scale=0)
+
+
+def test_capture_target(monitor: Monitor):
+    c = Capture()
+    assert c.target is None
+    c.start(monitor, await_first_frame=False)
+    assert c.target == monitor
+    c.stop()
+    assert c.target is None
+
+
+def test_capture_target_kind(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    assert c.target_kind is None
+    c.start(monitor, await_first_frame=False)
+    assert c.target_kind == "monitor"
+    c.stop()
+    assert c.target_kind is None
+    c.start(resizing_window, await_first_frame=False)
+    assert c.target_kind == "window"
+    c.stop()
+    c.start(VirtualDesktop(), await_first_frame=False)
+    assert c.target_kind == "virtual_desktop"
+    c.stop()
+
+
+def test_capture_start_non_blocking(monitor: Monitor):
+    c = Capture()
+    t0 = time.perf_counter()
+    c.start(monitor, await_first_frame=False)
+    elapsed = time.perf_counter() - t0
+    c.stop()
+    # Starting creates the device and spawns the thread, but must not wait for the compositor
+    assert elapsed < 0.2, f"start blocked for {elapsed:.3f}s without await_first_frame"
+
+
+def test_capture_min_update_interval(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert not c.throttled_at_source, "No interval was set"
+    c.stop()
+    c.start(monitor, await_first_frame=True, min_update_interval_ms=100)
+    c.frame()
+    time.sleep(0.5)
+    assert c.stats().produced <= 10, "Frames must be limited with or without compositor support"
+    c.stop()
+
+
+def test_capture_recording(monitor: Monitor, tmp_path):
+    path = tmp_path / "capture.mp4"
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert not c.recording
+    with pytest.raises(RuntimeError):
+        c.stop_recording()
+    c.start_recording(str(path), fps=30)
+    assert c.recording
+    with pytest.raises(RuntimeError):
+        c.start_recording(str(path))
+    time.sleep(0.5)
+    c.stop_recording()
+    assert not c.recording
+    c.stop()
+    assert path.stat().st_size > 0
+
+
+def test_capture_pipe(monitor: Monitor, tmp_path):
+    path = tmp_path / "capture.raw"
+    c = Capture()
+    c.start(monitor, await_first_frame=True, fixed_interval_ms=20)
+    assert not c.piping
+    with pytest.raises(PipeError):
+        c.stop_pipe()
+    with pytest.raises(PipeError):
+        c.start_pipe(str(path), fps=0)
+    width, height = c.frame().shape[1::-1]
+    c.start_pipe(str(path), fps=10)
+    assert c.piping
+    with pytest.raises(PipeError):
+        c.start_pipe(str(path))
+    time.sleep(0.5)
+    c.stop_pipe()
+    assert not c.piping
+    c.stop()
+    size = path.stat().st_size
+    assert size > 0
+    assert size % (width * height * 4) == 0
+
+
+def test_capture_on_started(monitor: Monitor):
+    events = []
+    c = Capture()
+    c.start(monitor, await_first_frame=False, on_started=lambda: events.append("started"))
+    assert events == ["started"], "The callback runs once before start returns"
+    c.stop()
+    with pytest.raises(TypeError):
+        c.start(monitor, on_started=1)
+    assert not c.active
+
+    def fail():
+        raise ValueError("setup rejected")
+
+    with pytest.raises(ValueError):
+        c.start(monitor, on_started=fail)
+    assert not c.active, "A failing callback stops the capture"
+
+
+def test_capture_start_awaits_frame(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert c.width is not None, "A frame must be available once start returns"
+    c.frame()
+    c.stop()
+
+
+def test_capture_read(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.read()
+    c.start(monitor, await_first_frame=True)
+    data = c.read()
+    assert data.array.shape == (monitor.height, monitor.width, 4)
+    assert (data.width, data.height) == (monitor.width, monitor.height)
+    assert data.frame_id >= 1
+    assert data.timestamp > 0
+    array, timestamp, frame_id, width, height = data
+    assert array is data.array
+    assert (timestamp, frame_id, width, height) == (
+        data.timestamp,
+        data.frame_id,
+        data.width,
+        data.height,
+    )
+    assert len(data) == 5
+    c.stop()
+
+
+def test_capture_read_auto_crop(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert c.read().crop is None
+    data = c.read(auto_crop=True, tolerance=0)
+    x, y, width, height = data.crop
+    assert data.array.shape == (height, width, 4)
+    assert x + width <= data.width and y + height <= data.height
+    if (width, height) != (data.width, data.height):
+        assert np.any(data.array[0, :, :3] > 0) and np.any(data.array[-1, :, :3] > 0)
+        assert np.any(data.array[:, 0, :3] > 0) and np.any(data.array[:, -1, :3] > 0)
+    c.stop()
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba16Float)
+    with pytest.raises(PixelForgeError):
+        c.read(auto_crop=True)
+    c.stop()
+
+
+def test_capture_read_missed_since_last(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    data = c.read()
+    assert data.missed_since_last is None or data.missed_since_last >= 0
+    c.stop()
+    c.start(monitor, await_first_frame=True, fixed_interval_ms=50)
+    c.wait_for_frame(timeout_ms=1000)
+    assert c.read().missed_since_last is None, "Repeated frames have no delivery gap"
+    c.stop()
+
+
+def test_capture_is_protected(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert not c.is_protected, "No frame has been read yet"
+    frame = c.frame()
+    assert c.is_protected == (not frame[..., :3].any())
+    c.stop()
+
+
+@pytest.mark.parametrize(
+    "apartment_type", [ApartmentType.Multithreaded, ApartmentType.Singlethreaded]
+)
+def test_capture_apartment_type(monitor: Monitor, apartment_type: ApartmentType):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, apartment_type=apartment_type)
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+
+
+@pytest.mark.parametrize(
+    "thread_priority",
+    [ThreadPriority.BelowNormal, ThreadPriority.Normal, ThreadPriority.AboveNormal],
+)
+def test_capture_thread_priority(monitor: Monitor, thread_priority: ThreadPriority):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, thread_priority=thread_priority)
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+
+
+def test_capture_content_size(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    assert c.content_width is None and c.content_height is None
+    c.start(monitor, await_first_frame=True)
+    assert (c.content_width, c.content_height) == (c.width, c.height)
+    c.stop()
+    # Frames of a resizing window can be larger than the window content, but never smaller
+    c.start(resizing_window, await_first_frame=True)
+    deadline = time.monotonic() + 1
+    while time.monotonic() < deadline:
+        data = c.read()
+        assert data.content_width <= data.width and data.content_height <= data.height
+    assert c.resize_count > 0
+    c.stop()
+
+
+def test_capture_max_size(resizing_window: Window):
+    c = Capture()
+    c.start(resizing_window, await_first_frame=True, max_size=(4096, 4096))
+    deadline = time.monotonic() + 1
+    while time.monotonic() < deadline:
+        data = c.read()
+        assert (data.width, data.height) == (data.content_width, data.content_height)
+    assert c.resize_count > 0, "Resizes are counted without recreating the frame buffers"
+    c.stop()
+
+
+def test_capture_include_secondary_windows(resizing_window: Window):
+    c = Capture()
+    config = CaptureConfig(capture_target=resizing_window, include_secondary_windows=True)
+    if not windows_build().secondary_windows_supported:
+        with pytest.raises(PixelForgeError, match="secondary windows"):
+            c.start(config)
+        assert not c.active
+        return
+    c.start(config)
+    assert c.frame().shape == (c.height, c.width, 4)
+    c.stop()
+
+
+def test_capture_resize_policy(resizing_window: Window):
+    c = Capture()
+    assert c.resize_policy == ResizePolicy.Recreate
+    c.start(resizing_window, await_first_frame=True, resize_policy=ResizePolicy.Fixed)
+    assert c.resize_policy == ResizePolicy.Fixed
+    size = (c.width, c.height)
+    deadline = time.monotonic() + 1
+    while time.monotonic() < deadline:
+        data = c.read()
+        assert (data.width, data.height) == size, "Fixed captures keep their initial size"
+    assert c.resize_count == 0
+    c.stop()
+    closed = threading.Event()
+    c.start(
+        resizing_window,
+        await_first_frame=True,
+        resize_policy=ResizePolicy.Error,
+        on_target_closed=closed.set,
+    )
+    deadline = time.monotonic() + 2
+    while not c.resized and time.monotonic() < deadline:
+        time.sleep(0.05)
+    assert c.resized, "The resized window should end the capture"
+    assert not c.active
+    assert not c.closed, "The resized window is still open"
+    assert not closed.wait(timeout=0.5), "on_target_closed should not be called"
+    with pytest.raises(CaptureThreadError):
+        c.try_stop()
+
+
+def test_capture_unpremultiply_alpha(monitor: Monitor):
+    c = Capture()
+    assert c.alpha_mode == "premultiplied"
+    c.start(monitor, await_first_frame=True, unpremultiply_alpha=True)
+    assert c.alpha_mode == "straight"
+    frame = c.frame()
+    assert frame.dtype == np.uint8
+    c.stop()
+    with pytest.raises(PixelForgeError):
+        c.start(monitor, color_format=ColorFormat.Rgba16Float, unpremultiply_alpha=True)
+    assert not c.active
+
+
+@pytest.mark.parametrize("color_format", [ColorFormat.Rgba8, ColorFormat.Rgba16Float])
+def test_capture_channel_order(monitor: Monitor, color_format: ColorFormat):
+    c = Capture()
+    assert c.channel_order == "RGBA"
+    c.start(monitor, await_first_frame=True, color_format=color_format)
+    assert c.channel_order == "RGBA"
+    c.stop()
+
+
+@pytest.mark.parametrize(
+    "color_format, color_space", [(ColorFormat.Rgba8, "sRGB"), (ColorFormat.Rgba16Float, "scRGB")]
+)
+def test_capture_color_space(monitor: Monitor, color_format: ColorFormat, color_space: str):
+    c = Capture()
+    assert c.color_space == "sRGB"
+    c.start(monitor, await_first_frame=True, color_format=color_format)
+    assert c.color_space == color_space
+    c.stop()
+
+
+@pytest.mark.parametrize("color_format", [ColorFormat.Rgba8, ColorFormat.Rgba16Float])
+def test_capture_frame_spec(monitor: Monitor, color_format: ColorFormat):
+    c = Capture()
+    assert c.frame_spec() == ("uint8", 4)
+    c.start(monitor, await_first_frame=True, color_format=color_format)
+    dtype, channels = c.frame_spec()
+    frame = c.frame()
+    assert frame.dtype == np.dtype(dtype)
+    assert frame.shape[2] == channels
+    c.stop()
+
+
+def test_capture_client_region(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.start(monitor, client_region=(0, 0, 10, 10))
+    with pytest.raises(RuntimeError):
+        c.start(resizing_window, client_region=(0, 0, 10_000, 10))
+    with pytest.raises(RuntimeError):
+        c.start(resizing_window, client_region=(0, 0, 0, 10))
+    # The region fits into the client area of both window sizes
+    c.start(resizing_window, await_first_frame=True, client_region=(10, 20, 100, 50))
+    assert c.frame().shape == (50, 100, 4)
+    c.stop()
+
+
+def test_capture_client_region_readers(resizing_window: Window):
+    c = Capture()
+    c.start(resizing_window, await_first_frame=True, client_region=(10, 20, 100, 50))
+    data = c.read()
+    assert data.array.shape == (50, 100, 4)
+    assert (data.width, data.height) == (100, 50)
+    crop = c.read(auto_crop=True).crop
+    assert crop[0] + crop[2] <= 100 and crop[1] + crop[3] <= 50, "Crops lie inside the region"
+    assert c.frame_contiguous().shape == (50, 100, 4)
+    assert c.frame_contiguous().flags["C_CONTIGUOUS"]
+    data, width, height, stride = c.frame_bytes()
+    assert (width, height, stride) == (100, 50, 400)
+    assert len(data) == 400 * 50
+
+    async def next_frame():
+        return await asyncio.wait_for(c.frame_async(), timeout=5)
+
+    assert asyncio.run(next_frame()).shape == (50, 100, 4)
+    # Views reference the mapped frame, so they are not cropped
+    assert c.frame_view().shape != (50, 100, 4)
+    c.stop()
+
+
+def test_capture_frame_on_monitor(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    with pytest.raises(RuntimeError):
+        c.frame_on_monitor(monitor)
+    c.stop()
+    c.start(resizing_window, await_first_frame=True)
+    window_monitor = resizing_window.monitor()
+    frame = c.frame_on_monitor(window_monitor)
+    assert frame.ndim == 3
+    assert frame.shape[0] > 0 and frame.shape[1] > 0
+    assert c.frame_on_monitor(window_monitor, layout="chw").shape[0] == 4
+    c.stop()
+
+
+def test_capture_desktop_to_frame(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    # The primary monitor has its top left corner at the origin of the virtual desktop
+    assert c.desktop_to_frame(0, 0) == (0, 0)
+    assert c.desktop_to_frame(-1, -1) is None
+    c.stop()
+    c.start(monitor, await_first_frame=True, flip_horizontal=True)
+    assert c.desktop_to_frame(0, 0) == (c.width - 1, 0)
+    c.stop()
+    c.start(resizing_window, await_first_frame=True)
+    with pytest.raises(RuntimeError):
+        c.desktop_to_frame(0, 0)
+    c.stop()
+
+
+def test_capture_stale_frame_warning(monitor: Monitor):
+    c = Capture()
+    assert c.stale_frame_threshold_ms is None
+    c.start(monitor, await_first_frame=True)
+    c.stale_frame_threshold_ms = 0.0
+    with pytest.warns(RuntimeWarning):
+        c.frame()
+    with pytest.warns(RuntimeWarning):
+        c.read()
+    c.stale_frame_threshold_ms = 60_000.0
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        c.frame()
+    c.stop()
+
+
 def test_capture_active(monitor: Monitor):
     c = Capture()
     assert not c.active, "Capture should not be running"
@@ -46,3 +583,892 @@ def test_capture_valid_frame(monitor: Monitor):
     assert frame.shape[0] > 0, "Frame must not be empty"
     c.stop()
     assert not c.active, "Capture should not be running"
+
+
+def test_capture_max_fps(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, max_fps=15)
+    frame = c.frame()
+    c.stop()
+    assert frame.shape == (monitor.height, monitor.width, 4)
+
+
+# Window of a separate process that changes its color in quick succession and then stays red
+SETTLING_WINDOW = """
+import tkinter
+root = tkinter.Tk()
+root.title('pixel_forge settling window')
+root.geometry('300x200')
+colors = ['blue', 'green'] * 10 + ['red']
+def step(i=0):
+    root.configure(bg=colors[i])
+    if i + 1 < len(colors):
+        root.after(20, step, i + 1)
+root.after(1000, step)
+root.mainloop()
+"""
+
+
+def test_capture_max_fps_keeps_last_frame():
+    process = subprocess.Popen([sys.executable, "-c", SETTLING_WINDOW])
+    try:
+        window = Window("pixel_forge settling window", timeout_ms=5000)
+        c = Capture()
+        c.start(window, await_first_frame=True, max_fps=2)
+        # The final color arrives within the interval of a stored frame and must not be skipped
+        time.sleep(3)
+        frame = c.frame()
+        c.stop()
+        height, width = frame.shape[:2]
+        assert tuple(frame[height // 2, width // 2, :3]) == (255, 0, 0)
+    finally:
+        process.kill()
+        process.wait()
+
+
+def test_capture_has_new_frame(monitor: Monitor):
+    c = Capture()
+    assert not c.has_new_frame, "No frame should be available before start"
+    c.start(monitor, await_first_frame=True)
+    assert c.has_new_frame, "First frame should be unread"
+    c.frame()
+    assert not c.has_new_frame, "Frame should be marked as read"
+    c.stop()
+    assert not c.has_new_frame, "No frame should be available after stop"
+
+
+@pytest.mark.parametrize("flip_vertical", [False, True])
+def test_capture_dirty_regions(monitor: Monitor, flip_vertical: bool):
+    c = Capture()
+    assert c.dirty_regions is None, "No regions should be available before start"
+    c.start(monitor, await_first_frame=True, flip_vertical=flip_vertical)
+    frame = c.frame()
+    regions = c.dirty_regions
+    c.stop()
+    if regions is None:
+        return  # Windows build does not report changed regions
+    height, width = frame.shape[:2]
+    for x, y, w, h in regions:
+        assert w > 0 and h > 0, "Regions should not be empty"
+        assert x + w <= width and y + h <= height, "Regions should lie within the frame"
+
+
+def test_capture_frame_into(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    out = np.zeros((monitor.height, monitor.width, 4), dtype=np.uint8)
+    c.frame_into(out)
+    assert not c.has_new_frame, "frame_into should mark the frame as read"
+    with pytest.raises(RuntimeError):
+        c.frame_into(np.zeros((monitor.height, monitor.width + 1, 4), dtype=np.uint8))
+    c.stop()
+
+
+def test_capture_double_buffer(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(PixelForgeError):
+        c.configure_double_buffer((monitor.height, monitor.width, 3))
+    c.start(monitor, await_first_frame=True)
+    first, second = c.configure_double_buffer((monitor.height, monitor.width, 4))
+    frames = [c.frame() for _ in range(3)]
+    assert frames[0] is second and frames[1] is first and frames[2] is second
+    assert not c.has_new_frame, "Frames written into the buffers should be marked as read"
+    assert c.frame(layout="chw").shape == (4, monitor.height, monitor.width)
+    assert c.configure_double_buffer(None) is None
+    assert c.frame() is not first and c.frame() is not second
+    c.configure_double_buffer((1, 1, 4))
+    with pytest.raises(RuntimeError):
+        c.frame()
+    c.stop()
+
+
+def test_capture_frame_alpha(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    alpha = c.frame_alpha()
+    assert not c.has_new_frame, "frame_alpha should mark the frame as read"
+    c.stop()
+    assert alpha.dtype == np.uint8
+    assert alpha.shape == (monitor.height, monitor.width)
+    assert np.all(alpha == 255), "Monitors are opaque"
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba16Float)
+    with pytest.raises(RuntimeError):
+        c.frame_alpha()
+    c.stop()
+
+
+def test_capture_frame_rgb565(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    packed = c.frame_rgb565()
+    assert not c.has_new_frame, "frame_rgb565 should mark the frame as read"
+    c.stop()
+    assert packed.dtype == np.uint16
+    assert packed.shape == (monitor.height, monitor.width)
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba16Float)
+    with pytest.raises(RuntimeError):
+        c.frame_rgb565()
+    c.stop()
+
+
+def test_capture_shared_texture_handle(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(CaptureNotRunningError):
+        c.shared_texture_handle()
+    c.start(monitor, await_first_frame=True)
+    handle = c.shared_texture_handle()
+    assert isinstance(handle, int)
+    assert handle != 0
+    c.stop()
+
+
+def test_capture_queue(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert c.queue_depth == 0
+    assert c.try_recv() is None, "Frames are only queued with a queue depth"
+    c.stop()
+    c.start(monitor, await_first_frame=True, queue_depth=2)
+    assert c.queue_depth == 2
+    frame = c.recv(timeout_ms=1000)
+    assert frame is not None
+    assert frame.shape == (monitor.height, monitor.width, 4)
+    assert c.queued_frames <= 2
+    time.sleep(0.5)  # Let frames pile up
+    assert c.queued_frames <= 2, "The queue must not grow beyond its depth"
+    assert isinstance(c.queue_drop_count, int)
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.recv()
+
+
+def test_capture_frame_at_size(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    img = c.frame_at_size((224, 224))
+    assert img.shape == (224, 224, 4)
+    assert img.dtype == np.uint8
+    pad_color = (1, 2, 3, 4)
+    img = c.frame_at_size((224, 224), preserve_aspect=True, pad_color=pad_color)
+    assert img.shape == (224, 224, 4)
+    if monitor.width > monitor.height:  # Landscape monitors get bars at the top and bottom
+        assert (img[0] == pad_color).all()
+        assert (img[-1] == pad_color).all()
+    with pytest.raises(RuntimeError):
+        c.frame_at_size((0, 224))
+    c.stop()
+
+
+def test_capture_frame_pil(monitor: Monitor):
+    pytest.importorskip("PIL")
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    image = c.frame_pil()
+    assert image.mode == "RGBA"
+    assert image.size == (monitor.width, monitor.height)
+    assert c.frame_pil(alpha=False).mode == "RGB"
+    assert not c.has_new_frame, "frame_pil should mark the frame as read"
+    c.stop()
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba16Float)
+    with pytest.raises(RuntimeError):
+        c.frame_pil()
+    c.stop()
+
+
+def test_capture_closed():
+    process, window = open_resizing_window()
+    c = Capture()
+    closed = threading.Event()
+    c.start(window, await_first_frame=True, on_target_closed=closed.set)
+    assert not c.closed
+    process.kill()
+    process.wait()
+    assert closed.wait(timeout=5), "on_target_closed should be called"
+    assert c.closed
+    deadline = time.monotonic() + 5
+    while c.active and time.monotonic() < deadline:
+        time.sleep(0.05)
+    assert not c.active, "The capture thread should end with the closed target"
+    with pytest.raises(CaptureNotRunningError):
+        c.frame()
+    c.stop()
+    with pytest.raises(TypeError):
+        c.start(primary_monitor(), on_target_closed=1)
+
+
+def test_capture_closed_after_failed_restart(monitor: Monitor):
+    process, window = open_resizing_window()
+    c = Capture()
+    closed = threading.Event()
+    c.start(window, await_first_frame=True, on_target_closed=closed.set)
+    # A start that fails must keep the callback of the running capture
+    with pytest.raises(RuntimeError):
+        c.start(monitor, client_region=(0, 0, 10, 10))
+    with pytest.raises(RuntimeError):
+        c.start(window, on_target_closed=lambda: None)
+    assert c.active
+    process.kill()
+    process.wait()
+    assert closed.wait(timeout=5), "on_target_closed should still be called"
+    c.stop()
+
+
+# Visible window of a separate process without any area, like a rolled up window
+ZERO_SIZE_WINDOW = """
+import ctypes
+from ctypes import wintypes
+user32 = ctypes.windll.user32
+user32.CreateWindowExW.restype = wintypes.HWND
+WS_POPUP_VISIBLE = 0x90000000
+title = 'pixel_forge zero-size window'
+user32.CreateWindowExW(0, 'STATIC', title, WS_POPUP_VISIBLE, 0, 0, 0, 0, None, None, None, None)
+msg = wintypes.MSG()
+while user32.GetMessageW(ctypes.byref(msg), None, 0, 0) > 0:
+    user32.DispatchMessageW(ctypes.byref(msg))
+"""
+
+
+def test_capture_zero_size_window():
+    process = subprocess.Popen([sys.executable, "-c", ZERO_SIZE_WINDOW])
+    try:
+        window = Window("pixel_forge zero-size window", timeout_ms=5000)
+        c = Capture()
+        with pytest.raises(PixelForgeError):
+            c.start(window)
+        assert not c.active
+    finally:
+        process.kill()
+        process.wait()
+
+
+# Window of a separate process that is destroyed and recreated after a second, like a game that
+# switches to fullscreen
+RECREATED_WINDOW = """
+import tkinter
+root = tkinter.Tk()
+root.withdraw()
+def open_window():
+    window = tkinter.Toplevel(root)
+    window.title('pixel_forge recreated window')
+    window.geometry('400x300')
+    return window
+window = open_window()
+def recreate():
+    window.destroy()
+    open_window()
+root.after(1000, recreate)
+root.mainloop()
+"""
+
+
+def test_capture_sticky_window():
+    process = subprocess.Popen([sys.executable, "-c", RECREATED_WINDOW])
+    try:
+        deadline = time.monotonic() + 5
+        windows = []
+        while not windows and time.monotonic() < deadline:
+            windows = [w for w in enumerate_windows() if w.name == "pixel_forge recreated window"]
+            time.sleep(0.05)
+        window = windows[0]
+        class_name = window.class_name
+        c = Capture()
+        c.start(window, await_first_frame=True, sticky_window=True)
+        assert c.rebind_count == 0
+        deadline = time.monotonic() + 5
+        while c.rebind_count == 0 and time.monotonic() < deadline:
+            time.sleep(0.05)
+        assert c.rebind_count == 1, "The recreated window should replace the closed one"
+        assert not c.closed
+        assert c.target != window
+        assert c.target.class_name == class_name
+        assert c.wait_for_frame(timeout_ms=1000) is not None
+        c.stop()
+    finally:
+        process.kill()
+        process.wait()
+
+
+@pytest.mark.parametrize("try_stop", [False, True])
+def test_capture_stop_destroyed_target(try_stop: bool):
+    process, window = open_resizing_window()
+    c = Capture()
+    c.start(window, await_first_frame=True)
+    process.kill()
+    process.wait()
+    # The teardown of a destroyed target must never bring down the interpreter
+    if try_stop:
+        try:
+            c.try_stop()
+        except RuntimeError:
+            pass
+    else:
+        c.stop()
+    assert not c.active
+    c.stop()  # Stopping again is a no-op
+
+
+def test_capture_recent_frames(resizing_window: Window):
+    c = Capture()
+    c.start(resizing_window, await_first_frame=True)
+    assert c.recent_frames() == [], "No frames are kept without a history"
+    c.stop()
+    c.start(resizing_window, await_first_frame=True, history_size=3)
+    time.sleep(0.5)  # The window resizes every 50 ms, so the history fills up
+    frames = c.recent_frames()
+    assert 1 <= len(frames) <= 3
+    assert all(frame.ndim == 3 and frame.shape[2] == 4 for frame in frames)
+    assert len(c.recent_frames(1)) == 1
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.recent_frames()
+
+
+def test_capture_frame_averaged(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    with pytest.raises(FrameUnavailableError):
+        c.frame_averaged(2)  # No frames are kept without a history
+    c.stop()
+    c.start(monitor, await_first_frame=True, history_size=4)
+    averaged = c.frame_averaged(4)
+    assert averaged.dtype == np.uint8
+    assert averaged.shape == (monitor.height, monitor.width, 4)
+    assert c.frame_averaged(1).shape == averaged.shape
+    with pytest.raises(PixelForgeError):
+        c.frame_averaged(0)
+    c.stop()
+
+
+def test_capture_fixed_interval(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, fixed_interval_ms=50, max_fps=1)
+    produced = c.stats().produced
+    time.sleep(1.0)
+    # A static screen still yields a frame per tick, and max_fps does not limit the timer
+    assert c.stats().produced - produced >= 10
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.start(monitor, fixed_interval_ms=0)
+
+
+def test_capture_is_receiving(monitor: Monitor):
+    c = Capture()
+    assert not c.is_receiving()
+    c.start(monitor, await_first_frame=True)
+    assert c.is_receiving(), "The first frame has just arrived"
+    assert c.is_receiving(within_ms=60_000)
+    c.stop()
+    assert not c.is_receiving()
+    # The timer keeps storing frames of a static target
+    c.start(monitor, await_first_frame=True, fixed_interval_ms=20)
+    time.sleep(0.2)
+    assert c.is_receiving(within_ms=100)
+    c.stop()
+
+
+def test_capture_frame_async(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+
+    async def cancel_wait() -> None:
+        future = c.frame_async()
+        future.cancel()
+        with pytest.raises(asyncio.CancelledError):
+            await future
+
+    asyncio.run(cancel_wait())
+    c.stop()
+    assert not c.active, "Capture should not be running"
+
+
+def test_capture_frame_async_next_frame(monitor: Monitor, resizing_window: Window):
+    c = Capture()
+    # The resizing window keeps changing the content of the monitor
+    c.start(monitor, await_first_frame=True)
+    current = c.frame()
+
+    async def next_frame() -> np.ndarray:
+        return await asyncio.wait_for(c.frame_async(), timeout=5)
+
+    frame = asyncio.run(next_frame())
+    c.stop()
+    assert frame.shape == (monitor.height, monitor.width, 4)
+    assert not np.array_equal(frame, current), "The future resolves with a frame after the call"
+
+
+def test_capture_frame_view(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    view = c.frame_view()
+    assert view.shape == (monitor.height, monitor.width, 4)
+    frame = np.asarray(view)
+    assert frame.shape == (monitor.height, monitor.width, 4)
+    assert not frame.flags.writeable, "Frame views must be read-only"
+    c.stop()
+    assert frame.sum() >= 0, "View must stay valid after the capture is stopped"
+
+
+def test_capture_frame_view_dlpack(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    view = c.frame_view()
+    c.stop()
+    assert view.__dlpack_device__() == (1, 0), "Frames are mapped into CPU memory"
+    frame = np.from_dlpack(view)
+    assert frame.shape == (monitor.height, monitor.width, 4)
+    assert np.array_equal(frame, np.asarray(view))
+    del view
+    assert frame.sum() >= 0, "The tensor must keep the view alive"
+
+
+def test_capture_min_feature_level(monitor: Monitor):
+    c = Capture()
+    assert c.feature_level is None, "No device has been created yet"
+    c.start(monitor, await_first_frame=True, min_feature_level=FeatureLevel.Level11_0)
+    assert c.active, "Capture should be running"
+    assert c.feature_level in (FeatureLevel.Level11_0, FeatureLevel.Level11_1)
+    c.stop()
+    assert c.feature_level is not None, "The level of the last capture is kept"
+
+
+def test_capture_software_fallback(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, software_fallback=True)
+    frame = c.frame()
+    c.stop()
+    assert frame.shape == (monitor.height, monitor.width, 4)
+
+
+def test_capture_config(monitor: Monitor):
+    config = CaptureConfig(capture_target=monitor, max_fps=30).set(flip_vertical=True)
+    assert config.max_fps == 30 and config.flip_vertical and config.await_first_frame
+    config.color_format = ColorFormat.Rgba16Float
+    with pytest.raises(TypeError):
+        config.set(no_such_option=1)
+    with pytest.raises(TypeError):
+        config.set(max_fps="fast")
+    c = Capture()
+    c.start(config)
+    assert c.color_space == "scRGB"
+    c.stop()
+    # Keyword arguments override the options of the configuration
+    c.start(config, color_format=ColorFormat.Rgba8)
+    assert c.color_space == "sRGB"
+    c.stop()
+    with pytest.raises(PixelForgeError):
+        Capture().start(CaptureConfig())
+
+
+def test_capture_stats(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    c.frame()
+    stats = c.stats()
+    c.stop()
+    assert stats.produced >= 1, "At least one frame should have been produced"
+    assert stats.consumed == 1, "Exactly one frame should have been consumed"
+    assert stats.consumed + stats.dropped <= stats.produced
+
+
+def test_capture_ping(monitor: Monitor):
+    c = Capture()
+    assert not c.ping(timeout_ms=100), "Ping without a capture thread should fail"
+    c.start(monitor, await_first_frame=True)
+    assert c.ping(), "Running capture thread should respond to pings"
+    c.stop()
+    assert not c.ping(timeout_ms=100), "Ping of a stopped capture should fail"
+
+
+def test_capture_frame_bytes(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    data, width, height, stride = c.frame_bytes()
+    c.stop()
+    assert isinstance(data, bytes)
+    assert (width, height) == (monitor.width, monitor.height)
+    assert stride >= 4 * width
+    assert len(data) == height * stride
+
+
+def test_capture_virtual_desktop():
+    desktop = VirtualDesktop()
+    c = Capture()
+    c.start(desktop, await_first_frame=True)
+    frame = c.frame()
+    c.stop()
+    assert frame.shape == (desktop.height, desktop.width, 4)
+
+
+def test_capture_size(monitor: Monitor):
+    assert capture_size(monitor) == (monitor.width, monitor.height)
+    desktop = VirtualDesktop()
+    assert capture_size(desktop) == (desktop.width, desktop.height)
+
+
+def test_supported_formats(monitor: Monitor):
+    formats = supported_formats(monitor)
+    assert ColorFormat.Rgba8 in formats
+    for color_format in formats:
+        c = Capture()
+        c.start(monitor, await_first_frame=True, color_format=color_format)
+        c.stop()
+
+
+def test_capture_resize_count(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert c.resize_count == 0, "Monitors should not resize during the capture"
+    c.stop()
+
+
+def test_capture_monitor_change_count(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert c.monitor_change_count == 0, "Only window targets change their monitor"
+    c.stop()
+
+
+def test_capture_frame_size(monitor: Monitor):
+    c = Capture()
+    assert c.width is None and c.height is None, "No frame should be available before start"
+    c.start(monitor, await_first_frame=True)
+    assert (c.width, c.height) == (monitor.width, monitor.height)
+    c.stop()
+    assert c.width is None and c.height is None, "No frame should be available after stop"
+
+
+def test_capture_group(monitor: Monitor):
+    desktop = VirtualDesktop()
+    group = CaptureGroup([monitor, desktop])
+    assert len(group) == 2
+    group.start(await_first_frame=True)
+    assert group.active
+    frames = group.frames()
+    assert frames[monitor].shape == (monitor.height, monitor.width, 4)
+    assert frames[desktop].shape == (desktop.height, desktop.width, 4)
+    group.stop()
+    assert not group.active
+
+
+def test_capture_wait_for_frame(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    img = c.wait_for_frame(timeout_ms=1000)
+    assert img is not None, "The first frame has not been read yet and should be returned"
+    assert img.shape == (monitor.height, monitor.width, 4)
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.wait_for_frame(timeout_ms=0)
+
+
+def test_capture_stop_unblocks_waiter(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, max_fps=1)
+    c.frame()  # Mark the first frame as read so the waiter blocks until the next one
+    results = []
+    waiter = threading.Thread(target=lambda: results.append(c.wait_for_frame()))
+    waiter.start()
+    time.sleep(0.1)
+    c.stop()
+    waiter.join(timeout=5)
+    assert not waiter.is_alive(), "Stopping the capture must unblock the waiting thread"
+    assert results == [None]
+    assert not c.active
+
+
+def test_capture_share(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    handles = [c.share() for _ in range(2)]
+    assert all(handle.capture is c and handle.active for handle in handles)
+    # Handles track their frames independently, so each of them receives the first frame
+    for handle in handles:
+        img = handle.wait_for_frame(timeout_ms=1000)
+        assert img is not None
+        assert img.shape == (monitor.height, monitor.width, 4)
+    assert c.wait_for_frame(timeout_ms=1000) is not None
+
+    results = []
+    waiters = [
+        threading.Thread(target=lambda h=handle: results.append(h.wait_for_frame()))
+        for handle in handles
+    ]
+    for waiter in waiters:
+        waiter.start()
+    time.sleep(0.1)
+    c.stop()
+    for waiter in waiters:
+        waiter.join(timeout=5)
+    assert not any(waiter.is_alive() for waiter in waiters)
+    assert not any(handle.active for handle in handles)
+    with pytest.raises(RuntimeError):
+        handles[0].frame()
+
+
+def test_capture_frame_notification_latency(monitor: Monitor):
+    c = Capture()
+    # The timer stores a new frame every 20ms, even while the monitor is static
+    c.start(monitor, await_first_frame=True, fixed_interval_ms=20)
+    latencies = []
+    for _ in range(10):
+        # Reading the latest frame makes the wait block on the condition variable for the next one
+        t0 = time.perf_counter()
+        c.frame()
+        conversion_ms = (time.perf_counter() - t0) * 1000
+        assert c.wait_for_frame(timeout_ms=1000) is not None
+        # The age of the frame right after the wait is the wake-up latency plus the conversion
+        latencies.append(c.frame_age_ms() - conversion_ms)
+    c.stop()
+    # Polling added up to 10ms. The median ignores scheduling hiccups of single waits
+    assert sorted(latencies)[len(latencies) // 2] < 5
+
+
+def test_grab(monitor: Monitor):
+    img = grab(monitor, timeout_ms=5000)
+    assert img.shape == (monitor.height, monitor.width, 4)
+
+
+def test_grab_monitors():
+    frames = grab_monitors(timeout_ms=5000)
+    assert [monitor for monitor, _ in frames] == enumerate_monitors()
+    for monitor, img in frames:
+        assert img.shape == (monitor.height, monitor.width, 4)
+        assert img.dtype == np.uint8
+
+
+def test_capture_when_available():
+    process = subprocess.Popen([sys.executable, "-c", RESIZING_WINDOW])
+    try:
+        c = capture_when_available("pixel_forge resizing window", timeout_ms=5000)
+        assert c.active
+        assert c.target.name == "pixel_forge resizing window"
+        c.stop()
+    finally:
+        process.kill()
+        process.wait()
+    with pytest.raises(WindowNotFoundError):
+        capture_when_available("pixel_forge nonexistent window", timeout_ms=100)
+
+
+def test_screenshot_foreground():
+    img = screenshot_foreground(timeout_ms=5000)
+    assert img.ndim == 3
+    assert img.shape[2] == 4
+
+
+def test_capture_snapshot(monitor: Monitor):
+    img = Capture.snapshot(monitor, timeout_ms=5000)
+    assert img.shape == (monitor.height, monitor.width, 4)
+
+
+def test_capture_frame_contiguous(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    img = c.frame_contiguous()
+    c.stop()
+    assert img.shape == (monitor.height, monitor.width, 4)
+    assert img.flags["C_CONTIGUOUS"]
+    assert img.strides == (monitor.width * 4, 4, 1)
+
+
+def test_capture_verify_checksums(monitor: Monitor):
+    c = Capture()
+    assert not c.verify_checksums
+    c.start(monitor, await_first_frame=True)
+    c.frame()
+    assert c.last_checksum is None, "Checksums are disabled by default"
+    c.verify_checksums = True
+    img = c.frame_contiguous()
+    assert c.last_checksum == zlib.crc32(img.tobytes())
+    for _ in range(10):
+        c.frame()
+    assert c.checksum_mismatches == 0
+    c.verify_checksums = False
+    assert c.last_checksum is None
+    c.stop()
+
+
+def test_capture_target_in_constructor(monitor: Monitor):
+    c = Capture(monitor)
+    c.start(await_first_frame=True)
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+    with pytest.raises(RuntimeError):
+        Capture().start()
+
+
+def test_capture_color_format(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba16Float)
+    img = c.frame()
+    assert img.dtype == np.float16
+    assert img.shape == (monitor.height, monitor.width, 4)
+    assert c.frame_contiguous().dtype == np.float16
+    assert np.asarray(c.frame_view()).dtype == np.float16
+    c.stop()
+    c.start(monitor, await_first_frame=True, color_format=ColorFormat.Rgba8)
+    assert c.frame().dtype == np.uint8
+    c.stop()
+
+
+@pytest.mark.parametrize("buffer_count", [1, 2, 3])
+def test_capture_buffer_count(monitor: Monitor, buffer_count: int):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, buffer_count=buffer_count)
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+
+
+def test_capture_frames_iterator(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    for n, img in enumerate(c.frames(timeout_ms=1000)):
+        assert img.shape == (monitor.height, monitor.width, 4)
+        if n == 2:
+            break
+    c.stop()
+    assert list(c.frames()) == [], "Iteration should stop immediately once the capture is stopped"
+
+
+def test_capture_invalid_target():
+    tkinter = pytest.importorskip("tkinter")
+    root = tkinter.Tk()
+    root.title("pixel_forge invalid target")
+    root.update()
+    window = Window("pixel_forge invalid target")
+    root.destroy()
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.start(window)
+    assert not c.active
+
+
+def test_is_supported():
+    assert is_supported()
+
+
+def test_diagnostics():
+    info = diagnostics()
+    assert info.graphics_capture_supported == is_supported()
+    assert isinstance(info.feature_level, FeatureLevel)
+    assert isinstance(info.adapter_name, str) and info.adapter_name
+    assert info.os_build is not None and info.os_build >= 17134, "Capture requires Windows 1803"
+    assert isinstance(info.cursor_toggle_supported, bool)
+    assert isinstance(info.border_toggle_supported, bool)
+    assert "Diagnostics(" in repr(info)
+
+
+def test_windows_build():
+    build = windows_build()
+    assert build.major == 10, "Windows 11 still reports major version 10"
+    assert build.build == diagnostics().os_build
+    assert build.graphics_capture_supported == is_supported()
+    assert build.cursor_toggle_supported == supports_cursor_toggle()
+    assert build.border_toggle_supported == supports_border_toggle()
+    if build.min_update_interval_supported:
+        assert build.build >= 26100, "The minimum update interval requires Windows 11 24H2"
+    if build.secondary_windows_supported:
+        assert build.build >= 22000, "Secondary windows require Windows 11"
+    assert "WindowsBuild(" in repr(build)
+
+
+def test_supports_toggles():
+    info = diagnostics()
+    assert supports_cursor_toggle() == info.cursor_toggle_supported
+    assert supports_border_toggle() == info.border_toggle_supported
+    if supports_border_toggle():
+        assert supports_cursor_toggle(), "Builds with the border toggle also have the cursor toggle"
+
+
+def test_capture_adapter_index(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, adapter_index=0)
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.start(monitor, adapter_index=1000)
+    assert not c.active
+
+
+def test_capture_device_flags(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True, device_flags=0x20 | 0x800)  # BGRA and video support
+    assert c.frame().shape == (monitor.height, monitor.width, 4)
+    c.stop()
+    with pytest.raises(RuntimeError):
+        c.start(monitor, device_flags=0x2)  # Capturing requires BGRA support
+    assert not c.active
+
+
+def test_capture_frame_info(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.frame_info()
+    c.start(monitor, await_first_frame=True)
+    width, height, stride, channels = c.frame_info()
+    assert (width, height, channels) == (monitor.width, monitor.height, 4)
+    assert stride >= width * channels
+    assert c.has_new_frame, "frame_info should not mark the frame as read"
+    c.stop()
+
+
+def test_capture_frame_hash(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.frame_hash()
+    c.start(monitor, await_first_frame=True)
+    frame_hash = c.frame_hash()
+    assert isinstance(frame_hash, int)
+    assert 0 <= frame_hash < 2**64
+    assert c.has_new_frame, "frame_hash should not mark the frame as read"
+    c.stop()
+
+
+def test_capture_frames_differ(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.frames_differ()
+    c.start(monitor, await_first_frame=True)
+    assert c.frames_differ(), "The first comparison always differs"
+    assert not c.frames_differ(threshold=1.0), "No fraction of pixels exceeds 1"
+    assert c.frames_differ(downsample=4), "Samples of another size count as changed"
+    assert c.has_new_frame, "frames_differ should not mark the frame as read"
+    with pytest.raises(RuntimeError):
+        c.frames_differ(threshold=1.5)
+    with pytest.raises(RuntimeError):
+        c.frames_differ(downsample=0)
+    c.stop()
+
+
+def test_capture_frame_changed(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    assert not c.frame_changed, "No frame has been read yet"
+    c.frame()
+    assert c.frame_changed, "The first read frame always counts as changed"
+    c.stop()
+
+
+def test_capture_frame_array_view(monitor: Monitor):
+    c = Capture()
+    c.start(monitor, await_first_frame=True)
+    img = c.frame_array_view()
+    assert img.shape == (monitor.height, monitor.width, 4)
+    assert not img.flags["WRITEABLE"]
+    assert not img.flags["OWNDATA"], "The array should reference the mapped frame memory"
+    c.stop()
+    assert img.shape == (monitor.height, monitor.width, 4), "Arrays outlive the capture"
+
+
+def test_capture_frame_age(monitor: Monitor):
+    c = Capture()
+    with pytest.raises(RuntimeError):
+        c.frame_age_ms()
+    c.start(monitor, await_first_frame=True)
+    assert c.frame_age_ms() >= 0
+    c.stop()
+
+
+def test_capture_with_null_device():
+    with pytest.raises(RuntimeError):
+        Capture.with_device(0)
@@ -1,4 +1,18 @@
-from pixel_forge import Monitor, enumerate_monitors, primary_monitor
+import asyncio
+
+import pytest
+
+from pixel_forge import (
+    Monitor,
+    MonitorNotFoundError,
+    enumerate_display_devices,
+    enumerate_monitors,
+    enumerate_monitors_async,
+    monitor_count,
+    on_display_change,
+    primary_monitor,
+    set_process_dpi_aware,
+)
 
 
 def test_monitor_init():
@@ -8,6 +22,13 @@ def test_monitor_init():
     assert isinstance(m, Monitor)
 
 
+def test_monitor_from_hmonitor():
+    monitor = Monitor()
+    assert Monitor.from_hmonitor(hash(monitor)) == monitor
+    with pytest.raises(RuntimeError):
+        Monitor.from_hmonitor(0)
+
+
 def test_primary_monitor():
     monitor = primary_monitor()
     assert isinstance(monitor, Monitor)
@@ -21,11 +42,73 @@ def test_enumerate_monitors():
         assert isinstance(monitor, Monitor)
 
 
+def test_monitor_count():
+    assert monitor_count() == len(enumerate_monitors())
+
+
+def test_monitor_contains_point():
+    monitor = primary_monitor()
+    # The primary monitor has its top left corner at the origin of the virtual desktop
+    assert monitor.contains_point(0, 0)
+    assert not monitor.contains_point(-1, -1)
+    assert sum(m.contains_point(0, 0) for m in enumerate_monitors()) == 1
+
+
+def test_enumerate_monitors_order():
+    for n, monitor in enumerate(enumerate_monitors()):
+        assert monitor.index == n + 1
+        assert Monitor(n + 1) == monitor
+
+
+def test_monitor_out_of_range():
+    with pytest.raises(MonitorNotFoundError):
+        Monitor(10_000)
+    # Handlers written before the dedicated exception classes keep catching the error
+    with pytest.raises(RuntimeError) as excinfo:
+        Monitor(monitor_count() + 1)
+    assert excinfo.type is MonitorNotFoundError
+
+
 def test_monitor_properties():
     monitor = Monitor()
+    assert monitor.valid
     assert monitor.width > 0
     assert monitor.height > 0
     assert monitor.index >= 0
     assert monitor.refresh_rate > 0
+    assert abs(monitor.refresh_rate_precise - monitor.refresh_rate) < 1
+    assert monitor.bits_per_pixel > 0
+    assert monitor.orientation in (0, 90, 180, 270)
     assert isinstance(monitor.device_name, str)
     assert isinstance(monitor.device_string, str)
+    assert isinstance(monitor.friendly_name, str)
+    assert isinstance(monitor.adapter_name, str)
+
+
+def test_on_display_change():
+    listener = on_display_change(lambda: None)
+    assert listener.active
+    listener.stop()
+    assert not listener.active
+    with pytest.raises(TypeError):
+        on_display_change(1)
+
+
+def test_enumerate_monitors_async():
+    async def scan():
+        return await enumerate_monitors_async()
+
+    assert asyncio.run(scan()) == enumerate_monitors()
+
+
+def test_set_process_dpi_aware():
+    assert isinstance(set_process_dpi_aware(), bool)
+    assert not set_process_dpi_aware(), "The DPI awareness can only be set once"
+
+
+def test_enumerate_display_devices():
+    devices = enumerate_display_devices()
+    active = {device.device_name for device in devices if device.active}
+    assert active == {monitor.device_name for monitor in enumerate_monitors()}
+    assert sum(device.primary for device in devices) == 1
+    assert primary_monitor().device_name in active
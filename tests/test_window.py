@@ -1,4 +1,49 @@
-from pixel_forge import Window, enumerate_windows
+import asyncio
+import subprocess
+import sys
+import time
+
+import pytest
+
+from pixel_forge import (
+    Capture,
+    Monitor,
+    PixelForgeError,
+    Window,
+    WindowNotFoundError,
+    enumerate_top_level_windows,
+    enumerate_monitors,
+    enumerate_processes_with_windows,
+    enumerate_windows,
+    enumerate_windows_async,
+    enumerate_windows_on_monitor,
+    foreground_window,
+    wallpaper_window,
+)
+
+# Windows of the own process are never valid capture targets, so the untitled window has to be
+# created by a separate process
+UNTITLED_WINDOW = "import tkinter; root = tkinter.Tk(); root.title(''); root.mainloop()"
+RENAMING_WINDOW = (
+    "import tkinter; root = tkinter.Tk(); root.title('pixel_forge-rename');"
+    "root.after(1000, lambda: root.title('pixel_forge-renamed')); root.mainloop()"
+)
+# Tk widgets are native child windows
+CHILD_WINDOW = (
+    "import tkinter; root = tkinter.Tk(); root.title('pixel_forge-child');"
+    "tkinter.Button(root, text='child', width=20, height=5).pack(); root.mainloop()"
+)
+
+
+@pytest.fixture
+def untitled_window():
+    process = subprocess.Popen([sys.executable, "-c", UNTITLED_WINDOW])
+    deadline = time.monotonic() + 5
+    while not any(w.name == "" for w in enumerate_windows()) and time.monotonic() < deadline:
+        time.sleep(0.1)
+    yield
+    process.kill()
+    process.wait()
 
 
 def test_enumerate_windows():
@@ -8,7 +53,187 @@ def test_enumerate_windows():
         assert isinstance(window, Window)
 
 
+def test_enumerate_windows_on_monitor():
+    windows = set(enumerate_windows())
+    monitors = enumerate_monitors()
+    on_monitors = [set(enumerate_windows_on_monitor(monitor)) for monitor in monitors]
+    for monitor, monitor_windows in zip(monitors, on_monitors):
+        assert monitor_windows <= windows
+        assert all(window.monitor() == monitor for window in monitor_windows)
+    # Windows belong to at most one monitor
+    assert sum(len(w) for w in on_monitors) == len(set().union(*on_monitors))
+
+
+def test_enumerate_windows_z_order():
+    windows = enumerate_windows(z_order=True)
+    assert len(windows) > 0
+    assert all(window.valid for window in windows)
+    foreground = foreground_window()
+    if foreground in windows:
+        # Only topmost windows like the taskbar can be in front of the foreground window
+        assert windows.index(foreground) < 10
+
+
+def test_enumerate_windows_async():
+    async def scan():
+        return await enumerate_windows_async(skip_untitled=True)
+
+    windows = asyncio.run(scan())
+    assert len(windows) > 0
+    assert all(isinstance(w, Window) and w.name != "" for w in windows)
+
+
+def test_enumerate_top_level_windows():
+    windows = enumerate_top_level_windows()
+    assert len(windows) > 0
+    assert all(window.valid for window in windows)
+    # Both enumerations visit every top-level window, but possibly in a different order
+    assert set(windows) == set(enumerate_windows())
+
+
+def test_enumerate_processes_with_windows():
+    processes = enumerate_processes_with_windows()
+    assert len(processes) > 0
+    pids = [pid for pid, _, _ in processes]
+    assert pids == sorted(set(pids)), "Processes should be listed once, sorted by ID"
+    for _, name, count in processes:
+        assert count > 0
+        assert name is None or (name and "\\" not in name), "Names should not contain the path"
+
+
+def test_enumerate_windows_skip_untitled(untitled_window):
+    assert any(window.name == "" for window in enumerate_windows())
+    windows = enumerate_windows(skip_untitled=True)
+    assert all(window.name != "" for window in windows)
+
+
 def test_window_properties():
     window = enumerate_windows()[0]
     assert window.valid
     assert isinstance(window.name, str)
+    assert window.is_visible, "Valid windows are always visible"
+    assert isinstance(window.is_minimized, bool)
+    assert isinstance(window.is_foreground, bool)
+    assert isinstance(window.is_protected, bool)
+    _, _, width, height = window.client_rect
+    assert width >= 0 and height >= 0
+    _, _, window_width, window_height = window.window_rect
+    assert window_width >= width and window_height >= height
+    assert window.monitor() is None or isinstance(window.monitor(), Monitor)
+    assert window.class_name != ""
+    x, y = window.center()
+    assert isinstance(x, int) and isinstance(y, int)
+    assert window.process_id > 0
+
+
+def test_window_child_windows():
+    process = subprocess.Popen([sys.executable, "-c", CHILD_WINDOW])
+    try:
+        window = Window("pixel_forge-child", timeout_ms=5000)
+        children = window.child_windows()
+        assert len(children) > 0
+        assert all(not child.valid for child in children), "Child windows are not top-level"
+        child = min(children, key=lambda child: child.client_rect[2] * child.client_rect[3])
+        _, _, width, height = child.client_rect
+        c = Capture()
+        c.start(child, await_first_frame=True)
+        frame = c.frame()
+        c.stop()
+        assert frame.shape == (height, width, 4), "Frames should be cropped to the child window"
+    finally:
+        process.kill()
+        process.wait()
+
+
+def test_window_child_window_readers():
+    process = subprocess.Popen([sys.executable, "-c", CHILD_WINDOW])
+    try:
+        window = Window("pixel_forge-child", timeout_ms=5000)
+        children = window.child_windows()
+        child = min(children, key=lambda child: child.client_rect[2] * child.client_rect[3])
+        _, _, width, height = child.client_rect
+        c = Capture()
+        # The timer repeats frames of the static window, so frame_async receives a new one
+        c.start(child, await_first_frame=True, fixed_interval_ms=50)
+        data = c.read()
+        assert data.array.shape == (height, width, 4), "read should crop to the child window"
+        assert (data.width, data.height) == (width, height)
+        assert c.read(auto_crop=True).array.shape[0] <= height
+        assert c.frame_contiguous().shape == (height, width, 4)
+        data, bytes_width, bytes_height, stride = c.frame_bytes()
+        assert (bytes_width, bytes_height, stride) == (width, height, 4 * width)
+        assert len(data) == stride * height
+
+        async def next_frame():
+            return await asyncio.wait_for(c.frame_async(), timeout=5)
+
+        assert asyncio.run(next_frame()).shape == (height, width, 4)
+        c.stop()
+    finally:
+        process.kill()
+        process.wait()
+
+
+def test_window_from_hwnd():
+    window = enumerate_windows()[0]
+    assert Window.from_hwnd(hash(window)) == window
+    with pytest.raises(RuntimeError):
+        Window.from_hwnd(0)
+
+
+def test_window_timeout():
+    start = time.monotonic()
+    with pytest.raises(RuntimeError):
+        Window("pixel_forge-missing", timeout_ms=200)
+    assert time.monotonic() - start >= 0.2
+    with pytest.raises(RuntimeError):
+        Window("pixel_forge-missing")
+
+
+def test_window_not_found_error():
+    with pytest.raises(WindowNotFoundError):
+        Window("pixel_forge-missing")
+    assert issubclass(WindowNotFoundError, PixelForgeError)
+    assert issubclass(PixelForgeError, RuntimeError), "Errors must stay catchable as RuntimeError"
+
+
+def test_window_is_foreground():
+    assert foreground_window().is_foreground
+
+
+def test_window_activate():
+    window = foreground_window()
+    window.activate()
+    assert window.is_foreground
+
+
+def test_wallpaper_window():
+    window = wallpaper_window()
+    assert isinstance(window, Window)
+    _, _, width, height = window.client_rect
+    assert width > 0 and height > 0
+
+
+def test_window_on_title_change():
+    process = subprocess.Popen([sys.executable, "-c", RENAMING_WINDOW])
+    try:
+        deadline = time.monotonic() + 5
+        windows = []
+        while not windows and time.monotonic() < deadline:
+            windows = [w for w in enumerate_windows() if w.name == "pixel_forge-rename"]
+            time.sleep(0.05)
+        assert windows, "Renaming window did not open"
+        titles = []
+        listener = windows[0].on_title_change(titles.append)
+        assert listener.active
+        deadline = time.monotonic() + 5
+        while "pixel_forge-renamed" not in titles and time.monotonic() < deadline:
+            time.sleep(0.1)
+        assert "pixel_forge-renamed" in titles
+        listener.stop()
+        assert not listener.active
+        with pytest.raises(TypeError):
+            windows[0].on_title_change(1)
+    finally:
+        process.kill()
+        process.wait()